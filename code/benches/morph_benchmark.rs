@@ -1,19 +1,17 @@
-use code::objects::model3d::Model3D;
+use code::objects::model3d::{Model3D, Rotate, Scale};
+use code::objects::morph::Morph;
 use code::objects::triangle_mesh::TriangleMesh;
 use code::utils::morphing::{
-    create_supermesh, find_normals, parametrize_mesh, relocate_vertices_on_mesh,
+    MorphParams, create_supermesh, find_normals, parametrize_mesh, relocate_vertices_on_mesh,
 };
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{Criterion, criterion_group, criterion_main};
 use std::hint::black_box;
 use std::path::Path; // Импортируем Path
 
 const INPUTS: &[(&str, &str)] = &[
     ("models/apple2.obj", "models/pear.obj"),
     ("models/apple2.obj", "models/lemon.obj"),
-    ("models/apple2.obj", "models/banana.obj"),
-    ("models/lemon.obj", "models/banana.obj"),
     ("models/lemon.obj", "models/pear.obj"),
-    ("models/banana.obj", "models/pear.obj"),
 ];
 
 fn morph_stages_benchmark(c: &mut Criterion) {
@@ -38,6 +36,7 @@ fn morph_stages_benchmark(c: &mut Criterion) {
         // --- Подготовка данных ---
         let source = TriangleMesh::from_obj(source_path).expect("Failed to load source");
         let target = TriangleMesh::from_obj(target_path).expect("Failed to load target");
+        let params = MorphParams::default();
 
         // --- Этап 1: Параметризация ---
         // Используем iter_with_setup для более точных измерений
@@ -45,39 +44,88 @@ fn morph_stages_benchmark(c: &mut Criterion) {
             b.iter_with_setup(
                 || (source.clone(), target.clone()),
                 |(mut s, mut t)| {
-                    parametrize_mesh(black_box(&mut s));
-                    parametrize_mesh(black_box(&mut t));
+                    parametrize_mesh(black_box(&mut s), &params).unwrap();
+                    parametrize_mesh(black_box(&mut t), &params).unwrap();
                 },
             )
         });
 
         // "По-настоящему" выполняем шаги для передачи данных дальше
         let mut parametrized_source_mesh = source.clone();
-        parametrize_mesh(&mut parametrized_source_mesh);
+        parametrize_mesh(&mut parametrized_source_mesh, &params).unwrap();
         let mut parametrized_target_mesh = target.clone();
-        parametrize_mesh(&mut parametrized_target_mesh);
+        parametrize_mesh(&mut parametrized_target_mesh, &params).unwrap();
 
         // --- Этап 2: Построение суперсетки ---
         group.bench_function("Построение суперсетки", |b| {
-            b.iter(|| black_box(create_supermesh(&parametrized_source_mesh, &parametrized_target_mesh).unwrap()))
+            b.iter(|| {
+                black_box(
+                    create_supermesh(
+                        &parametrized_source_mesh,
+                        &parametrized_target_mesh,
+                        &params,
+                    )
+                    .unwrap(),
+                )
+            })
         });
 
-        let (vertices, triangles) =
-            create_supermesh(&parametrized_source_mesh, &parametrized_target_mesh).unwrap();
+        let (vertices, _, _) = create_supermesh(
+            &parametrized_source_mesh,
+            &parametrized_target_mesh,
+            &params,
+        )
+        .unwrap();
 
         // --- Этап 3: Перенос вершин ---
         group.bench_function("Перенос вершин", |b| {
             b.iter(|| {
-                black_box(relocate_vertices_on_mesh(&vertices, &parametrized_source_mesh, source.vertices_world()).unwrap());
-                black_box(relocate_vertices_on_mesh(&vertices, &parametrized_target_mesh, target.vertices_world()).unwrap());
+                black_box(
+                    relocate_vertices_on_mesh(
+                        &vertices,
+                        &parametrized_source_mesh,
+                        source.vertices_world(),
+                        &params,
+                    )
+                    .unwrap(),
+                );
+                black_box(
+                    relocate_vertices_on_mesh(
+                        &vertices,
+                        &parametrized_target_mesh,
+                        target.vertices_world(),
+                        &params,
+                    )
+                    .unwrap(),
+                );
             })
         });
 
         // --- Этап 4: Перенос нормалей ---
+        let (_, src_locations) = relocate_vertices_on_mesh(
+            &vertices,
+            &parametrized_source_mesh,
+            source.vertices_world(),
+            &params,
+        )
+        .unwrap();
+        let (_, dst_locations) = relocate_vertices_on_mesh(
+            &vertices,
+            &parametrized_target_mesh,
+            target.vertices_world(),
+            &params,
+        )
+        .unwrap();
         group.bench_function("Перенос нормалей", |b| {
             b.iter(|| {
-                black_box(find_normals(&vertices, &triangles, &parametrized_source_mesh, source.normals()).unwrap());
-                black_box(find_normals(&vertices, &triangles, &parametrized_target_mesh, target.normals()).unwrap());
+                black_box(find_normals(
+                    &src_locations,
+                    &source.corner_normals(params.smoothing_angle),
+                ));
+                black_box(find_normals(
+                    &dst_locations,
+                    &target.corner_normals(params.smoothing_angle),
+                ));
             })
         });
 
@@ -85,5 +133,19 @@ fn morph_stages_benchmark(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, morph_stages_benchmark);
-criterion_main!(benches);
\ No newline at end of file
+/// Поворот и масштабирование построенного морфинга, как при перетаскивании мышью
+fn morph_drag_benchmark(c: &mut Criterion) {
+    let source = TriangleMesh::from_obj("models/apple2.obj").expect("Failed to load source");
+    let target = TriangleMesh::from_obj("models/pear.obj").expect("Failed to load target");
+    let mut morph = Morph::new(source, target).expect("Failed to build morph");
+
+    c.bench_function("Morph drag", |b| {
+        b.iter(|| {
+            morph.rotate(black_box((0.01, 0.02, 0.)));
+            morph.scale(black_box(1.001));
+        })
+    });
+}
+
+criterion_group!(benches, morph_stages_benchmark, morph_drag_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use code::objects::model3d::Model3D;
+use code::objects::triangle_mesh::TriangleMesh;
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::fmt::Write;
+use std::hint::black_box;
+
+/// Число разбиений сферы: около 330 тысяч треугольников
+const SUBDIVISIONS: usize = 7;
+
+/// Записывает подразбитую сферу во временный .obj файл
+fn write_large_obj() -> String {
+    let sphere = TriangleMesh::unit_sphere(SUBDIVISIONS);
+    let mut obj = String::new();
+    for v in sphere.vertices() {
+        writeln!(obj, "v {} {} {}", v.x, v.y, v.z).unwrap();
+    }
+    for n in sphere.normals() {
+        writeln!(obj, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+    }
+    for (i, &(a, b, c)) in sphere.triangles().iter().enumerate() {
+        writeln!(
+            obj,
+            "f {}//{} {}//{} {}//{}",
+            a + 1,
+            i + 1,
+            b + 1,
+            i + 1,
+            c + 1,
+            i + 1
+        )
+        .unwrap();
+    }
+
+    let path = std::env::temp_dir().join("obj_benchmark_sphere.obj");
+    std::fs::write(&path, obj).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn obj_load_benchmark(c: &mut Criterion) {
+    let path = write_large_obj();
+    let mut group = c.benchmark_group("OBJ");
+    group.sample_size(10);
+
+    group.bench_function("Загрузка сферы", |b| {
+        b.iter(|| black_box(TriangleMesh::from_obj(black_box(&path)).unwrap()))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, obj_load_benchmark);
+criterion_main!(benches);
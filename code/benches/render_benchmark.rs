@@ -0,0 +1,99 @@
+use code::objects::model3d::{InteractiveModel, Triangle};
+use code::objects::triangle_mesh::TriangleMesh;
+use code::render::Renderer;
+use code::render::z_buffer::ZBufferPerformer;
+use code::scene::Scene;
+use code::utils::math::transform_homogeneous;
+use criterion::{Criterion, criterion_group, criterion_main};
+use nalgebra::{Matrix4, Point3, Vector3, Vector4};
+use std::cell::RefCell;
+use std::hint::black_box;
+use std::rc::Rc;
+
+const SIZE: u32 = 512;
+const LAYERS: usize = 64;
+const PROJECTED_VERTICES: usize = 100_000;
+
+/// Стопка квадратов, перекрывающих друг друга, перечисленных от дальнего к ближнему:
+/// худший случай для отрисовки без сортировки
+fn overdraw_scene() -> Scene {
+    let mut vertices = Vec::new();
+    let mut triangles: Vec<Triangle> = Vec::new();
+    for layer in 0..LAYERS {
+        let z = -0.5 + layer as f64 / LAYERS as f64;
+        let base = vertices.len();
+        vertices.extend([
+            Point3::new(-1., -1., z),
+            Point3::new(1., -1., z),
+            Point3::new(1., 1., z),
+            Point3::new(-1., 1., z),
+        ]);
+        triangles.push((base, base + 1, base + 2));
+        triangles.push((base, base + 2, base + 3));
+    }
+
+    let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(
+        TriangleMesh::from_parts(vertices, triangles, None),
+    ));
+    let mut scene = Scene::new(1.);
+    scene.object = Some(object);
+    scene
+}
+
+fn overdraw_benchmark(c: &mut Criterion) {
+    let scene = overdraw_scene();
+    let mut renderers =
+        [("back-to-front", false), ("front-to-back", true)].map(|(name, depth_sorting)| {
+            let mut renderer = ZBufferPerformer::new(SIZE, SIZE);
+            renderer.set_depth_sorting(depth_sorting);
+            renderer.create_frame(SIZE, SIZE, &scene);
+            (name, renderer)
+        });
+
+    // Сортировка от ближнего к дальнему должна отсекать перекрытые фрагменты до закраски
+    let [unsorted, sorted] = renderers.each_ref().map(|(_, r)| r.shaded_fragments());
+    assert!(
+        sorted < unsorted,
+        "front-to-back: {sorted}, back-to-front: {unsorted}"
+    );
+
+    let mut group = c.benchmark_group("Overdraw");
+    for (name, renderer) in &mut renderers {
+        group.bench_function(*name, |b| {
+            b.iter(|| black_box(renderer.create_frame(SIZE, SIZE, black_box(&scene))))
+        });
+    }
+
+    group.finish();
+}
+
+/// Проекция вершин большой сетки: по одной вершине и блоками одним произведением матриц
+fn projection_benchmark(c: &mut Criterion) {
+    let matrix = Matrix4::new_perspective(1., 1., 0.1, 100.)
+        * Matrix4::new_translation(&Vector3::new(0., 0., -5.));
+    let vertices: Vec<Point3<f64>> = (0..PROJECTED_VERTICES)
+        .map(|i| {
+            let t = i as f64 * 0.01;
+            Point3::new(t.cos(), t.sin(), (t * 0.1).cos())
+        })
+        .collect();
+    let mut group = c.benchmark_group("Projection 100k");
+
+    group.bench_function("per-vertex", |b| {
+        b.iter(|| {
+            let clip: Vec<Vector4<f64>> = black_box(&vertices)
+                .iter()
+                .map(|v| matrix * v.to_homogeneous())
+                .collect();
+            black_box(clip)
+        })
+    });
+    group.bench_function("batched", |b| {
+        b.iter(|| black_box(transform_homogeneous(&matrix, black_box(&vertices))))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, overdraw_benchmark, projection_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+// Экспорт анимации морфинга в фоновом потоке: кадры рисуются и кодируются,
+// пока интерфейс остаётся отзывчивым и показывает прогресс
+use super::state::render_scene;
+use crate::render::RendererKind;
+use crate::scene::SceneSnapshot;
+use crate::utils::morphing::{CANCELLED, CancellationToken};
+#[cfg(feature = "video")]
+use crate::utils::video_export::VideoEncoder;
+use image::codecs::gif::GifEncoder;
+use image::{Delay, DynamicImage, Frame, RgbImage};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+
+/// Получатель кадров анимации (кодировщик файла)
+pub trait AnimationSink: Send {
+    fn write_frame(&mut self, image: RgbImage) -> Result<(), String>;
+
+    /// Дописывает файл после последнего кадра
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// GIF анимация с постоянной задержкой между кадрами
+pub struct GifSink {
+    pub encoder: GifEncoder<BufWriter<File>>,
+    pub delay: Delay,
+}
+
+impl AnimationSink for GifSink {
+    fn write_frame(&mut self, image: RgbImage) -> Result<(), String> {
+        let frame = Frame::from_parts(DynamicImage::from(image).into_rgba8(), 0, 0, self.delay);
+        self.encoder.encode_frame(frame).map_err(|e| e.to_string())
+    }
+
+    // Завершающий блок GIF записывается при уничтожении кодировщика
+}
+
+#[cfg(feature = "video")]
+impl AnimationSink for VideoEncoder {
+    fn write_frame(&mut self, image: RgbImage) -> Result<(), String> {
+        VideoEncoder::write_frame(self, &image)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        VideoEncoder::finish(*self)
+    }
+}
+
+/// Экспорт анимации, выполняющийся в фоновом потоке
+pub struct AnimationExport {
+    handle: JoinHandle<Result<(), String>>,
+    cancellation: CancellationToken,
+    frames_done: Arc<AtomicUsize>,
+    frame_count: usize,
+}
+
+impl AnimationExport {
+    /// Отрисовывает сцену `scene` с объектом морфинга на стадиях `phases` и передаёт
+    /// кадры `sink` по одному, так что память не растёт с их числом. При ошибке или
+    /// отмене недописанный файл `path` удаляется.
+    pub fn spawn(
+        scene: SceneSnapshot,
+        renderer_kind: RendererKind,
+        (width, height): (u32, u32),
+        phases: Vec<f64>,
+        mut sink: Box<dyn AnimationSink>,
+        path: PathBuf,
+    ) -> Self {
+        let frame_count = phases.len();
+        let cancellation = CancellationToken::default();
+        let frames_done = Arc::new(AtomicUsize::new(0));
+
+        let handle = {
+            let cancellation = cancellation.clone();
+            let frames_done = frames_done.clone();
+            thread::spawn(move || {
+                let scene = scene.into_scene();
+                let Some(object) = scene.object.clone() else {
+                    return Err("Морфинг не построен".to_string());
+                };
+
+                let result = phases.into_iter().try_for_each(|phase| {
+                    cancellation.check()?;
+                    object.borrow_mut().update(phase);
+                    sink.write_frame(render_scene(renderer_kind, &scene, width, height))?;
+                    frames_done.fetch_add(1, Ordering::Relaxed);
+                    Ok::<(), String>(())
+                });
+
+                let result = match result {
+                    Ok(()) => sink.finish(),
+                    Err(e) => {
+                        // Файл закрывается до удаления
+                        drop(sink);
+                        Err(e)
+                    }
+                };
+                if result.is_err() {
+                    let _ = fs::remove_file(&path);
+                }
+                result
+            })
+        };
+
+        Self {
+            handle,
+            cancellation,
+            frames_done,
+            frame_count,
+        }
+    }
+
+    /// Доля готовых кадров от 0 до 1
+    pub fn progress(&self) -> f32 {
+        self.frames_done.load(Ordering::Relaxed) as f32 / self.frame_count.max(1) as f32
+    }
+
+    /// Просит поток остановиться перед следующим кадром
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Дожидается завершения потока. Отменённый экспорт возвращает `CANCELLED`
+    pub fn join(self) -> Result<(), String> {
+        self.handle
+            .join()
+            .unwrap_or_else(|_| Err("поток экспорта аварийно завершился".to_string()))
+    }
+}
+
+/// Ошибка экспорта, о которой нужно сообщить пользователю (не отмена)
+pub fn export_error(result: Result<(), String>) -> Option<String> {
+    result.err().filter(|e| e != CANCELLED)
+}
@@ -0,0 +1,456 @@
+use super::playback::LoopMode;
+use super::state::Primitive;
+use crate::objects::camera::PresetView;
+use serde::{Deserialize, Serialize};
+
+/// Язык интерфейса
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    Russian,
+    English,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Russian, Language::English];
+
+    /// Название языка на нём самом
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Russian => "Русский",
+            Language::English => "English",
+        }
+    }
+
+    /// Строка интерфейса на этом языке
+    pub fn tr(self, text: Text) -> &'static str {
+        let (russian, english) = text.variants();
+        match self {
+            Language::Russian => russian,
+            Language::English => english,
+        }
+    }
+
+    /// Предупреждение о разнице размеров исходного и целевого объектов
+    pub fn scale_mismatch(self, ratio: f64) -> String {
+        match self {
+            Language::Russian => format!("⚠ Размеры объектов отличаются в {:.0} раз", ratio),
+            Language::English => format!("⚠ Object sizes differ {:.0}x", ratio),
+        }
+    }
+}
+
+/// Строки интерфейса
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    SaveSession,
+    LoadSession,
+    SaveFrame,
+    ExportObj,
+    ExportNormals,
+    FlipWinding,
+    FlipWindingHint,
+    AccentColor,
+    Controls,
+    AppTitle,
+    Error,
+    SelectObjFiles,
+    WeldOnLoad,
+    AutoFitOnLoad,
+    SourceObject,
+    TargetObject,
+    ChooseFile,
+    RebuildMorph,
+    CreateMorph,
+    BuildingMorph,
+    Cancel,
+    NormalizeScales,
+    NormalizeScalesHint,
+    SourceShort,
+    TargetShort,
+    Relaxation,
+    Rounds,
+    Converged,
+    NotConverged,
+    Residual,
+    ExportCorrespondence,
+    ExportCorrespondenceHint,
+    SelectBothObjects,
+    ObjectControls,
+    LoadObjectFirst,
+    RotationDegrees,
+    PresetViews,
+    Scaling,
+    ScaleUp,
+    ScaleDown,
+    ResetTransforms,
+    MeshProcessing,
+    SubdivisionIterations,
+    SmoothMesh,
+    DecimationRatio,
+    DecimateMesh,
+    Display,
+    RendererSolid,
+    RendererHybrid,
+    RendererTransparent,
+    RendererWireframe,
+    Renderer,
+    FieldOfView,
+    ShowNonManifoldEdges,
+    AmbientOcclusion,
+    TwoSided,
+    ExplodeFactor,
+    VertexMarkers,
+    SupermeshOrigin,
+    SupermeshOriginHint,
+    Specular,
+    Phong,
+    BlinnPhong,
+    LightAttenuation,
+    AttenuationLinear,
+    Outline,
+    Thickness,
+    Threshold,
+    OutlineColor,
+    Fog,
+    FogStart,
+    FogEnd,
+    FogColor,
+    InputSettings,
+    ZoomStep,
+    InvertScroll,
+    RotationSensitivity,
+    InvertRotation,
+    ViewMode,
+    RenderTime,
+    Milliseconds,
+    ViewSource,
+    ViewTarget,
+    ViewMorph,
+    CreateMorphToEnable,
+    ViewSourceSphere,
+    ViewTargetSphere,
+    MorphControls,
+    SupermeshTriangles,
+    SupermeshWireframe,
+    Phase,
+    Exact,
+    ArrowStep,
+    ArrowStepHint,
+    Frames,
+    SaveGif,
+    Video,
+    SaveVideo,
+    SaveVideoHint,
+    ExportingAnimation,
+    Pause,
+    Play,
+    Repeat,
+    SecondsSuffix,
+    Duration,
+    EndDwell,
+    MorphMetrics,
+    MorphMetricsHint,
+    Volume,
+    Area,
+    RenderTimings,
+    WaitingForFrame,
+    StageTransform,
+    StageShading,
+    StageRasterization,
+    StagePostProcessing,
+    StageTotal,
+    MaterialParams,
+    Color,
+    Diffuse,
+    SpecularReflectance,
+    Gloss,
+    Opacity,
+    OpacityHint,
+    Pattern,
+    PatternCheckerboard,
+    PatternStripes,
+    PatternScale,
+    LoadTexture,
+    Images,
+    RemoveTexture,
+    RimColor,
+    RimStrength,
+    RimPower,
+    None,
+    Sphere,
+    Cube,
+    Torus,
+    LoopOnce,
+    LoopRepeat,
+    LoopPingPong,
+    ViewFront,
+    ViewBack,
+    ViewTop,
+    ViewBottom,
+    ViewLeft,
+    ViewRight,
+    LightTheme,
+    DarkTheme,
+    Language,
+}
+
+impl Text {
+    /// Русский и английский варианты строки
+    fn variants(self) -> (&'static str, &'static str) {
+        match self {
+            Text::SaveSession => ("💾 Сохранить сессию", "💾 Save session"),
+            Text::LoadSession => ("📂 Загрузить сессию", "📂 Load session"),
+            Text::SaveFrame => ("🖼 Сохранить кадр", "🖼 Save frame"),
+            Text::ExportObj => ("📐 Экспорт OBJ", "📐 Export OBJ"),
+            Text::ExportNormals => ("Нормали", "Normals"),
+            Text::FlipWinding => ("Обратный обход", "Flip winding"),
+            Text::FlipWindingHint => (
+                "Для программ, ожидающих обход граней по часовой стрелке",
+                "For programs that expect clockwise face winding",
+            ),
+            Text::AccentColor => ("Цвет выделения", "Accent color"),
+            Text::Controls => ("⚙ Управление", "⚙ Controls"),
+            Text::AppTitle => ("🍎 Морфинг фруктов", "🍎 Fruit morphing"),
+            Text::Error => ("⚠ Ошибка", "⚠ Error"),
+            Text::SelectObjFiles => ("📂 Выбор OBJ файлов:", "📂 OBJ files:"),
+            Text::WeldOnLoad => ("Сваривать совпадающие вершины", "Weld coincident vertices"),
+            Text::AutoFitOnLoad => (
+                "Вписывать объект в кадр при загрузке",
+                "Fit object to view on load",
+            ),
+            Text::SourceObject => ("Исходный объект:", "Source object:"),
+            Text::TargetObject => ("Целевой объект:", "Target object:"),
+            Text::ChooseFile => ("📁 Выбрать файл...", "📁 Choose file..."),
+            Text::RebuildMorph => ("🔄 Пересоздать морфинг", "🔄 Rebuild morph"),
+            Text::CreateMorph => ("✨ Создать морфинг", "✨ Create morph"),
+            Text::BuildingMorph => ("Построение морфинга...", "Building morph..."),
+            Text::Cancel => ("⏹ Отмена", "⏹ Cancel"),
+            Text::NormalizeScales => ("📏 Привести к общему масштабу", "📏 Normalize scales"),
+            Text::NormalizeScalesHint => (
+                "Масштабировать оба объекта до единичного радиуса и перестроить морфинг",
+                "Scale both objects to unit radius and rebuild the morph",
+            ),
+            Text::SourceShort => ("исходный", "source"),
+            Text::TargetShort => ("целевой", "target"),
+            Text::Relaxation => ("Релаксация", "Relaxation"),
+            Text::Rounds => ("раундов", "rounds"),
+            Text::Converged => ("сошлось", "converged"),
+            Text::NotConverged => ("не сошлось", "not converged"),
+            Text::Residual => ("смещение", "residual"),
+            Text::ExportCorrespondence => (
+                "📄 Экспорт соответствия вершин (CSV)",
+                "📄 Export vertex correspondence (CSV)",
+            ),
+            Text::ExportCorrespondenceHint => (
+                "Треугольник и барицентрические координаты каждой вершины суперсетки \
+                 на исходной и целевой сетках",
+                "Triangle and barycentric coordinates of every supermesh vertex \
+                 on the source and target meshes",
+            ),
+            Text::SelectBothObjects => ("⚠ Выберите оба объекта", "⚠ Select both objects"),
+            Text::ObjectControls => ("🎯 Управление объектом:", "🎯 Object controls:"),
+            Text::LoadObjectFirst => (
+                "⚠ Загрузите объект для управления",
+                "⚠ Load an object to control it",
+            ),
+            Text::RotationDegrees => ("🔄 Поворот (градусы):", "🔄 Rotation (degrees):"),
+            Text::PresetViews => (
+                "Стандартные виды (клавиши 1-6):",
+                "Preset views (keys 1-6):",
+            ),
+            Text::Scaling => ("🔍 Масштабирование:", "🔍 Scaling:"),
+            Text::ScaleUp => ("➕ Увеличить x1.1", "➕ Enlarge x1.1"),
+            Text::ScaleDown => ("➖ Уменьшить x0.9", "➖ Shrink x0.9"),
+            Text::ResetTransforms => ("🔄 Сбросить преобразования", "🔄 Reset transforms"),
+            Text::MeshProcessing => ("🛠 Обработка сетки:", "🛠 Mesh processing:"),
+            Text::SubdivisionIterations => ("Итерации подразбиения:", "Subdivision iterations:"),
+            Text::SmoothMesh => ("🍥 Сгладить сетку", "🍥 Smooth mesh"),
+            Text::DecimationRatio => (
+                "Доля треугольников после упрощения:",
+                "Triangles kept after decimation:",
+            ),
+            Text::DecimateMesh => ("✂ Упростить сетку", "✂ Decimate mesh"),
+            Text::Display => ("🖼 Отображение:", "🖼 Display:"),
+            Text::RendererSolid => ("Заливка", "Solid"),
+            Text::RendererHybrid => ("Заливка и каркас", "Solid and wireframe"),
+            Text::RendererTransparent => ("Прозрачность", "Transparency"),
+            Text::RendererWireframe => ("Каркас", "Wireframe"),
+            Text::Renderer => ("Рендерер:", "Renderer:"),
+            Text::FieldOfView => ("Угол обзора:", "Field of view:"),
+            Text::ShowNonManifoldEdges => (
+                "Подсвечивать немногообразные рёбра",
+                "Highlight non-manifold edges",
+            ),
+            Text::AmbientOcclusion => {
+                ("Затенение окружающим светом (AO)", "Ambient occlusion (AO)")
+            }
+            Text::TwoSided => ("Двустороннее освещение", "Two-sided lighting"),
+            Text::ExplodeFactor => ("Разнесение граней", "Explode faces"),
+            Text::VertexMarkers => ("Отмечать вершины", "Mark vertices"),
+            Text::SupermeshOrigin => (
+                "Раскрашивать суперсетку по происхождению рёбер",
+                "Color supermesh by edge origin",
+            ),
+            Text::SupermeshOriginHint => (
+                "Красный - исходная сетка, синий - целевая, зелёный - пересечение",
+                "Red - source mesh, blue - target, green - intersection",
+            ),
+            Text::Specular => ("Блики:", "Highlights:"),
+            Text::Phong => ("Фонг", "Phong"),
+            Text::BlinnPhong => ("Блинн-Фонг", "Blinn-Phong"),
+            Text::LightAttenuation => ("Ослабление света:", "Light attenuation:"),
+            Text::AttenuationLinear => ("Линейное", "Linear"),
+            Text::Outline => ("Контур", "Outline"),
+            Text::Thickness => ("Толщина", "Thickness"),
+            Text::Threshold => ("Порог", "Threshold"),
+            Text::OutlineColor => ("Цвет контура:", "Outline color:"),
+            Text::Fog => ("Туман", "Fog"),
+            Text::FogStart => ("Начало", "Start"),
+            Text::FogEnd => ("Конец", "End"),
+            Text::FogColor => ("Цвет тумана:", "Fog color:"),
+            Text::InputSettings => ("🖱 Настройки управления", "🖱 Input settings"),
+            Text::ZoomStep => ("Шаг приближения", "Zoom step"),
+            Text::InvertScroll => ("Инвертировать прокрутку", "Invert scrolling"),
+            Text::RotationSensitivity => ("Чувствительность вращения", "Rotation sensitivity"),
+            Text::InvertRotation => ("Инвертировать вращение", "Invert rotation"),
+            Text::ViewMode => ("👁 Режим просмотра:", "👁 View mode:"),
+            Text::RenderTime => ("Рендер:", "Render:"),
+            Text::Milliseconds => ("мс", "ms"),
+            Text::ViewSource => ("📦 Исходный", "📦 Source"),
+            Text::ViewTarget => ("🎯 Целевой", "🎯 Target"),
+            Text::ViewMorph => ("✨ Морфинг", "✨ Morph"),
+            Text::CreateMorphToEnable => {
+                ("Создайте морфинг для активации", "Create a morph to enable")
+            }
+            Text::ViewSourceSphere => ("🌐 Сфера исходного", "🌐 Source sphere"),
+            Text::ViewTargetSphere => ("🌐 Сфера целевого", "🌐 Target sphere"),
+            Text::MorphControls => ("⏱ Управление морфингом:", "⏱ Morph controls:"),
+            Text::SupermeshTriangles => ("Треугольников в суперсетке:", "Supermesh triangles:"),
+            Text::SupermeshWireframe => ("🕸 Каркас суперсетки", "🕸 Supermesh wireframe"),
+            Text::Phase => ("Стадия:", "Phase:"),
+            Text::Exact => ("Точно:", "Exact:"),
+            Text::ArrowStep => ("Шаг стрелок", "Arrow key step"),
+            Text::ArrowStepHint => (
+                "← / → меняют стадию на шаг, с Shift - на 10 шагов",
+                "← / → change the phase by one step, with Shift by 10 steps",
+            ),
+            Text::Frames => ("Кадров:", "Frames:"),
+            Text::SaveGif => ("🎞 Сохранить GIF", "🎞 Save GIF"),
+            Text::Video => ("Видео:", "Video:"),
+            Text::SaveVideo => ("🎬 Сохранить видео", "🎬 Save video"),
+            Text::SaveVideoHint => (
+                "MP4 или WebM, требуется ffmpeg в PATH",
+                "MP4 or WebM, requires ffmpeg in PATH",
+            ),
+            Text::ExportingAnimation => ("Запись анимации...", "Exporting animation..."),
+            Text::Pause => ("⏸ Пауза", "⏸ Pause"),
+            Text::Play => ("▶ Воспроизвести", "▶ Play"),
+            Text::Repeat => ("Повтор", "Repeat"),
+            Text::SecondsSuffix => (" с", " s"),
+            Text::Duration => ("Длительность", "Duration"),
+            Text::EndDwell => ("Пауза на концах", "Pause at ends"),
+            Text::MorphMetrics => (
+                "📉 Объём и площадь по стадиям",
+                "📉 Volume and area by phase",
+            ),
+            Text::MorphMetricsHint => (
+                "Провал объёма на промежуточной стадии - признак схлопывания",
+                "A volume dip at an intermediate phase indicates collapse",
+            ),
+            Text::Volume => ("Объём:", "Volume:"),
+            Text::Area => ("Площадь:", "Area:"),
+            Text::RenderTimings => ("🐞 Время отрисовки", "🐞 Render timings"),
+            Text::WaitingForFrame => ("Ожидание кадра...", "Waiting for frame..."),
+            Text::StageTransform => ("Преобразование вершин", "Vertex transform"),
+            Text::StageShading => ("Освещение", "Shading"),
+            Text::StageRasterization => ("Растеризация", "Rasterization"),
+            Text::StagePostProcessing => ("Постобработка", "Post-processing"),
+            Text::StageTotal => ("Всего", "Total"),
+            Text::MaterialParams => ("🎨 Параметры материала:", "🎨 Material:"),
+            Text::Color => ("Цвет:", "Color:"),
+            Text::Diffuse => ("Диффузное отражение:", "Diffuse reflectance:"),
+            Text::SpecularReflectance => ("Зеркальное отражение:", "Specular reflectance:"),
+            Text::Gloss => ("Глянцевость:", "Gloss:"),
+            Text::Opacity => ("Непрозрачность:", "Opacity:"),
+            Text::OpacityHint => (
+                "Объект с непрозрачностью меньше 1 смешивается с фоном",
+                "An object with opacity below 1 is blended with the background",
+            ),
+            Text::Pattern => ("Узор:", "Pattern:"),
+            Text::PatternCheckerboard => ("Шахматы", "Checkerboard"),
+            Text::PatternStripes => ("Полосы", "Stripes"),
+            Text::PatternScale => ("Размер клетки:", "Cell size:"),
+            Text::LoadTexture => ("🖼 Загрузить текстуру", "🖼 Load texture"),
+            Text::Images => ("Изображения", "Images"),
+            Text::RemoveTexture => ("Убрать", "Remove"),
+            Text::RimColor => ("Цвет ободка:", "Rim color:"),
+            Text::RimStrength => ("Яркость ободка:", "Rim strength:"),
+            Text::RimPower => ("Резкость ободка:", "Rim sharpness:"),
+            Text::None => ("Нет", "None"),
+            Text::Sphere => ("Сфера", "Sphere"),
+            Text::Cube => ("Куб", "Cube"),
+            Text::Torus => ("Тор", "Torus"),
+            Text::LoopOnce => ("Один раз", "Once"),
+            Text::LoopRepeat => ("По кругу", "Loop"),
+            Text::LoopPingPong => ("Туда и обратно", "Ping-pong"),
+            Text::ViewFront => ("Спереди", "Front"),
+            Text::ViewBack => ("Сзади", "Back"),
+            Text::ViewTop => ("Сверху", "Top"),
+            Text::ViewBottom => ("Снизу", "Bottom"),
+            Text::ViewLeft => ("Слева", "Left"),
+            Text::ViewRight => ("Справа", "Right"),
+            Text::LightTheme => ("☀ Светлая тема", "☀ Light theme"),
+            Text::DarkTheme => ("🌙 Тёмная тема", "🌙 Dark theme"),
+            Text::Language => ("Язык", "Language"),
+        }
+    }
+}
+
+impl From<Primitive> for Text {
+    fn from(primitive: Primitive) -> Self {
+        match primitive {
+            Primitive::Sphere => Text::Sphere,
+            Primitive::Cube => Text::Cube,
+            Primitive::Torus => Text::Torus,
+        }
+    }
+}
+
+impl From<LoopMode> for Text {
+    fn from(mode: LoopMode) -> Self {
+        match mode {
+            LoopMode::Once => Text::LoopOnce,
+            LoopMode::Loop => Text::LoopRepeat,
+            LoopMode::PingPong => Text::LoopPingPong,
+        }
+    }
+}
+
+impl From<PresetView> for Text {
+    fn from(view: PresetView) -> Self {
+        match view {
+            PresetView::Front => Text::ViewFront,
+            PresetView::Back => Text::ViewBack,
+            PresetView::Top => Text::ViewTop,
+            PresetView::Bottom => Text::ViewBottom,
+            PresetView::Left => Text::ViewLeft,
+            PresetView::Right => Text::ViewRight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_variant_is_returned_after_switching() {
+        let mut language = Language::default();
+        assert_eq!(language.tr(Text::SaveSession), "💾 Сохранить сессию");
+
+        language = Language::English;
+        assert_eq!(language.tr(Text::SaveSession), "💾 Save session");
+        assert_eq!(language.tr(Text::from(LoopMode::PingPong)), "Ping-pong");
+    }
+}
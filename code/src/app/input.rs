@@ -1,55 +1,536 @@
-use super::state::MyEguiApp;
-use crate::config::{ROTATION_SENSITIVITY_FACTOR, SCALING_SENSITIVITY_FACTOR};
-use crate::objects::model3d::{Rotate, Scale};
-use eframe::egui::Context;
+use super::state::{MyEguiApp, ViewMode};
+use crate::config::{
+    INERTIA_SAMPLES, INERTIA_STOP_SPEED, MAX_MODEL_SCALE, MIN_MODEL_SCALE,
+    MORPH_PHASE_COARSE_FACTOR, ROTATION_DAMPING, ROTATION_SENSITIVITY_FACTOR,
+    SCALING_SENSITIVITY_FACTOR,
+};
+use crate::objects::camera::PresetView;
+use crate::objects::transform::Transform;
+use crate::scene::Ray;
+use eframe::egui::{Context, Key, Pos2, Rect};
+use nalgebra::{UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Ограничения изменения масштаба жестом за один кадр
+const MIN_GESTURE_ZOOM: f64 = 0.5;
+const MAX_GESTURE_ZOOM: f64 = 2.0;
+
+/// Настройки приближения колесом мыши
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrollZoomSettings {
+    /// Прокрутка вниз увеличивает объект
+    pub invert: bool,
+    /// Относительное изменение масштаба на единицу прокрутки
+    pub step: f32,
+}
+
+impl Default for ScrollZoomSettings {
+    fn default() -> Self {
+        Self {
+            invert: false,
+            step: SCALING_SENSITIVITY_FACTOR,
+        }
+    }
+}
+
+/// Настройки вращения объекта мышью и жестом
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RotationSettings {
+    /// Объект вращается в сторону, противоположную движению курсора
+    pub invert: bool,
+    /// Множитель угла поворота
+    pub sensitivity: f64,
+}
+
+impl Default for RotationSettings {
+    fn default() -> Self {
+        Self {
+            invert: false,
+            sensitivity: ROTATION_SENSITIVITY_FACTOR,
+        }
+    }
+}
+
+impl RotationSettings {
+    /// Множитель угла с учётом инверсии
+    fn factor(&self) -> f64 {
+        if self.invert {
+            -self.sensitivity
+        } else {
+            self.sensitivity
+        }
+    }
+}
+
+/// Вращение по инерции после отпускания кнопки мыши
+#[derive(Debug, Clone, Default)]
+pub struct RotationInertia {
+    // Последние повороты при перетаскивании и длительности их кадров
+    samples: VecDeque<(Vector3<f64>, f64)>,
+    // Угловая скорость (ось, умноженная на рад/с) после отпускания
+    velocity: Option<Vector3<f64>>,
+}
+
+impl RotationInertia {
+    /// Запоминает поворот `axis_angle`, сделанный за `dt` секунд перетаскивания
+    pub fn record(&mut self, axis_angle: Vector3<f64>, dt: f64) {
+        if self.samples.len() == INERTIA_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((axis_angle, dt));
+    }
+
+    /// Кнопка отпущена: скорость оценивается по последним перемещениям
+    pub fn release(&mut self) {
+        let (angle, time) = self
+            .samples
+            .drain(..)
+            .fold((Vector3::zeros(), 0.), |(angle, time), (a, dt)| {
+                (angle + a, time + dt)
+            });
+        self.velocity = Some(angle / time).filter(|v| time > 0. && v.norm() > INERTIA_STOP_SPEED);
+    }
+
+    /// Новое нажатие останавливает вращение
+    pub fn stop(&mut self) {
+        self.samples.clear();
+        self.velocity = None;
+    }
+
+    /// Поворот за кадр длительностью `dt`; скорость затухает экспоненциально
+    /// и обнуляется, опустившись ниже INERTIA_STOP_SPEED
+    pub fn step(&mut self, dt: f64) -> Option<Vector3<f64>> {
+        let velocity = self.velocity?;
+        let decayed = velocity * (-ROTATION_DAMPING * dt).exp();
+        self.velocity = Some(decayed).filter(|v| v.norm() > INERTIA_STOP_SPEED);
+        Some(velocity * dt)
+    }
+}
 
 impl MyEguiApp {
-    pub fn mouse_wheel_scaling(&mut self, ctx: &Context) {
-        // Масштабирование работает только если курсор над окном просмотра
+    pub fn mouse_wheel_dolly(&mut self, ctx: &Context) {
+        // Приближение работает только если курсор над окном просмотра
         if !self.viewport_has_pointer {
             return;
         }
 
-        let scroll_delta = ctx.input(|i| i.raw_scroll_delta);
-        if scroll_delta.y == 0.0 {
+        // Ctrl + колесо egui превращает в жест масштабирования, его обрабатывает
+        // touch_gestures и масштабирует сам объект
+        let (scroll_delta, zoom_delta) = ctx.input(|i| (i.raw_scroll_delta, i.zoom_delta()));
+        if scroll_delta.y == 0.0 || zoom_delta != 1.0 {
             return;
         }
 
-        let scaling_factor =
-            (1. + scroll_delta.y.max(-200.) * SCALING_SENSITIVITY_FACTOR).max(f32::EPSILON);
+        // Камера приближается так, чтобы видимый размер менялся в заданное число раз
+        let zoom_factor = wheel_scale_factor(scroll_delta.y, &self.scroll_zoom);
+        let camera = &mut self.scene.camera;
+        camera.dolly(camera.distance() * (1. - 1. / zoom_factor));
+
+        self.needs_redraw = true; // Требуется перерисовка после перемещения камеры
+    }
+
+    pub fn touch_gestures(&mut self, ctx: &Context) {
+        // Жесты работают только если курсор над окном просмотра
+        if !self.viewport_has_pointer {
+            return;
+        }
+
+        let (zoom_delta, rotation_delta) = ctx.input(|i| {
+            let rotation = i.multi_touch().map_or(0.0, |touch| touch.rotation_delta);
+            (i.zoom_delta(), rotation)
+        });
+        let scaling_factor = gesture_scale_factor(zoom_delta);
+        if scaling_factor.is_none() && rotation_delta == 0.0 {
+            return;
+        }
 
-        // Применяем масштабирование к текущему объекту напрямую
         if let Some(object) = self.scene.object.as_ref() {
-            object.borrow_mut().scale(scaling_factor.into());
+            let mut object = object.borrow_mut();
+            if let Some(factor) = scaling_factor {
+                let scaling = Transform::from_matrix(object.model_matrix()).scaling;
+                object.scale(limit_scale_factor(&scaling, factor));
+            }
+            // Поворот двумя пальцами — вокруг оси взгляда; углы egui отсчитываются
+            // по часовой стрелке, так как ось Y экрана направлена вниз
+            if rotation_delta != 0.0 {
+                let angle = -rotation_delta as f64 * self.rotation.factor();
+                object.rotate((0., 0., angle));
+            }
         }
 
-        self.needs_redraw = true; // Требуется перерисовка после масштабирования мышью
+        self.needs_redraw = true; // Требуется перерисовка после жеста
     }
 
     pub fn mouse_drag_rotation(&mut self, ctx: &Context) {
+        let (pressed, released) =
+            ctx.input(|i| (i.pointer.primary_pressed(), i.pointer.primary_released()));
+        if pressed {
+            self.rotation_inertia.stop();
+        }
+        if released {
+            self.rotation_inertia.release();
+        }
+
         // Вращение работает только если курсор над окном просмотра
         if !self.viewport_has_pointer || !ctx.input(|i| i.pointer.primary_down()) {
             return;
         }
+        let Some(rect) = self.viewport_rect else {
+            return;
+        };
+
+        // Перетаскивание, начатое на панели (например, ползунком), объект не вращает,
+        // даже когда курсор заходит в окно просмотра
+        let press_origin = ctx.input(|i| i.pointer.press_origin());
+        if ctx.is_using_pointer() || !drag_started_in_viewport(press_origin, rect) {
+            return;
+        }
 
         let delta = ctx.input(|i| i.pointer.delta());
+        let Some(current) = ctx.input(|i| i.pointer.interact_pos()) else {
+            return;
+        };
 
+        // Неподвижный курсор тоже учитывается: остановка перед отпусканием гасит инерцию
+        let dt = ctx.input(|i| i.unstable_dt) as f64;
         if delta.x == 0.0 && delta.y == 0.0 {
+            self.rotation_inertia.record(Vector3::zeros(), dt);
             return;
         }
 
-        let rotation_x = delta.y * ROTATION_SENSITIVITY_FACTOR;
-        let rotation_y = delta.x * ROTATION_SENSITIVITY_FACTOR;
+        // Поворот, переводящий прошлое положение курсора на трекболе в текущее
+        let from = project_to_arcball(current - delta, rect);
+        let to = project_to_arcball(current, rect);
+        let Some(axis_angle) = drag_rotation(&from, &to, &self.rotation) else {
+            return;
+        };
+        self.rotation_inertia.record(axis_angle, dt);
 
         // Применяем поворот к текущему объекту напрямую
         if let Some(object) = self.scene.object.as_mut() {
-            object.borrow_mut().rotate((
-                rotation_x.to_radians().into(),
-                rotation_y.to_radians().into(),
-                0.,
-            ));
+            object
+                .borrow_mut()
+                .rotate((axis_angle.x, axis_angle.y, axis_angle.z));
         }
 
         self.needs_redraw = true; // Требуется перерисовка после поворота мышью
     }
+
+    /// Продолжает вращение объекта по инерции в течение кадра длительностью `dt`
+    pub fn apply_rotation_inertia(&mut self, dt: f64) {
+        if self.is_error_shown() {
+            return;
+        }
+        let Some(axis_angle) = self.rotation_inertia.step(dt) else {
+            return;
+        };
+        if let Some(object) = self.scene.object.as_ref() {
+            object
+                .borrow_mut()
+                .rotate((axis_angle.x, axis_angle.y, axis_angle.z));
+        }
+        self.needs_redraw = true; // Требуется перерисовка, пока объект вращается
+    }
+
+    /// Клавиши камеры: F - показать все объекты, C - навести на выделенный объект,
+    /// 1-6 - стандартные виды в порядке PresetView::ALL
+    pub fn keyboard_camera_commands(&mut self, ctx: &Context) {
+        // Клавиши не перехватываются во время ввода текста
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (frame_all, center) = ctx.input(|i| (i.key_pressed(Key::F), i.key_pressed(Key::C)));
+        if frame_all {
+            self.frame_all();
+        }
+        if center {
+            self.center_on_selection();
+        }
+
+        const VIEW_KEYS: [Key; 6] = [
+            Key::Num1,
+            Key::Num2,
+            Key::Num3,
+            Key::Num4,
+            Key::Num5,
+            Key::Num6,
+        ];
+        let view = ctx.input(|i| {
+            VIEW_KEYS
+                .iter()
+                .zip(PresetView::ALL)
+                .find(|(key, _)| i.key_pressed(**key))
+                .map(|(_, view)| view)
+        });
+        if let Some(view) = view {
+            self.snap_to_view(view);
+        }
+    }
+
+    /// Стрелки влево и вправо меняют стадию морфинга на шаг, с Shift - на крупный шаг
+    pub fn keyboard_morph_phase(&mut self, ctx: &Context) {
+        if self.view_mode != ViewMode::Morph || !self.morph_created || ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (left, right, shift) = ctx.input(|i| {
+            (
+                i.key_pressed(Key::ArrowLeft),
+                i.key_pressed(Key::ArrowRight),
+                i.modifiers.shift,
+            )
+        });
+        let direction = right as i32 - left as i32;
+        if direction == 0 {
+            return;
+        }
+
+        let step = if shift {
+            self.morph_phase_step * MORPH_PHASE_COARSE_FACTOR
+        } else {
+            self.morph_phase_step
+        };
+        self.step_morph_phase(direction as f64 * step);
+    }
+
+    /// Щелчок по окну просмотра выделяет ближайший объект под курсором
+    pub fn mouse_click_picking(&mut self, ctx: &Context) {
+        if !self.viewport_has_pointer || !ctx.input(|i| i.pointer.primary_clicked()) {
+            return;
+        }
+        let Some(rect) = self.viewport_rect else {
+            return;
+        };
+        let Some(pos) = ctx.input(|i| i.pointer.interact_pos()) else {
+            return;
+        };
+
+        // Положение курсора в пикселях кадра
+        let x = (pos.x - rect.min.x) / rect.width() * self.viewport_width as f32;
+        let y = (pos.y - rect.min.y) / rect.height() * self.viewport_height as f32;
+        let Some(ray) = Ray::through_pixel(
+            &self.scene.camera,
+            self.viewport_width,
+            self.viewport_height,
+            x as f64,
+            y as f64,
+        ) else {
+            return;
+        };
+
+        let selected = self.scene.pick(&ray);
+        if selected != self.scene.selected {
+            self.scene.selected = selected;
+            self.needs_redraw = true; // Требуется перерисовка подсветки выделения
+        }
+    }
+}
+
+/// Множитель приближения за одно событие прокрутки колеса
+pub fn wheel_scale_factor(scroll_y: f32, settings: &ScrollZoomSettings) -> f64 {
+    let scroll_y = if settings.invert { -scroll_y } else { scroll_y };
+    (1. + scroll_y.max(-200.) * settings.step).max(f32::EPSILON) as f64
+}
+
+/// Множитель масштаба по относительному изменению масштаба жеста (щипок, Ctrl + колесо).
+/// Резкие скачки за один кадр ограничиваются, `None` — если масштаб не изменился.
+pub fn gesture_scale_factor(zoom_delta: f32) -> Option<f64> {
+    if !zoom_delta.is_finite() || zoom_delta <= 0.0 || zoom_delta == 1.0 {
+        return None;
+    }
+    Some((zoom_delta as f64).clamp(MIN_GESTURE_ZOOM, MAX_GESTURE_ZOOM))
+}
+
+/// Ограничивает равномерный множитель масштаба так, чтобы накопленный масштаб `scaling`
+/// по каждой оси остался в пределах MIN_MODEL_SCALE..MAX_MODEL_SCALE и объект не
+/// вырождался. Масштаб, уже вышедший за пределы, дальше в ту же сторону не меняется
+pub fn limit_scale_factor(scaling: &Vector3<f64>, factor: f64) -> f64 {
+    let (min, max) = (scaling.min(), scaling.max());
+    if min <= 0. || !max.is_finite() {
+        return factor;
+    }
+    factor.clamp(
+        MIN_MODEL_SCALE.min(min) / min,
+        MAX_MODEL_SCALE.max(max) / max,
+    )
+}
+
+/// То же, что limit_scale_factor, для масштабирования по осям объекта
+pub fn limit_scale_factors(scaling: &Vector3<f64>, factors: &Vector3<f64>) -> Vector3<f64> {
+    Vector3::from_fn(|i, _| limit_scale_factor(&Vector3::repeat(scaling[i]), factors[i]))
+}
+
+/// Проецирует точку экрана на виртуальную единичную полусферу, вписанную в окно просмотра.
+///
+/// Ось X направлена вправо, Y — вверх, Z — к наблюдателю. Точки вне круга
+/// проецируются на его границу (экватор сферы).
+pub fn project_to_arcball(pos: Pos2, rect: Rect) -> Vector3<f64> {
+    let radius = (rect.width().min(rect.height()) / 2.).max(f32::EPSILON) as f64;
+    let x = (pos.x - rect.center().x) as f64 / radius;
+    let y = (rect.center().y - pos.y) as f64 / radius;
+
+    let squared = x * x + y * y;
+    if squared <= 1. {
+        Vector3::new(x, y, (1. - squared).sqrt())
+    } else {
+        Vector3::new(x, y, 0.) / squared.sqrt()
+    }
+}
+
+/// Поворот (ось, умноженная на угол в радианах), переводящий одну точку трекбола
+/// в другую. `None`, если точки совпадают.
+pub fn arcball_rotation(from: &Vector3<f64>, to: &Vector3<f64>) -> Option<Vector3<f64>> {
+    let rotation = UnitQuaternion::rotation_between(from, to)?;
+    let axis_angle = rotation.scaled_axis();
+    (axis_angle.norm() > f64::EPSILON).then_some(axis_angle)
+}
+
+/// Нажатие, с которого началось перетаскивание, пришлось на окно просмотра `rect`
+pub fn drag_started_in_viewport(press_origin: Option<Pos2>, rect: Rect) -> bool {
+    press_origin.is_some_and(|origin| rect.contains(origin))
+}
+
+/// Поворот при перетаскивании по трекболу из `from` в `to` с учётом настроек вращения
+pub fn drag_rotation(
+    from: &Vector3<f64>,
+    to: &Vector3<f64>,
+    settings: &RotationSettings,
+) -> Option<Vector3<f64>> {
+    arcball_rotation(from, to)
+        .map(|axis_angle| axis_angle * settings.factor())
+        .filter(|axis_angle| axis_angle.norm() > f64::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::pos2;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn zooming_out_clamps_at_minimum_scale() {
+        // Ctrl + колесо и щипок масштабируют сам объект
+        let mut transform = Transform::default();
+        for _ in 0..1000 {
+            let factor = gesture_scale_factor(0.001).unwrap();
+            transform.scale(limit_scale_factor(&transform.scaling, factor));
+        }
+        assert!((transform.scaling - Vector3::repeat(MIN_MODEL_SCALE)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn axis_scaling_clamps_each_axis_separately() {
+        let mut transform = Transform::default();
+        let factors = Vector3::new(1e6, 1e-6, 2.);
+        transform.scale_xyz(&limit_scale_factors(&transform.scaling, &factors));
+        assert!(
+            (transform.scaling - Vector3::new(MAX_MODEL_SCALE, MIN_MODEL_SCALE, 2.)).norm() < 1e-9
+        );
+
+        // Масштаб вне пределов (например, из сессии) не сбрасывается, но и не растёт дальше
+        let scaling = Vector3::repeat(1e5);
+        assert_eq!(limit_scale_factor(&scaling, 2.), 1.);
+        assert_eq!(limit_scale_factor(&scaling, 0.5), 0.5);
+    }
+
+    #[test]
+    fn inverted_scroll_zooms_in_when_scrolling_down() {
+        let settings = ScrollZoomSettings::default();
+        let inverted = ScrollZoomSettings {
+            invert: true,
+            ..settings
+        };
+        assert!(wheel_scale_factor(-10., &inverted) > 1.);
+        assert!(wheel_scale_factor(-10., &settings) < 1.);
+    }
+
+    #[test]
+    fn higher_rotation_sensitivity_rotates_further() {
+        let from = Vector3::z();
+        let to = Vector3::new(0.1, 0., 1.).normalize();
+        let settings = RotationSettings::default();
+        let sensitive = RotationSettings {
+            sensitivity: 2.5,
+            ..settings
+        };
+
+        let base = drag_rotation(&from, &to, &settings).unwrap();
+        let faster = drag_rotation(&from, &to, &sensitive).unwrap();
+        assert!(faster.norm() > base.norm());
+        assert!((faster - base * 2.5).norm() < 1e-12);
+
+        let inverted = RotationSettings {
+            invert: true,
+            ..settings
+        };
+        let reversed = drag_rotation(&from, &to, &inverted).unwrap();
+        assert!((reversed + base).norm() < 1e-12);
+    }
+
+    #[test]
+    fn gesture_zoom_maps_to_clamped_scale_factor() {
+        assert_eq!(gesture_scale_factor(1.0), None);
+        assert_eq!(gesture_scale_factor(0.0), None);
+        assert_eq!(gesture_scale_factor(f32::NAN), None);
+        assert_eq!(gesture_scale_factor(1.25), Some(1.25));
+        assert_eq!(gesture_scale_factor(0.5), Some(0.5));
+        assert_eq!(gesture_scale_factor(100.0), Some(MAX_GESTURE_ZOOM));
+        assert_eq!(gesture_scale_factor(0.001), Some(MIN_GESTURE_ZOOM));
+    }
+
+    #[test]
+    fn inertia_increments_decay_until_stop() {
+        let mut inertia = RotationInertia::default();
+        for _ in 0..INERTIA_SAMPLES {
+            inertia.record(Vector3::new(0., 0.05, 0.), 0.016);
+        }
+        inertia.release();
+
+        let increments: Vec<f64> = std::iter::from_fn(|| inertia.step(0.016))
+            .map(|axis_angle| axis_angle.norm())
+            .take(10_000)
+            .collect();
+        assert!(increments.len() > 2 && increments.len() < 10_000);
+        assert!(increments.windows(2).all(|pair| pair[1] < pair[0]));
+
+        // Новое нажатие останавливает вращение сразу
+        inertia.record(Vector3::new(0., 0.05, 0.), 0.016);
+        inertia.release();
+        inertia.stop();
+        assert_eq!(inertia.step(0.016), None);
+    }
+
+    #[test]
+    fn drag_from_panel_does_not_rotate() {
+        let viewport = Rect::from_min_max(pos2(0., 0.), pos2(800., 600.));
+
+        assert!(drag_started_in_viewport(Some(pos2(400., 300.)), viewport));
+        // Нажатие на ползунке правой панели, затем курсор зашёл в окно просмотра
+        assert!(!drag_started_in_viewport(Some(pos2(900., 300.)), viewport));
+        assert!(!drag_started_in_viewport(None, viewport));
+    }
+
+    #[test]
+    fn arcball_maps_center_and_edge_and_rotates_between_them() {
+        let rect = Rect::from_min_max(pos2(100., 50.), pos2(300., 250.));
+
+        let center = project_to_arcball(rect.center(), rect);
+        let right = project_to_arcball(pos2(300., 150.), rect);
+        let top = project_to_arcball(pos2(200., 50.), rect);
+        let far_outside = project_to_arcball(pos2(1000., 150.), rect);
+
+        assert!((center - Vector3::z()).norm() < 1e-9);
+        assert!((right - Vector3::x()).norm() < 1e-9);
+        assert!((top - Vector3::y()).norm() < 1e-9);
+        assert!((far_outside - Vector3::x()).norm() < 1e-9);
+
+        // Перетаскивание из центра к правому краю поворачивает объект на 90° вокруг Y
+        let rotation = arcball_rotation(&center, &right).unwrap();
+        assert!((rotation - Vector3::new(0., FRAC_PI_2, 0.)).norm() < 1e-9);
+        assert!(arcball_rotation(&center, &center).is_none());
+    }
 }
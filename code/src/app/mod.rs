@@ -1,6 +1,11 @@
 // Модуль графического приложения - объединяет состояние, UI и обработку ввода
+pub mod export;
+pub mod i18n;
 pub mod input;
+pub mod playback;
+pub mod session;
 pub mod state;
+pub mod theme;
 pub mod ui;
 
 // Реэкспортируем основные типы для удобства использования
@@ -0,0 +1,171 @@
+// Воспроизведение морфинга: продвижение стадии во времени с паузами на концах
+use crate::config::{MORPH_END_DWELL, MORPH_PLAYBACK_DURATION};
+
+/// Что происходит, когда стадия доходит до конца
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LoopMode {
+    /// Остановиться на конечной стадии
+    #[default]
+    Once,
+    /// Вернуться к началу и повторить
+    Loop,
+    /// Сменить направление и проиграть обратно
+    PingPong,
+}
+
+impl LoopMode {
+    pub const ALL: [LoopMode; 3] = [LoopMode::Once, LoopMode::Loop, LoopMode::PingPong];
+}
+
+/// Состояние проигрывания морфинга. Сама стадия хранится в приложении,
+/// здесь - только направление и оставшаяся пауза
+#[derive(Clone, Debug)]
+pub struct MorphPlayback {
+    pub playing: bool,
+    pub loop_mode: LoopMode,
+    // Длительность прохода от 0 до 1, секунды
+    pub duration: f64,
+    // Пауза на стадиях 0 и 1, секунды
+    pub end_dwell: f64,
+    forward: bool,
+    // Оставшееся время паузы; None - стадия движется
+    dwell_left: Option<f64>,
+}
+
+impl Default for MorphPlayback {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            loop_mode: LoopMode::default(),
+            duration: MORPH_PLAYBACK_DURATION,
+            end_dwell: MORPH_END_DWELL,
+            forward: true,
+            dwell_left: None,
+        }
+    }
+}
+
+impl MorphPlayback {
+    /// Запускает проигрывание. Возвращает стадию, с которой оно начнётся:
+    /// однократное проигрывание, уже дошедшее до конца, начинается заново
+    pub fn play(&mut self, phase: f64) -> f64 {
+        self.playing = true;
+        self.dwell_left = None;
+        // Обратное направление имеет смысл только в режиме "туда и обратно"
+        if self.loop_mode != LoopMode::PingPong {
+            self.forward = true;
+        }
+        if self.loop_mode == LoopMode::Once && phase >= 1. {
+            0.
+        } else {
+            phase
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Продвигает стадию `phase` на время `dt` (секунды) и возвращает новую стадию
+    pub fn advance(&mut self, mut phase: f64, dt: f64) -> f64 {
+        if !self.playing || self.duration <= 0. {
+            return phase;
+        }
+
+        let mut remaining = dt;
+        while remaining > 0. {
+            let target = if self.forward { 1. } else { 0. };
+
+            if let Some(left) = self.dwell_left {
+                let spent = left.min(remaining);
+                remaining -= spent;
+                if left > spent {
+                    self.dwell_left = Some(left - spent);
+                    break;
+                }
+
+                // Пауза закончилась. На начальной стадии движение продолжается,
+                // на конечной - зависит от режима
+                self.dwell_left = None;
+                if phase == target {
+                    match self.loop_mode {
+                        LoopMode::PingPong => self.forward = !self.forward,
+                        LoopMode::Loop => {
+                            phase = 1. - target;
+                            self.dwell_left = Some(self.end_dwell);
+                        }
+                        LoopMode::Once => self.playing = false,
+                    }
+                }
+                continue;
+            }
+
+            // Время, за которое стадия дойдёт до конца
+            let to_end = (target - phase).abs() * self.duration;
+            if remaining < to_end {
+                let travel = remaining / self.duration;
+                phase += if self.forward { travel } else { -travel };
+                break;
+            }
+
+            remaining -= to_end;
+            phase = target;
+            if self.loop_mode == LoopMode::Once {
+                self.playing = false;
+                break;
+            }
+            self.dwell_left = Some(self.end_dwell);
+        }
+
+        phase
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phases(playback: &mut MorphPlayback, steps: usize, dt: f64) -> Vec<f64> {
+        let mut phase = 0.;
+        (0..steps)
+            .map(|_| {
+                phase = playback.advance(phase, dt);
+                phase
+            })
+            .collect()
+    }
+
+    fn playback(loop_mode: LoopMode) -> MorphPlayback {
+        MorphPlayback {
+            playing: true,
+            loop_mode,
+            duration: 1.,
+            end_dwell: 0.5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ping_pong_holds_at_both_ends() {
+        let mut playback = playback(LoopMode::PingPong);
+        let expected = [
+            0.25, 0.5, 0.75, 1., 1., 1., 0.75, 0.5, 0.25, 0., 0., 0., 0.25,
+        ];
+        assert_eq!(phases(&mut playback, expected.len(), 0.25), expected);
+    }
+
+    #[test]
+    fn loop_jumps_back_to_start_after_dwell() {
+        let mut playback = playback(LoopMode::Loop);
+        let expected = [0.5, 1., 0., 0., 0.5, 1.];
+        assert_eq!(phases(&mut playback, expected.len(), 0.5), expected);
+    }
+
+    #[test]
+    fn once_stops_at_target() {
+        let mut playback = playback(LoopMode::Once);
+        assert_eq!(phases(&mut playback, 3, 0.75), [0.75, 1., 1.]);
+        assert!(!playback.playing);
+        assert_eq!(playback.play(1.), 0.);
+    }
+}
@@ -0,0 +1,405 @@
+use super::i18n::Language;
+use super::input::{RotationSettings, ScrollZoomSettings};
+use super::state::{MyEguiApp, ViewMode};
+use super::theme::ThemeSettings;
+use crate::config::{GLOSS_MAX, GLOSS_MIN, SESSION_FILE_PATH};
+use crate::objects::light::{Attenuation, LightSource};
+use crate::objects::model3d::{Material, Model3D, Pattern};
+use image::Rgb;
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Сохраняемые параметры материала
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterialSession {
+    pub diffuse_reflectance_factor: f64,
+    pub specular_reflectance_factor: f64,
+    pub gloss: f64,
+    pub color: [u8; 3],
+    pub opacity: f64,
+    pub rim_strength: f64,
+    pub rim_power: f64,
+    pub rim_color: [u8; 3],
+    pub pattern: Pattern,
+    pub pattern_scale: f64,
+    pub texture_path: Option<PathBuf>,
+}
+
+impl Default for MaterialSession {
+    fn default() -> Self {
+        Self::from(&Material::default())
+    }
+}
+
+impl From<&Material> for MaterialSession {
+    fn from(material: &Material) -> Self {
+        Self {
+            diffuse_reflectance_factor: material.diffuse_reflectance_factor,
+            specular_reflectance_factor: material.specular_reflectance_factor,
+            gloss: material.gloss,
+            color: material.color.0,
+            opacity: material.opacity,
+            rim_strength: material.rim_strength,
+            rim_power: material.rim_power,
+            rim_color: material.rim_color.0,
+            pattern: material.pattern,
+            pattern_scale: material.pattern_scale,
+            texture_path: material.texture_path.clone(),
+        }
+    }
+}
+
+impl From<&MaterialSession> for Material {
+    fn from(session: &MaterialSession) -> Self {
+        Self {
+            diffuse_reflectance_factor: session.diffuse_reflectance_factor,
+            specular_reflectance_factor: session.specular_reflectance_factor,
+            // Старые сессии могли сохранить блеск вне нынешнего диапазона
+            gloss: session.gloss.clamp(GLOSS_MIN, GLOSS_MAX),
+            color: Rgb(session.color),
+            opacity: session.opacity.clamp(0., 1.),
+            rim_strength: session.rim_strength,
+            rim_power: session.rim_power,
+            rim_color: Rgb(session.rim_color),
+            pattern: session.pattern,
+            pattern_scale: session.pattern_scale,
+            // Текстура перечитывается с диска в apply_session
+            texture: None,
+            texture_path: None,
+        }
+    }
+}
+
+/// Сохраняемые параметры источника света
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightSession {
+    pub pos: [f64; 3],
+    pub intensity: f64,
+    pub color: [u8; 3],
+    #[serde(default)]
+    pub attenuation: Attenuation,
+}
+
+impl From<&LightSource> for LightSession {
+    fn from(light: &LightSource) -> Self {
+        Self {
+            pos: light.pos.coords.into(),
+            intensity: light.intensity,
+            color: light.color.0,
+            attenuation: light.attenuation,
+        }
+    }
+}
+
+impl From<&LightSession> for LightSource {
+    fn from(session: &LightSession) -> Self {
+        Self {
+            pos: Point3::from(session.pos),
+            intensity: session.intensity,
+            color: Rgb(session.color),
+            attenuation: session.attenuation,
+        }
+    }
+}
+
+/// Состояние приложения, сохраняемое между запусками
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub source_path: Option<PathBuf>,
+    pub target_path: Option<PathBuf>,
+    pub source_material: Option<MaterialSession>,
+    pub target_material: Option<MaterialSession>,
+    pub camera_pos: [f64; 3],
+    #[serde(default)]
+    pub camera_look_at: [f64; 3],
+    #[serde(default = "default_camera_up")]
+    pub camera_up: [f64; 3],
+    pub light: LightSession,
+    pub view_mode: ViewMode,
+    pub morph_phase: f64,
+    #[serde(default)]
+    pub scroll_zoom: ScrollZoomSettings,
+    #[serde(default)]
+    pub rotation: RotationSettings,
+    #[serde(default)]
+    pub theme: ThemeSettings,
+    #[serde(default)]
+    pub language: Language,
+}
+
+fn default_camera_up() -> [f64; 3] {
+    [0., 1., 0.]
+}
+
+impl Session {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Записать сессию в файл
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Прочитать сессию из файла
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let json = fs::read_to_string(path)?;
+        Ok(Self::from_json(&json)?)
+    }
+}
+
+impl MyEguiApp {
+    /// Собрать текущее состояние приложения для сохранения
+    pub fn session(&self) -> Session {
+        Session {
+            source_path: self.source_path.clone(),
+            target_path: self.target_path.clone(),
+            source_material: self
+                .source_mesh
+                .as_ref()
+                .map(|mesh| MaterialSession::from(mesh.borrow().material())),
+            target_material: self
+                .target_mesh
+                .as_ref()
+                .map(|mesh| MaterialSession::from(mesh.borrow().material())),
+            camera_pos: self.scene.camera.pos.coords.into(),
+            camera_look_at: self.scene.camera.look_at().coords.into(),
+            camera_up: self.scene.camera.up().into(),
+            light: LightSession::from(&self.scene.light_source),
+            view_mode: self.view_mode.clone(),
+            morph_phase: self.morph_phase,
+            scroll_zoom: self.scroll_zoom,
+            rotation: self.rotation,
+            theme: self.theme,
+            language: self.language,
+        }
+    }
+
+    /// Восстановить состояние приложения, заново загрузив сетки с диска.
+    /// Отсутствующие файлы пропускаются.
+    pub fn apply_session(&mut self, session: Session) {
+        for (path, material, is_target) in [
+            (&session.source_path, &session.source_material, false),
+            (&session.target_path, &session.target_material, true),
+        ] {
+            let Some(path) = path else {
+                continue;
+            };
+            if !path.exists() {
+                eprintln!(
+                    "Файл сессии ссылается на отсутствующую модель {}",
+                    path.display()
+                );
+                continue;
+            }
+
+            self.load_mesh_from_path(&path.to_string_lossy(), is_target);
+
+            let mesh = if is_target {
+                &self.target_mesh
+            } else {
+                &self.source_mesh
+            };
+            if let (Some(mesh), Some(material)) = (mesh, material) {
+                let mut restored = Material::from(material);
+                if let Some(texture_path) = &material.texture_path
+                    && let Err(e) = restored.load_texture(&texture_path.to_string_lossy())
+                {
+                    eprintln!("Ошибка загрузки текстуры {}: {}", texture_path.display(), e);
+                }
+                mesh.borrow_mut().material = restored;
+            }
+        }
+
+        let camera = &mut self.scene.camera;
+        camera.set_position(Point3::from(session.camera_pos));
+        camera.set_look_at(Point3::from(session.camera_look_at));
+        camera.set_up(Vector3::from(session.camera_up));
+        self.scene.light_source = LightSource::from(&session.light);
+        self.scroll_zoom = session.scroll_zoom;
+        self.rotation = session.rotation;
+        self.theme = session.theme;
+        self.language = session.language;
+
+        let needs_morph = matches!(
+            session.view_mode,
+            ViewMode::Morph | ViewMode::SourceSphere | ViewMode::TargetSphere
+        );
+        // Морфинг строится в фоне; до его готовности показывается исходный объект,
+        // а режим просмотра и стадия восстанавливаются в poll_morph_build
+        self.stop_morph_build();
+        if needs_morph {
+            self.morph_created = false;
+            self.start_morph_build();
+            if let Some(build) = self.morph_build.as_mut() {
+                build.restore = Some((session.view_mode.clone(), session.morph_phase));
+            }
+        }
+
+        let view_mode = if needs_morph {
+            ViewMode::Source
+        } else {
+            session.view_mode
+        };
+        self.set_view_mode(view_mode);
+        self.needs_redraw = true;
+    }
+
+    /// Сохранить сессию в файл настроек
+    pub fn save_session(&mut self) {
+        if let Err(e) = self.session().save(Path::new(SESSION_FILE_PATH)) {
+            eprintln!("Ошибка сохранения сессии: {}", e);
+            self.error_message = Some(format!("Не удалось сохранить сессию: {}", e));
+        }
+    }
+
+    /// Загрузить сессию из файла настроек
+    pub fn load_session(&mut self) {
+        match Session::load(Path::new(SESSION_FILE_PATH)) {
+            Ok(session) => self.apply_session(session),
+            Err(e) => {
+                eprintln!("Ошибка загрузки сессии: {}", e);
+                self.error_message = Some(format!("Не удалось загрузить сессию: {}", e));
+            }
+        }
+    }
+
+    /// Восстановить сессию при запуске, если файл настроек существует
+    pub fn restore_session(&mut self) {
+        if Path::new(SESSION_FILE_PATH).exists() {
+            self.load_session();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::theme::ThemeMode;
+
+    fn sample_session() -> Session {
+        Session {
+            source_path: Some(PathBuf::from("models/apple2.obj")),
+            target_path: None,
+            source_material: Some(MaterialSession::from(&Material::default())),
+            target_material: None,
+            camera_pos: [0., 0., 3.],
+            camera_look_at: [0., 0., 0.],
+            camera_up: [0., 1., 0.],
+            light: LightSession {
+                pos: [1., 2., 3.],
+                intensity: 15.,
+                color: [255, 255, 255],
+                attenuation: Attenuation::InverseSquare,
+            },
+            view_mode: ViewMode::Morph,
+            morph_phase: 0.42,
+            scroll_zoom: ScrollZoomSettings {
+                invert: true,
+                step: 0.005,
+            },
+            rotation: RotationSettings {
+                invert: true,
+                sensitivity: 1.5,
+            },
+            theme: ThemeSettings {
+                mode: ThemeMode::Light,
+                accent: [200, 80, 40],
+            },
+            language: Language::English,
+        }
+    }
+
+    #[test]
+    fn session_json_roundtrip() {
+        let session = sample_session();
+        let json = session.to_json().unwrap();
+        assert_eq!(Session::from_json(&json).unwrap(), session);
+    }
+
+    #[test]
+    fn theme_toggle_is_stored_in_session() {
+        let mut app = MyEguiApp::default();
+        assert_eq!(app.session().theme.mode, ThemeMode::Dark);
+
+        app.theme.toggle();
+        assert_eq!(app.session().theme.mode, ThemeMode::Light);
+    }
+
+    #[test]
+    fn material_conversion_roundtrip() {
+        let material = Material {
+            color: Rgb([10, 20, 30]),
+            ..Material::default()
+        };
+        let restored = Material::from(&MaterialSession::from(&material));
+        assert_eq!(restored.color, material.color);
+        assert_eq!(restored.gloss, material.gloss);
+        assert_eq!(restored.opacity, material.opacity);
+    }
+
+    #[test]
+    fn texture_path_is_restored_from_session() {
+        let texture_path =
+            std::env::temp_dir().join(format!("session_texture-{}.png", std::process::id()));
+        image::RgbImage::from_pixel(2, 2, Rgb([200, 10, 10]))
+            .save(&texture_path)
+            .unwrap();
+
+        let mut app = MyEguiApp::default();
+        let model = format!("{}/models/cube.obj", env!("CARGO_MANIFEST_DIR"));
+        app.load_mesh_from_path(&model, false);
+        let mesh = app.source_mesh.clone().unwrap();
+        mesh.borrow_mut()
+            .material
+            .load_texture(&texture_path.to_string_lossy())
+            .unwrap();
+
+        let session = Session::from_json(&app.session().to_json().unwrap()).unwrap();
+        let mut restored = MyEguiApp::default();
+        restored.apply_session(session);
+        std::fs::remove_file(&texture_path).unwrap();
+
+        let mesh = restored.source_mesh.unwrap();
+        let material = &mesh.borrow().material;
+        assert!(material.texture.is_some());
+        assert_eq!(material.texture_path.as_ref(), Some(&texture_path));
+    }
+
+    #[test]
+    fn morph_view_is_restored_after_background_build() {
+        let model = PathBuf::from(format!("{}/models/cube.obj", env!("CARGO_MANIFEST_DIR")));
+        let session = Session {
+            source_path: Some(model.clone()),
+            target_path: Some(model),
+            ..sample_session()
+        };
+
+        let mut app = MyEguiApp::default();
+        app.apply_session(session);
+        assert!(app.morph_build.is_some(), "морфинг строится в фоне");
+        assert_eq!(app.view_mode, ViewMode::Source);
+
+        while app.morph_build.is_some() {
+            app.poll_morph_build();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(app.morph_created);
+        assert_eq!(app.view_mode, ViewMode::Morph);
+        assert_eq!(app.morph_phase, 0.42);
+    }
+
+    #[test]
+    fn invalid_json_is_rejected() {
+        assert!(Session::from_json("{ \"camera_pos\": [0, 0] }").is_err());
+    }
+}
@@ -1,37 +1,103 @@
-use crate::objects::camera::Camera;
+use super::export::{AnimationExport, AnimationSink, GifSink, export_error};
+use super::i18n::{Language, Text};
+use super::input::{
+    RotationInertia, RotationSettings, ScrollZoomSettings, limit_scale_factor, limit_scale_factors,
+};
+use super::playback::MorphPlayback;
+use super::theme::ThemeSettings;
 use crate::objects::triangle_mesh::TriangleMesh;
 use rfd::FileDialog;
 use std::cell::RefCell;
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::time::Instant;
-
-use crate::config::{ASPECT_RATIO, BACKGROUND_COLOR, FAR_PLANE, FOV_DEGREES, NEAR_PLANE};
-use crate::objects::light::LightSource;
-use crate::objects::model3d::InteractiveModel;
-use crate::objects::morph::Morph;
-use crate::render::Renderer;
-use crate::render::z_buffer::ZBufferPerformer;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::config::{
+    ANIMATION_FPS, ANIMATION_FRAME_COUNT, ASPECT_RATIO, AUTO_FIT_FILL, BACKGROUND_COLOR,
+    DEGENERATE_COMPACTNESS, MORPH_METRICS_SAMPLES, MORPH_PHASE_STEP, SCALE_MISMATCH_RATIO,
+    WELD_TOLERANCE,
+};
+#[cfg(feature = "video")]
+use crate::config::{VIDEO_HEIGHT, VIDEO_WIDTH};
+use crate::objects::camera::PresetView;
+use crate::objects::model3d::{InteractiveModel, Model3D, ObjExportOptions, Scale};
+use crate::objects::morph::{Morph, PhaseMetrics};
+use crate::objects::transform::Transform;
+use crate::render::auto::AutoPerformer;
+use crate::render::hybrid::HybridPerformer;
+use crate::render::transparency::TransparencyPerformer;
+use crate::render::wireframe_drawer::WireframePerformer;
+use crate::render::worker::{FrameRequest, SceneRenderWorker, spawn_scene_renderer};
+use crate::render::{RenderTimings, Renderer, RendererKind};
 use crate::scene::Scene;
-use eframe::egui::{Context, TextureHandle};
-use image::{Rgb, RgbImage};
-use imageproc::definitions::HasWhite;
+use crate::utils::morphing::{CANCELLED, CancellationToken, MorphParams};
+#[cfg(feature = "video")]
+use crate::utils::video_export::VideoEncoder;
+use eframe::egui::{Context, Rect, TextureHandle};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, RgbImage};
 use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
 
 const IMG_WIDTH: u32 = 2000;
 const IMG_HEIGHT: u32 = 2000;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ViewMode {
     Source,
     Target,
     Morph,
+    // Параметризации исходной и целевой сеток на сфере (для отладки морфинга)
+    SourceSphere,
+    TargetSphere,
+}
+
+/// Процедурные примитивы, доступные для загрузки без OBJ файла
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Primitive {
+    Sphere,
+    Cube,
+    Torus,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 3] = [Primitive::Sphere, Primitive::Cube, Primitive::Torus];
+
+    pub fn build(self) -> TriangleMesh {
+        match self {
+            Primitive::Sphere => TriangleMesh::unit_sphere(3),
+            Primitive::Cube => TriangleMesh::cube(),
+            Primitive::Torus => TriangleMesh::torus(1., 0.4, 32),
+        }
+    }
+}
+
+/// Построение морфинга в фоновом потоке
+pub struct MorphBuild {
+    handle: JoinHandle<Result<Morph, String>>,
+    cancellation: CancellationToken,
+    // Режим просмотра и стадия, которые нужно восстановить после построения (из сессии)
+    pub(super) restore: Option<(ViewMode, f64)>,
 }
 
 pub struct MyEguiApp {
     pub texture: Option<TextureHandle>,
+    // Передний буфер: последний показанный кадр
     pub frame: RgbImage,
-    pub renderer: Box<dyn Renderer>,
+    // Задний буфер, освободившийся после смены кадра; в него рисуется следующий
+    back_buffer: Option<RgbImage>,
+    // Поток отрисовки; UI не ждёт его и показывает самый свежий готовый кадр
+    render_worker: SceneRenderWorker,
+    renderer_kind: RendererKind,
+    // Буфер глубины последнего кадра (см. ZBufferPerformer::depth_buffer)
+    pub depth_buffer: Vec<f64>,
+    // Время этапов последнего кадра (отладочная панель)
+    pub render_timings: Option<RenderTimings>,
+    // Время отрисовки последнего кадра без ожидания UI и vsync
+    pub render_time: Option<Duration>,
 
     pub fps: f64,
     pub last_frame_time: Instant,
@@ -39,18 +105,67 @@ pub struct MyEguiApp {
     // UI state
     pub selected_source_file: String,
     pub selected_target_file: String,
+    // Краткая статистика загруженных сеток
+    pub source_stats: String,
+    pub target_stats: String,
+    pub source_path: Option<PathBuf>,
+    pub target_path: Option<PathBuf>,
+    // Папка последнего успешно загруженного файла - с неё начинается диалог выбора
+    pub last_directory: Option<PathBuf>,
     pub view_mode: ViewMode,
     // Флаг: курсор находится над окном просмотра
     pub viewport_has_pointer: bool,
+    // Область окна просмотра на экране (для вращения трекболом)
+    pub viewport_rect: Option<Rect>,
+    pub scroll_zoom: ScrollZoomSettings,
+    pub rotation: RotationSettings,
+    pub rotation_inertia: RotationInertia,
+    // Тема и цвет выделения интерфейса
+    pub theme: ThemeSettings,
+    // Язык интерфейса
+    pub language: Language,
 
     // Object states
     pub source_mesh: Option<Rc<RefCell<TriangleMesh>>>,
     pub target_mesh: Option<Rc<RefCell<TriangleMesh>>>,
     pub morph_object: Option<Rc<RefCell<Morph>>>,
+    pub source_sphere: Option<Rc<RefCell<TriangleMesh>>>,
+    pub target_sphere: Option<Rc<RefCell<TriangleMesh>>>,
     pub morph_created: bool,
+    // Построение морфинга, выполняющееся в фоновом потоке
+    pub morph_build: Option<MorphBuild>,
+    // Допуски и условия сходимости, с которыми строится морфинг
+    pub morph_params: MorphParams,
+    // Отношение размеров исходного и целевого объектов, если оно слишком велико
+    pub scale_warning: Option<f64>,
 
     // Morph animation state
     pub morph_phase: f64,
+    // Шаг стадии морфинга клавишами-стрелками
+    pub morph_phase_step: f64,
+    // Проигрывание морфинга во времени
+    pub morph_playback: MorphPlayback,
+    // Объём и площадь морфинга по стадиям (строятся по запросу)
+    pub morph_metrics: Option<Vec<PhaseMetrics>>,
+    // Параметры экспорта отображаемого объекта в OBJ
+    pub obj_export: ObjExportOptions,
+    // Параметры экспорта анимации морфинга в GIF и видео
+    pub animation_frame_count: usize,
+    pub animation_fps: u32,
+    // Экспорт анимации, выполняющийся в фоновом потоке
+    pub animation_export: Option<AnimationExport>,
+    #[cfg(feature = "video")]
+    pub video_width: u32,
+    #[cfg(feature = "video")]
+    pub video_height: u32,
+
+    // Параметры обработки сетки
+    pub subdivision_iterations: usize,
+    pub decimation_ratio: f64,
+    // Сваривать совпадающие вершины при загрузке
+    pub weld_on_load: bool,
+    // Масштабировать загруженный объект так, чтобы он помещался в кадр
+    pub auto_fit_on_load: bool,
 
     // Error handling
     pub error_message: Option<String>,
@@ -66,45 +181,88 @@ pub struct MyEguiApp {
     pub scene: Scene,
 }
 
+/// Запускает поток отрисовки с рендерером выбранного вида
+fn spawn_renderer(kind: RendererKind) -> SceneRenderWorker {
+    match kind {
+        RendererKind::Solid => spawn_scene_renderer(AutoPerformer::new(IMG_WIDTH, IMG_HEIGHT)),
+        RendererKind::Hybrid => spawn_scene_renderer(HybridPerformer::new(IMG_WIDTH, IMG_HEIGHT)),
+        RendererKind::Transparent => {
+            spawn_scene_renderer(TransparencyPerformer::new(IMG_WIDTH, IMG_HEIGHT))
+        }
+        RendererKind::Wireframe => spawn_scene_renderer(WireframePerformer),
+    }
+}
+
+/// Отрисовывает сцену в отдельное изображение рендерером выбранного вида
+pub fn render_scene(kind: RendererKind, scene: &Scene, width: u32, height: u32) -> RgbImage {
+    match kind {
+        RendererKind::Solid => AutoPerformer::new(width, height).create_frame(width, height, scene),
+        RendererKind::Hybrid => {
+            HybridPerformer::new(width, height).create_frame(width, height, scene)
+        }
+        RendererKind::Transparent => {
+            TransparencyPerformer::new(width, height).create_frame(width, height, scene)
+        }
+        RendererKind::Wireframe => WireframePerformer.create_frame(width, height, scene),
+    }
+}
+
 impl Default for MyEguiApp {
     fn default() -> Self {
-        let camera = Camera::new(
-            Point3::new(0., 0., 3.),
-            Point3::new(0.0, 0.0, 0.0),
-            Vector3::new(0.0, 1.0, 0.0),
-            FOV_DEGREES.to_radians(),
-            ASPECT_RATIO,
-            NEAR_PLANE.into(),
-            FAR_PLANE,
-        );
-        let light_source = LightSource {
-            pos: Point3::new(0., 0., 3.),
-            intensity: 15.,
-            color: Rgb::white(),
-        };
-
-        let scene = Scene {
-            camera,
-            light_source,
-            object: None,
-        };
+        let scene = Scene::new(ASPECT_RATIO);
 
         Self {
             texture: None,
             frame: RgbImage::from_pixel(IMG_WIDTH, IMG_HEIGHT, BACKGROUND_COLOR),
             scene,
-            renderer: Box::new(ZBufferPerformer::new(IMG_WIDTH, IMG_HEIGHT)),
+            back_buffer: None,
+            render_worker: spawn_renderer(RendererKind::default()),
+            renderer_kind: RendererKind::default(),
+            depth_buffer: Vec::new(),
+            render_timings: None,
+            render_time: None,
             fps: 0.0,
             last_frame_time: Instant::now(),
             selected_source_file: String::new(),
             selected_target_file: String::new(),
+            source_stats: String::new(),
+            target_stats: String::new(),
+            source_path: None,
+            target_path: None,
+            last_directory: None,
             view_mode: ViewMode::Source,
             viewport_has_pointer: false,
+            viewport_rect: None,
+            scroll_zoom: ScrollZoomSettings::default(),
+            rotation: RotationSettings::default(),
+            rotation_inertia: RotationInertia::default(),
+            theme: ThemeSettings::default(),
+            language: Language::default(),
             source_mesh: None,
             target_mesh: None,
             morph_object: None,
+            source_sphere: None,
+            target_sphere: None,
             morph_created: false,
+            morph_build: None,
+            morph_params: MorphParams::default(),
+            scale_warning: None,
             morph_phase: 0.0,
+            morph_phase_step: MORPH_PHASE_STEP,
+            morph_playback: MorphPlayback::default(),
+            morph_metrics: None,
+            obj_export: ObjExportOptions::default(),
+            animation_frame_count: ANIMATION_FRAME_COUNT,
+            animation_fps: ANIMATION_FPS,
+            animation_export: None,
+            #[cfg(feature = "video")]
+            video_width: VIDEO_WIDTH,
+            #[cfg(feature = "video")]
+            video_height: VIDEO_HEIGHT,
+            subdivision_iterations: 1,
+            decimation_ratio: 0.5,
+            weld_on_load: true,
+            auto_fit_on_load: true,
             error_message: None,
             needs_redraw: false,
             viewport_width: IMG_WIDTH,
@@ -115,57 +273,120 @@ impl Default for MyEguiApp {
 
 impl MyEguiApp {
     pub fn update_frame(&mut self, ctx: &Context) {
-        // Проверяем, нужно ли перерисовывать кадр
-        if !self.needs_redraw {
-            if self.texture.is_some() {
-                return;
+        // Показываем готовый кадр, если поток отрисовки его закончил
+        if let Some(rendered) = self.render_worker.poll() {
+            let egui_image = egui::ColorImage::from_rgb(
+                [
+                    rendered.image.width() as usize,
+                    rendered.image.height() as usize,
+                ],
+                rendered.image.as_raw(),
+            );
+
+            if let Some(texture) = self.texture.as_mut() {
+                texture.set(egui_image, Default::default());
+            } else {
+                self.texture =
+                    Some(ctx.load_texture("rendered_image", egui_image, Default::default()));
             }
+
+            self.depth_buffer = rendered.depth;
+            self.render_timings = rendered.timings;
+            self.render_time = Some(rendered.render_time);
+            // Прежний передний буфер становится задним
+            self.back_buffer = Some(std::mem::replace(&mut self.frame, rendered.image));
+        }
+
+        // Проверяем, нужно ли перерисовывать кадр
+        if self.texture.is_none() {
             self.needs_redraw = true;
         }
+        if !self.needs_redraw || self.render_worker.is_busy() {
+            return;
+        }
 
-        // Рендерим сцену
-        self.renderer.create_frame_mut(&mut self.frame, &self.scene);
+        // Запрашиваем отрисовку текущего состояния сцены в задний буфер
+        let (width, height) = (self.viewport_width, self.viewport_height);
+        let buffer = self
+            .back_buffer
+            .take()
+            .filter(|buffer| buffer.dimensions() == (width, height))
+            .unwrap_or_else(|| RgbImage::from_pixel(width, height, BACKGROUND_COLOR));
+        let request = FrameRequest {
+            scene: self.scene.snapshot(),
+            buffer,
+        };
+        if self.render_worker.submit(request) {
+            self.needs_redraw = false;
+        }
+    }
 
-        let egui_image = egui::ColorImage::from_rgb(
-            [self.frame.width() as usize, self.frame.height() as usize],
-            self.frame.as_raw(),
-        );
+    pub fn renderer_kind(&self) -> RendererKind {
+        self.renderer_kind
+    }
 
-        if self.texture.is_none() {
-            self.texture = Some(ctx.load_texture("rendered_image", egui_image, Default::default()));
-        } else {
-            self.texture
-                .as_mut()
-                .unwrap()
-                .set(egui_image, Default::default());
+    /// Переключает рендерер окна просмотра, перезапуская поток отрисовки
+    pub fn set_renderer_kind(&mut self, kind: RendererKind) {
+        if self.renderer_kind != kind {
+            self.renderer_kind = kind;
+            self.render_worker = spawn_renderer(kind);
+            self.needs_redraw = true;
         }
+    }
+
+    /// Отрисовывает текущую сцену выбранным рендерером в отдельное изображение заданного
+    /// размера, не затрагивая окно просмотра. Кадр рисуется отдельным рендерером с копией
+    /// камеры, соотношение сторон которой подстраивается под изображение, поэтому размер
+    /// экспорта не зависит от размера окна.
+    pub fn render_to_image(&self, width: u32, height: u32) -> RgbImage {
+        render_scene(
+            self.renderer_kind,
+            &self.export_scene(width, height),
+            width,
+            height,
+        )
+    }
 
-        self.needs_redraw = false;
+    /// Сцена с камерой, подогнанной под соотношение сторон кадра `width`x`height`.
+    /// Объекты общие с основной сценой
+    fn export_scene(&self, width: u32, height: u32) -> Scene {
+        let mut camera = self.scene.camera.clone();
+        camera.set_aspect_ratio(width as f64 / height as f64);
+        self.scene.with_camera(camera)
     }
 
-    pub fn update_fps(&mut self) {
+    /// Обновляет счётчик FPS и возвращает время, прошедшее с прошлого кадра, в секундах
+    pub fn update_fps(&mut self) -> f64 {
         let now = Instant::now();
         let frame_time = now.duration_since(self.last_frame_time).as_secs_f64();
         self.last_frame_time = now;
         self.fps = 1.0 / frame_time;
+        frame_time
     }
 
     pub fn load_mesh_from_path(&mut self, file_path: &str, is_target: bool) {
         match TriangleMesh::from_obj(file_path) {
-            Ok(mesh) => {
-                if is_target {
-                    self.target_mesh = Some(Rc::new(RefCell::new(mesh)));
-                    if let Some(file_name) = PathBuf::from(file_path).file_name() {
-                        self.selected_target_file = file_name.to_string_lossy().to_string();
-                    }
-                } else {
-                    self.source_mesh = Some(Rc::new(RefCell::new(mesh)));
-                    if let Some(file_name) = PathBuf::from(file_path).file_name() {
-                        self.selected_source_file = file_name.to_string_lossy().to_string();
-                    }
+            Ok(mut mesh) => {
+                if self.weld_on_load {
+                    mesh.weld_vertices(WELD_TOLERANCE);
                 }
-                self.morph_created = false;
-                self.update_scene_object();
+                // Согласованный обход нужен отсечению задних граней; сваривание
+                // выполняется раньше, чтобы соседние треугольники имели общие рёбра
+                let flipped = mesh.fix_winding();
+                if flipped > 0 {
+                    eprintln!("Исправлен порядок обхода {} треугольников", flipped);
+                }
+                if self.auto_fit_on_load {
+                    self.fit_mesh_to_view(&mut mesh);
+                }
+
+                let path = PathBuf::from(file_path);
+                self.last_directory = path.parent().map(Path::to_path_buf);
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                self.set_mesh(mesh, name, Some(path), is_target);
             }
             Err(e) => {
                 eprintln!("Ошибка загрузки модели {}: {}", file_path, e);
@@ -174,10 +395,165 @@ impl MyEguiApp {
         }
     }
 
+    /// Масштабирует сетку так, чтобы она занимала AUTO_FIT_FILL видимой области.
+    /// Масштаб переносится в вершины, и сброс преобразований его не отменяет
+    fn fit_mesh_to_view(&mut self, mesh: &mut TriangleMesh) {
+        let radius = mesh
+            .vertices()
+            .iter()
+            .map(|v| v.coords.norm())
+            .fold(0., f64::max);
+        if radius > f64::EPSILON {
+            mesh.scale_geometry(AUTO_FIT_FILL * self.scene.camera.framing_radius() / radius);
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Устанавливает стадию морфинга, ограничивая её отрезком [0, 1], и обновляет
+    /// морф-объект, если стадия изменилась
+    pub fn set_morph_phase(&mut self, phase: f64) {
+        let phase = phase.clamp(0., 1.);
+        if (phase - self.morph_phase).abs() <= f64::EPSILON {
+            return;
+        }
+
+        self.morph_phase = phase;
+        if let Some(ref morph) = self.morph_object {
+            morph.borrow_mut().update(self.morph_phase);
+        }
+        self.needs_redraw = true; // Требуется перерисовка при изменении фазы морфинга
+    }
+
+    /// Сдвигает стадию морфинга на `delta`
+    pub fn step_morph_phase(&mut self, delta: f64) {
+        self.set_morph_phase(self.morph_phase + delta);
+    }
+
+    /// Запускает или приостанавливает проигрывание морфинга
+    pub fn toggle_morph_playback(&mut self) {
+        if self.morph_playback.playing {
+            self.morph_playback.pause();
+        } else {
+            let phase = self.morph_playback.play(self.morph_phase);
+            self.set_morph_phase(phase);
+        }
+    }
+
+    /// Пересчитывает объём и площадь морфинга по стадиям для графика
+    pub fn compute_morph_metrics(&mut self) {
+        self.morph_metrics = self
+            .morph_object
+            .as_ref()
+            .map(|morph| morph.borrow().sample_metrics(MORPH_METRICS_SAMPLES));
+    }
+
+    /// Открыто модальное окно с ошибкой: сцена замирает до его закрытия
+    pub fn is_error_shown(&self) -> bool {
+        self.error_message.is_some()
+    }
+
+    /// Продвигает проигрываемый морфинг на время кадра `dt` (секунды)
+    pub fn advance_morph_playback(&mut self, dt: f64) {
+        if self.view_mode != ViewMode::Morph || !self.morph_created || self.is_error_shown() {
+            return;
+        }
+        let phase = self.morph_playback.advance(self.morph_phase, dt);
+        self.set_morph_phase(phase);
+    }
+
+    /// Направляет камеру на центр всех объектов и отодвигает так, чтобы они
+    /// целиком поместились в кадр
+    pub fn frame_all(&mut self) {
+        let Some((min, max)) = self.scene.bounding_box() else {
+            return;
+        };
+        let radius = (max - min).norm() / 2.;
+        let distance = self.scene.camera.framing_distance(radius);
+        self.aim_camera(nalgebra::center(&min, &max), radius, distance);
+    }
+
+    /// Направляет камеру на центр выделенного объекта, не меняя расстояния до цели
+    pub fn center_on_selection(&mut self) {
+        let Some(object) = self.scene.selected_object() else {
+            return;
+        };
+        let Some((min, max)) = object.borrow().bounding_box() else {
+            return;
+        };
+        let radius = (max - min).norm() / 2.;
+        let distance = self.scene.camera.distance();
+        self.aim_camera(nalgebra::center(&min, &max), radius, distance);
+    }
+
+    /// Переносит точку наблюдения в `target` и ставит камеру на расстоянии `distance`
+    /// от неё, сохраняя направление взгляда. Камера не подходит ближе чем на
+    /// `radius` (с запасом в ближнюю плоскость), чтобы не оказаться внутри объектов.
+    fn aim_camera(&mut self, target: Point3<f64>, radius: f64, distance: f64) {
+        let camera = &mut self.scene.camera;
+        let direction = (camera.pos - camera.look_at())
+            .try_normalize(f64::EPSILON)
+            .unwrap_or_else(Vector3::z);
+        let distance = distance.max(radius + 2. * camera.near_plane());
+
+        camera.set_look_at(target);
+        camera.set_position(target + direction * distance);
+        self.needs_redraw = true;
+    }
+
+    /// Загружает процедурный примитив в качестве исходного или целевого объекта
+    pub fn load_primitive(&mut self, primitive: Primitive, is_target: bool) {
+        let name = self.language.tr(Text::from(primitive)).to_string();
+        self.set_mesh(primitive.build(), name, None, is_target);
+    }
+
+    fn set_mesh(
+        &mut self,
+        mesh: TriangleMesh,
+        name: String,
+        path: Option<PathBuf>,
+        is_target: bool,
+    ) {
+        let mesh = Some(Rc::new(RefCell::new(mesh)));
+        if is_target {
+            self.target_mesh = mesh;
+            self.target_path = path;
+            self.selected_target_file = name;
+        } else {
+            self.source_mesh = mesh;
+            self.source_path = path;
+            self.selected_source_file = name;
+        }
+        self.morph_created = false;
+        self.cancel_morph_build();
+        self.refresh_mesh_stats();
+        self.update_scene_object();
+    }
+
+    /// Пересчитывает статистику исходной и целевой сеток
+    pub fn refresh_mesh_stats(&mut self) {
+        let stats = |mesh: &Option<Rc<RefCell<TriangleMesh>>>| {
+            mesh.as_ref()
+                .map(|mesh| mesh_stats(&mesh.borrow()))
+                .unwrap_or_default()
+        };
+        self.source_stats = stats(&self.source_mesh);
+        self.target_stats = stats(&self.target_mesh);
+    }
+
+    /// Папка, открываемая диалогом выбора файла: папка последней загруженной
+    /// модели, если она ещё существует, иначе текущая рабочая папка
+    fn dialog_directory(&self) -> PathBuf {
+        self.last_directory
+            .clone()
+            .filter(|dir| dir.is_dir())
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default()
+    }
+
     pub fn open_file_dialog(&mut self, is_target: bool) {
         if let Some(path) = FileDialog::new()
             .add_filter("OBJ файлы", &["obj"])
-            .set_directory("./code/models")
+            .set_directory(self.dialog_directory())
             .pick_file()
         {
             let path_str = path.to_string_lossy().to_string();
@@ -185,30 +561,382 @@ impl MyEguiApp {
         }
     }
 
-    pub fn create_morph_object(&mut self) {
-        if self.source_mesh.is_none() || self.target_mesh.is_none() {
-            return;
-        }
-
-        let source_mesh = self.source_mesh.as_ref().unwrap().borrow().clone();
-        let target_mesh = self.target_mesh.as_ref().unwrap().borrow().clone();
-
-        match Morph::new(source_mesh, target_mesh) {
+    fn finish_morph(&mut self, result: Result<Morph, String>) {
+        match result {
             Ok(morph) => {
+                let sphere = |mesh: &TriangleMesh| Some(Rc::new(RefCell::new(mesh.clone())));
+                self.source_sphere = sphere(morph.parametrized_source());
+                self.target_sphere = sphere(morph.parametrized_target());
                 self.morph_object = Some(Rc::new(RefCell::new(morph)));
                 self.morph_created = true;
                 self.morph_phase = 0.0; // Сброс фазы морфинга
+                self.morph_playback.pause();
+                self.morph_metrics = None;
                 self.update_scene_object();
             }
+            Err(e) if e == CANCELLED => {}
             Err(e) => {
                 eprintln!("Ошибка создания морфинга: {}", e);
-                self.error_message =
-                    Some("Не удалось создать морфинг: сетка повреждена или не замкнута)".into());
+                self.error_message = Some(format!("Не удалось создать морфинг: {}", e));
                 self.morph_created = false;
             }
         }
     }
 
+    /// Запускает построение морфинга в фоновом потоке, не блокируя интерфейс
+    pub fn start_morph_build(&mut self) {
+        if self.morph_build.is_some() {
+            return;
+        }
+        let (Some(source_mesh), Some(target_mesh)) = (&self.source_mesh, &self.target_mesh) else {
+            return;
+        };
+
+        let source_mesh = source_mesh.borrow().clone();
+        let target_mesh = target_mesh.borrow().clone();
+        self.scale_warning = scale_mismatch(&source_mesh, &target_mesh);
+        // У каждого построения свой флаг отмены
+        let params = MorphParams {
+            cancellation: CancellationToken::default(),
+            ..self.morph_params.clone()
+        };
+        let cancellation = params.cancellation.clone();
+
+        let handle = thread::spawn(move || Morph::with_params(source_mesh, target_mesh, &params));
+        self.morph_build = Some(MorphBuild {
+            handle,
+            cancellation,
+            restore: None,
+        });
+    }
+
+    /// Проверяет, завершилось ли фоновое построение морфинга, и забирает результат
+    pub fn poll_morph_build(&mut self) {
+        if !self
+            .morph_build
+            .as_ref()
+            .is_some_and(|build| build.handle.is_finished())
+        {
+            return;
+        }
+
+        let build = self.morph_build.take().unwrap();
+        let result = build
+            .handle
+            .join()
+            .unwrap_or_else(|_| Err("поток построения аварийно завершился".to_string()));
+
+        // Построение могло завершиться до проверки флага, но результат уже не нужен
+        if !build.cancellation.is_cancelled() {
+            self.finish_morph(result);
+            if let (true, Some((view_mode, phase))) = (self.morph_created, build.restore) {
+                self.set_morph_phase(phase);
+                self.set_view_mode(view_mode);
+            }
+        }
+    }
+
+    /// Просит фоновое построение морфинга остановиться. Поток завершается
+    /// при ближайшей проверке флага и забирается в `poll_morph_build`
+    pub fn cancel_morph_build(&mut self) {
+        if let Some(build) = &self.morph_build {
+            build.cancellation.cancel();
+        }
+    }
+
+    /// Масштабирует исходный и целевой объекты до единичного радиуса
+    /// описанной сферы и перестраивает морфинг
+    pub fn normalize_mesh_scales(&mut self) {
+        for mesh in [&self.source_mesh, &self.target_mesh].into_iter().flatten() {
+            let radius = mesh.borrow().bounding_radius();
+            if radius > f64::EPSILON {
+                mesh.borrow_mut().scale(1. / radius);
+            }
+        }
+        self.scale_warning = None;
+        self.morph_created = false;
+        self.stop_morph_build();
+        self.start_morph_build();
+        self.needs_redraw = true;
+    }
+
+    /// Отменяет фоновое построение и дожидается завершения потока
+    pub fn stop_morph_build(&mut self) {
+        if let Some(build) = self.morph_build.take() {
+            build.cancellation.cancel();
+            let _ = build.handle.join();
+        }
+    }
+
+    /// Сохраняет в CSV соответствие вершин суперсетки точкам исходной и целевой сеток
+    pub fn export_correspondence_csv(&mut self) {
+        let Some(morph) = self.morph_object.as_ref() else {
+            return;
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("CSV файлы", &["csv"])
+            .set_file_name("correspondence.csv")
+            .save_file()
+        else {
+            return;
+        };
+
+        let mut csv =
+            String::from("src_triangle,src_u,src_v,src_w,dst_triangle,dst_u,dst_v,dst_w\n");
+        for (src_tri, src_bary, dst_tri, dst_bary) in morph.borrow().export_correspondence() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                src_tri,
+                src_bary.x,
+                src_bary.y,
+                src_bary.z,
+                dst_tri,
+                dst_bary.x,
+                dst_bary.y,
+                dst_bary.z
+            ));
+        }
+
+        if let Err(e) = std::fs::write(&path, csv) {
+            eprintln!("Ошибка экспорта соответствия вершин: {}", e);
+            self.error_message = Some(format!("Не удалось сохранить CSV: {}", e));
+        }
+    }
+
+    /// Сохраняет в PNG кадр текущей сцены размером с окно просмотра
+    pub fn export_frame_png(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("PNG изображения", &["png"])
+            .set_file_name("frame.png")
+            .save_file()
+        else {
+            return;
+        };
+
+        let image = self.render_to_image(self.viewport_width, self.viewport_height);
+        if let Err(e) = image.save(&path) {
+            eprintln!("Ошибка сохранения кадра: {}", e);
+            self.error_message = Some(format!("Не удалось сохранить изображение: {}", e));
+        }
+    }
+
+    /// Сохраняет отображаемый объект в OBJ с параметрами `obj_export`
+    pub fn export_obj(&mut self) {
+        let Some(object) = self.scene.object.clone() else {
+            return;
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("OBJ файлы", &["obj"])
+            .set_file_name("model.obj")
+            .save_file()
+        else {
+            return;
+        };
+
+        let obj = object.borrow().to_obj_string(&self.obj_export);
+        if let Err(e) = std::fs::write(&path, obj) {
+            eprintln!("Ошибка экспорта OBJ: {}", e);
+            self.error_message = Some(format!("Не удалось сохранить OBJ: {}", e));
+        }
+    }
+
+    /// Предлагает выбрать файл и сохраняет в него GIF анимацию морфинга
+    pub fn save_morph_gif(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("GIF анимации", &["gif"])
+            .set_file_name("morph.gif")
+            .save_file()
+        else {
+            return;
+        };
+
+        let (frame_count, fps) = (self.animation_frame_count, self.animation_fps);
+        if let Err(e) = self.export_morph_gif(&path, frame_count, fps) {
+            eprintln!("Ошибка экспорта GIF: {}", e);
+            self.error_message = Some(format!("Не удалось сохранить GIF: {}", e));
+        }
+    }
+
+    /// Начинает запись GIF анимации морфинга из `frame_count` кадров размером с окно
+    /// просмотра с частотой `fps`. Кадры рисуются в фоне, см. poll_animation_export
+    pub fn export_morph_gif(
+        &mut self,
+        path: &Path,
+        frame_count: usize,
+        fps: u32,
+    ) -> Result<(), String> {
+        if fps == 0 {
+            return Err("Частота кадров должна быть положительной".to_string());
+        }
+        self.check_animation_export(frame_count)?;
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = GifEncoder::new(BufWriter::new(file));
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| e.to_string())?;
+        let sink = GifSink {
+            encoder,
+            delay: Delay::from_numer_denom_ms(1000, fps),
+        };
+
+        let (width, height) = (self.viewport_width, self.viewport_height);
+        self.start_animation_export(path, width, height, frame_count, Box::new(sink));
+        Ok(())
+    }
+
+    /// Предлагает выбрать файл и сохраняет в него видео морфинга
+    #[cfg(feature = "video")]
+    pub fn save_morph_video(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("Видео", &["mp4", "webm"])
+            .set_file_name("morph.mp4")
+            .save_file()
+        else {
+            return;
+        };
+
+        let (width, height) = (self.video_width, self.video_height);
+        let (frame_count, fps) = (self.animation_frame_count, self.animation_fps);
+        if let Err(e) = self.export_morph_video(&path, width, height, frame_count, fps) {
+            eprintln!("Ошибка экспорта видео: {}", e);
+            self.error_message = Some(format!("Не удалось сохранить видео: {}", e));
+        }
+    }
+
+    /// Начинает запись видео морфинга размером `width`x`height` через ffmpeg.
+    /// Кадры рисуются в фоне, см. poll_animation_export
+    #[cfg(feature = "video")]
+    pub fn export_morph_video(
+        &mut self,
+        path: &Path,
+        width: u32,
+        height: u32,
+        frame_count: usize,
+        fps: u32,
+    ) -> Result<(), String> {
+        if fps == 0 {
+            return Err("Частота кадров должна быть положительной".to_string());
+        }
+        self.check_animation_export(frame_count)?;
+        let encoder = VideoEncoder::start(path, width, height, fps)?;
+        self.start_animation_export(path, width, height, frame_count, Box::new(encoder));
+        Ok(())
+    }
+
+    /// Проверяет, что анимацию можно начать записывать
+    fn check_animation_export(&self, frame_count: usize) -> Result<(), String> {
+        if self.animation_export.is_some() {
+            return Err("Предыдущий экспорт анимации ещё не завершён".to_string());
+        }
+        if self.morph_object.is_none() {
+            return Err("Морфинг не построен".to_string());
+        }
+        if frame_count == 0 {
+            return Err("Число кадров должно быть положительным".to_string());
+        }
+        Ok(())
+    }
+
+    /// Запускает отрисовку морфинга на `frame_count` равномерно распределённых стадиях
+    /// от 0 до 1 в фоновом потоке. Поток рисует копию морфинга, поэтому стадия
+    /// и объект сцены в окне просмотра не меняются
+    fn start_animation_export(
+        &mut self,
+        path: &Path,
+        width: u32,
+        height: u32,
+        frame_count: usize,
+        sink: Box<dyn AnimationSink>,
+    ) {
+        let Some(morph) = self.morph_object.clone() else {
+            return;
+        };
+        let phases = (0..frame_count)
+            .map(|i| match frame_count {
+                1 => self.morph_phase,
+                _ => i as f64 / (frame_count - 1) as f64,
+            })
+            .collect();
+
+        let mut scene = self.export_scene(width, height);
+        scene.object = Some(morph as Rc<RefCell<dyn InteractiveModel>>);
+        self.animation_export = Some(AnimationExport::spawn(
+            scene.snapshot(),
+            self.renderer_kind,
+            (width, height),
+            phases,
+            sink,
+            path.to_path_buf(),
+        ));
+    }
+
+    /// Забирает результат завершившегося экспорта анимации
+    pub fn poll_animation_export(&mut self) {
+        if !self
+            .animation_export
+            .as_ref()
+            .is_some_and(AnimationExport::is_finished)
+        {
+            return;
+        }
+
+        let export = self.animation_export.take().unwrap();
+        if let Some(e) = export_error(export.join()) {
+            eprintln!("Ошибка экспорта анимации: {}", e);
+            self.error_message = Some(format!("Не удалось сохранить анимацию: {}", e));
+        }
+    }
+
+    /// Просит фоновый экспорт анимации остановиться; недописанный файл удаляется
+    pub fn cancel_animation_export(&mut self) {
+        if let Some(export) = &self.animation_export {
+            export.cancel();
+        }
+    }
+
+    /// Отменяет фоновый экспорт анимации и дожидается завершения потока
+    pub fn stop_animation_export(&mut self) {
+        if let Some(export) = self.animation_export.take() {
+            export.cancel();
+            let _ = export.join();
+        }
+    }
+
+    /// Возвращает сетку, отображаемую в режиме исходного или целевого объекта
+    pub fn current_mesh(&self) -> Option<Rc<RefCell<TriangleMesh>>> {
+        match self.view_mode {
+            ViewMode::Source => self.source_mesh.clone(),
+            ViewMode::Target => self.target_mesh.clone(),
+            ViewMode::Morph | ViewMode::SourceSphere | ViewMode::TargetSphere => None,
+        }
+    }
+
+    /// Сглаживает текущую сетку подразбиением Лупа
+    pub fn subdivide_current_mesh(&mut self) {
+        if let Some(mesh) = self.current_mesh() {
+            mesh.borrow_mut()
+                .subdivide_loop(self.subdivision_iterations);
+            self.morph_created = false; // Морфинг построен по старой сетке
+            self.cancel_morph_build();
+            self.refresh_mesh_stats();
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Упрощает текущую сетку, оставляя долю `decimation_ratio` треугольников
+    pub fn decimate_current_mesh(&mut self) {
+        if let Some(mesh) = self.current_mesh() {
+            let mut mesh = mesh.borrow_mut();
+            let target_triangles =
+                (mesh.triangles().len() as f64 * self.decimation_ratio).round() as usize;
+            mesh.decimate(target_triangles);
+            self.morph_created = false; // Морфинг построен по старой сетке
+            self.needs_redraw = true;
+        }
+        self.cancel_morph_build();
+        self.refresh_mesh_stats();
+    }
+
     pub fn reset_current_object(&mut self) {
         if let Some(object_to_reset) = self.scene.object.as_ref() {
             object_to_reset.borrow_mut().reset_transformations();
@@ -225,33 +953,57 @@ impl MyEguiApp {
         self.needs_redraw = true; // Требуется перерисовка после поворота
     }
 
+    /// Поворачивает объект к камере стороной `view`, заменяя накопленный поворот.
+    /// Масштаб и перенос объекта сохраняются
+    pub fn snap_to_view(&mut self, view: PresetView) {
+        if let Some(object) = self.scene.object.as_ref() {
+            let mut object = object.borrow_mut();
+            let mut transform = Transform::from_matrix(object.model_matrix());
+            transform.orientation = self.scene.camera.preset_orientation(view);
+            object.set_model_matrix(&transform.matrix());
+        }
+        self.rotation_inertia.stop();
+        self.needs_redraw = true; // Требуется перерисовка после смены вида
+    }
+
     pub fn apply_button_scale(&mut self, factor: f64) {
         if let Some(object) = self.scene.object.as_ref() {
-            object.borrow_mut().scale(factor);
+            let mut object = object.borrow_mut();
+            let scaling = Transform::from_matrix(object.model_matrix()).scaling;
+            object.scale(limit_scale_factor(&scaling, factor));
+        }
+        self.needs_redraw = true; // Требуется перерисовка после масштабирования
+    }
+
+    pub fn apply_button_scale_xyz(&mut self, x: f64, y: f64, z: f64) {
+        if let Some(object) = self.scene.object.as_ref() {
+            let mut object = object.borrow_mut();
+            let scaling = Transform::from_matrix(object.model_matrix()).scaling;
+            let factors = limit_scale_factors(&scaling, &Vector3::new(x, y, z));
+            object.scale_xyz((factors.x, factors.y, factors.z));
         }
         self.needs_redraw = true; // Требуется перерисовка после масштабирования
     }
 
     pub fn update_viewport_size(&mut self, width: u32, height: u32) {
+        // Свёрнутая область просмотра сохраняет прежний размер: пустой кадр не нужен,
+        // а соотношение сторон камеры было бы NaN или бесконечностью
+        if width == 0 || height == 0 {
+            return;
+        }
+
         // Проверяем, изменился ли размер viewport
         if self.viewport_width != width || self.viewport_height != height {
             self.viewport_width = width;
             self.viewport_height = height;
 
-            // Пересоздаем изображение с новым размером
-            self.frame = RgbImage::from_pixel(width, height, BACKGROUND_COLOR);
+            // Задний буфер старого размера не подходит; новый создастся при следующем запросе,
+            // и поток отрисовки по его размеру вызовет Renderer::resize
+            self.back_buffer = None;
 
             // Обновляем aspect ratio камеры
             let new_aspect_ratio = width as f64 / height as f64;
-            self.scene.camera = Camera::new(
-                self.scene.camera.pos,
-                Point3::new(0.0, 0.0, 0.0),
-                Vector3::new(0.0, 1.0, 0.0),
-                FOV_DEGREES.to_radians(),
-                new_aspect_ratio,
-                NEAR_PLANE,
-                FAR_PLANE,
-            );
+            self.scene.camera.set_aspect_ratio(new_aspect_ratio);
 
             // Помечаем что нужна перерисовка
             self.needs_redraw = true;
@@ -280,8 +1032,377 @@ impl MyEguiApp {
                 .morph_object
                 .as_ref()
                 .map(|rc| rc.clone() as Rc<RefCell<dyn InteractiveModel>>),
+            ViewMode::SourceSphere => self
+                .source_sphere
+                .as_ref()
+                .map(|rc| rc.clone() as Rc<RefCell<dyn InteractiveModel>>),
+            ViewMode::TargetSphere => self
+                .target_sphere
+                .as_ref()
+                .map(|rc| rc.clone() as Rc<RefCell<dyn InteractiveModel>>),
         };
         self.scene.object = object_to_set;
+        // Выделение относится к прежнему объекту
+        self.scene.selected = None;
         self.needs_redraw = true;
     }
 }
+
+/// Количество вершин и треугольников сетки и род поверхности
+fn mesh_stats(mesh: &TriangleMesh) -> String {
+    let genus = match mesh.validate_manifold() {
+        Ok(genus) => format!("род {}", genus),
+        Err(_) => "не многообразие".to_string(),
+    };
+    let mut stats = format!(
+        "Вершин: {}, треугольников: {}, {}\nПлощадь: {:.4}, объём: {:.4}",
+        mesh.vertices().len(),
+        mesh.triangles().len(),
+        genus,
+        mesh.surface_area(),
+        mesh.volume()
+    );
+    if is_near_degenerate(mesh) {
+        stats.push_str("\n⚠ Сетка почти вырождена");
+    }
+    stats
+}
+
+/// Сетка без площади или с объёмом, ничтожным для её площади (плоская или нитевидная)
+fn is_near_degenerate(model: &dyn Model3D) -> bool {
+    let area = model.surface_area();
+    if area < f64::EPSILON {
+        return true;
+    }
+    let compactness = 6. * std::f64::consts::PI.sqrt() * model.volume() / area.powf(1.5);
+    compactness < DEGENERATE_COMPACTNESS
+}
+
+/// Отношение радиусов описанных сфер большего и меньшего объектов,
+/// если оно не меньше `SCALE_MISMATCH_RATIO`
+fn scale_mismatch(source: &TriangleMesh, target: &TriangleMesh) -> Option<f64> {
+    let (a, b) = (source.bounding_radius(), target.bounding_radius());
+    let ratio = a.max(b) / a.min(b).max(f64::EPSILON);
+    (ratio >= SCALE_MISMATCH_RATIO).then_some(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FOV_DEGREES, INERTIA_SAMPLES};
+    use crate::objects::model3d::{Material, Translate};
+    use nalgebra::Matrix4;
+
+    #[test]
+    fn flattened_mesh_is_near_degenerate() {
+        assert!(!is_near_degenerate(&TriangleMesh::cube()));
+        assert!(!is_near_degenerate(&TriangleMesh::unit_sphere(1)));
+
+        let mut flat = TriangleMesh::cube();
+        flat.scale_xyz((1., 1e-4, 1.));
+        assert!(is_near_degenerate(&flat));
+    }
+
+    #[test]
+    fn large_scale_ratio_triggers_warning() {
+        let source = TriangleMesh::unit_sphere(1);
+        let mut target = TriangleMesh::unit_sphere(1);
+        assert_eq!(scale_mismatch(&source, &target), None);
+
+        target.scale(50.);
+        let ratio = scale_mismatch(&source, &target).unwrap();
+        assert!((ratio - 50.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn loaded_mesh_keeps_specular_factor() {
+        let mut app = MyEguiApp::default();
+        let path = format!("{}/models/apple2.obj", env!("CARGO_MANIFEST_DIR"));
+        app.load_mesh_from_path(&path, false);
+
+        let mesh = app.source_mesh.expect("модель должна загрузиться");
+        assert_eq!(
+            mesh.borrow().material.specular_reflectance_factor,
+            Material::default().specular_reflectance_factor
+        );
+    }
+
+    #[test]
+    fn offscreen_render_shows_square_cube_silhouette() {
+        let mut app = MyEguiApp::default();
+        app.scene.object = Some(Rc::new(RefCell::new(TriangleMesh::cube())));
+
+        // Передняя грань куба со стороной 1 на расстоянии 2.5 от камеры
+        let (width, height) = (400, 200);
+        let image = app.render_to_image(width, height);
+        assert_eq!(image.dimensions(), (width, height));
+
+        let half_tan = (FOV_DEGREES.to_radians() / 2.).tan();
+        let side = 0.5 / 2.5 / half_tan * height as f64;
+        let expected = side * side;
+        let silhouette = image.pixels().filter(|&&px| px != BACKGROUND_COLOR).count() as f64;
+        assert!(
+            (silhouette - expected).abs() < expected * 0.05,
+            "{} пикселей вместо {}",
+            silhouette,
+            expected
+        );
+        assert!(app.texture.is_none());
+    }
+
+    #[test]
+    fn export_at_other_aspect_keeps_live_camera() {
+        let mut app = MyEguiApp::default();
+        app.scene.object = Some(Rc::new(RefCell::new(TriangleMesh::cube())));
+        app.update_viewport_size(300, 300);
+        let camera_matrix = app.scene.camera.camera_matrix;
+
+        let image = app.render_to_image(640, 160);
+
+        assert_eq!(image.dimensions(), (640, 160));
+        assert_eq!(app.scene.camera.camera_matrix, camera_matrix);
+        assert_eq!((app.viewport_width, app.viewport_height), (300, 300));
+    }
+
+    #[test]
+    fn camera_commands_aim_at_object_center() {
+        let mut app = MyEguiApp::default();
+        let mut cube = TriangleMesh::cube();
+        cube.translate(&Vector3::new(1., 2., 0.));
+        app.scene.object = Some(Rc::new(RefCell::new(cube)));
+        let target = Point3::new(1., 2., 0.);
+
+        // Без выделения центрировать не на что
+        app.center_on_selection();
+        assert_eq!(app.scene.camera.look_at(), Point3::origin());
+
+        app.scene.selected = Some(0);
+        let distance = app.scene.camera.distance();
+        app.center_on_selection();
+        assert!((app.scene.camera.look_at() - target).norm() < 1e-12);
+        assert!((app.scene.camera.distance() - distance).abs() < 1e-12);
+
+        app.frame_all();
+        let radius = 3f64.sqrt() / 2.;
+        assert!((app.scene.camera.look_at() - target).norm() < 1e-12);
+        assert!(app.scene.camera.framing_radius() >= radius - 1e-12);
+        assert!(app.scene.camera.distance() > radius);
+    }
+
+    #[test]
+    fn top_view_turns_model_up_axis_to_camera() {
+        let mut app = MyEguiApp::default();
+        let mesh: Rc<RefCell<dyn InteractiveModel>> =
+            Rc::new(RefCell::new(TriangleMesh::unit_sphere(1)));
+        mesh.borrow_mut().rotate((0.3, -1.2, 0.8));
+        mesh.borrow_mut().scale(2.);
+        app.scene.object = Some(mesh.clone());
+
+        app.snap_to_view(PresetView::Top);
+
+        let camera = &app.scene.camera;
+        let toward_camera = (camera.pos - camera.look_at()).normalize();
+        let matrix = *mesh.borrow().model_matrix();
+        let model_up = (matrix * Vector3::y().to_homogeneous()).xyz().normalize();
+        assert!((model_up - toward_camera).norm() < 1e-9);
+        // Масштаб не сбрасывается
+        assert!(((matrix * Vector3::x().to_homogeneous()).norm() - 2.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn loading_a_model_remembers_its_directory() {
+        let models = Path::new(env!("CARGO_MANIFEST_DIR")).join("models");
+        let mut app = MyEguiApp::default();
+        assert_eq!(app.dialog_directory(), std::env::current_dir().unwrap());
+
+        app.load_mesh_from_path(&models.join("cube.obj").to_string_lossy(), false);
+        assert_eq!(app.last_directory.as_deref(), Some(models.as_path()));
+        assert_eq!(app.dialog_directory(), models);
+
+        // Неудачная загрузка не меняет запомненную папку
+        app.load_mesh_from_path("/nonexistent/model.obj", true);
+        assert_eq!(app.last_directory.as_deref(), Some(models.as_path()));
+    }
+
+    #[test]
+    fn error_dialog_freezes_scene_updates() {
+        let morph = Morph::new(TriangleMesh::unit_sphere(1), TriangleMesh::cube()).unwrap();
+        let mut app = MyEguiApp::default();
+        app.finish_morph(Ok(morph));
+        app.view_mode = ViewMode::Morph;
+        app.toggle_morph_playback();
+        for _ in 0..INERTIA_SAMPLES {
+            app.rotation_inertia
+                .record(Vector3::new(0., 0.05, 0.), 0.016);
+        }
+        app.rotation_inertia.release();
+
+        app.error_message = Some("Не удалось создать морфинг".to_string());
+        app.needs_redraw = false;
+        app.advance_morph_playback(0.1);
+        app.apply_rotation_inertia(0.1);
+        assert!(!app.needs_redraw);
+        assert_eq!(app.morph_phase, 0.);
+
+        // После закрытия окна сцена снова оживает
+        app.error_message = None;
+        app.advance_morph_playback(0.1);
+        assert!(app.needs_redraw);
+        assert!(app.morph_phase > 0.);
+    }
+
+    #[test]
+    fn morph_phase_steps_are_clamped_and_applied() {
+        let morph = Morph::new(TriangleMesh::unit_sphere(1), TriangleMesh::cube()).unwrap();
+        let mut expected = morph.clone();
+        let mut app = MyEguiApp {
+            morph_object: Some(Rc::new(RefCell::new(morph))),
+            ..Default::default()
+        };
+
+        app.step_morph_phase(-0.5);
+        assert_eq!(app.morph_phase, 0.);
+        assert!(!app.needs_redraw);
+
+        app.step_morph_phase(0.25);
+        app.step_morph_phase(2.);
+        assert_eq!(app.morph_phase, 1.);
+        assert!(app.needs_redraw);
+
+        expected.update(1.);
+        let morph = app.morph_object.as_ref().unwrap().borrow();
+        assert_eq!(morph.vertices(), expected.vertices());
+    }
+
+    #[test]
+    fn morph_gif_has_requested_frame_count() {
+        use image::AnimationDecoder;
+        use image::codecs::gif::GifDecoder;
+        use std::io::BufReader;
+
+        let morph = Morph::new(TriangleMesh::unit_sphere(1), TriangleMesh::cube()).unwrap();
+        let mut app = MyEguiApp {
+            morph_object: Some(Rc::new(RefCell::new(morph))),
+            viewport_width: 48,
+            viewport_height: 32,
+            ..Default::default()
+        };
+
+        // Имя с номером процесса, чтобы параллельные запуски не делили файл
+        let file_name = format!("morph_animation-{}.gif", std::process::id());
+        let path = std::env::temp_dir().join(file_name);
+        app.export_morph_gif(&path, 5, 10).unwrap();
+        assert!(app.animation_export.is_some(), "кадры пишутся в фоне");
+        wait_for_animation_export(&mut app);
+        assert!(app.error_message.is_none());
+
+        let decoder = GifDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].buffer().dimensions(), (48, 32));
+        assert!(app.scene.object.is_none());
+    }
+
+    fn wait_for_animation_export(app: &mut MyEguiApp) {
+        while app.animation_export.is_some() {
+            app.poll_animation_export();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn cancelled_animation_export_removes_partial_file() {
+        let morph = Morph::new(TriangleMesh::unit_sphere(1), TriangleMesh::cube()).unwrap();
+        let mut app = MyEguiApp {
+            morph_object: Some(Rc::new(RefCell::new(morph))),
+            viewport_width: 32,
+            viewport_height: 32,
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join(format!("cancelled-{}.gif", std::process::id()));
+
+        app.export_morph_gif(&path, 10_000, 10).unwrap();
+        assert!(
+            app.export_morph_gif(&path, 5, 10).is_err(),
+            "второй экспорт не запускается, пока идёт первый"
+        );
+        app.cancel_animation_export();
+        wait_for_animation_export(&mut app);
+
+        assert!(!path.exists());
+        assert!(app.error_message.is_none());
+        assert_eq!(app.morph_phase, 0.);
+    }
+
+    fn wait_for_morph_build(app: &mut MyEguiApp) {
+        while app.morph_build.is_some() {
+            app.poll_morph_build();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn app_with_meshes(source: &str, target: &str) -> MyEguiApp {
+        let mut app = MyEguiApp::default();
+        let models = format!("{}/models", env!("CARGO_MANIFEST_DIR"));
+        app.load_mesh_from_path(&format!("{}/{}", models, source), false);
+        app.load_mesh_from_path(&format!("{}/{}", models, target), true);
+        app
+    }
+
+    #[test]
+    fn morph_build_uses_configured_params() {
+        let mut app = app_with_meshes("cube.obj", "apple2.obj");
+        app.morph_params.relaxation_rounds_limit = 1;
+        app.start_morph_build();
+        wait_for_morph_build(&mut app);
+
+        let morph = app.morph_object.expect("морфинг должен построиться");
+        for report in morph.borrow().relaxation_reports() {
+            assert!(report.rounds <= 1, "раундов релаксации: {}", report.rounds);
+        }
+    }
+
+    #[test]
+    fn cancelling_running_build_discards_result() {
+        let mut app = app_with_meshes("apple2.obj", "pear.obj");
+        app.start_morph_build();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(
+            !app.morph_build.as_ref().unwrap().handle.is_finished(),
+            "построение должно ещё выполняться"
+        );
+
+        let started = Instant::now();
+        app.cancel_morph_build();
+        wait_for_morph_build(&mut app);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(app.morph_object.is_none());
+        assert!(!app.morph_created);
+        assert!(app.error_message.is_none());
+    }
+
+    #[test]
+    fn loaded_mesh_fits_into_frame() {
+        let mut app = MyEguiApp::default();
+        let path = format!("{}/models/apple2.obj", env!("CARGO_MANIFEST_DIR"));
+        app.load_mesh_from_path(&path, false);
+
+        let mesh = app.source_mesh.clone().expect("модель должна загрузиться");
+        let camera_matrix = app.scene.camera.camera_matrix;
+        let assert_in_frame = || {
+            for v in mesh.borrow().vertices_world() {
+                let p = Point3::from_homogeneous(camera_matrix * v.to_homogeneous()).unwrap();
+                assert!(p.x.abs() <= 1. && p.y.abs() <= 1., "{} вне кадра", v);
+            }
+        };
+        assert_in_frame();
+
+        // Подгонка входит в саму сетку: сброс преобразований её не отменяет
+        app.scene.object = Some(mesh.clone() as Rc<RefCell<dyn InteractiveModel>>);
+        app.apply_button_scale(0.01);
+        app.reset_current_object();
+        assert_eq!(mesh.borrow().model_matrix(), &Matrix4::identity());
+        assert_in_frame();
+    }
+}
@@ -0,0 +1,70 @@
+use crate::config::ACCENT_COLOR;
+use eframe::egui::{Color32, Visuals};
+use serde::{Deserialize, Serialize};
+
+/// Светлая или тёмная тема интерфейса
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// Оформление интерфейса: тема и цвет выделения
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub mode: ThemeMode,
+    /// Цвет выделенных элементов, ссылок и активных переключателей
+    pub accent: [u8; 3],
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::default(),
+            accent: ACCENT_COLOR,
+        }
+    }
+}
+
+impl ThemeSettings {
+    /// Переключает тему на противоположную
+    pub fn toggle(&mut self) {
+        self.mode = match self.mode {
+            ThemeMode::Dark => ThemeMode::Light,
+            ThemeMode::Light => ThemeMode::Dark,
+        };
+    }
+
+    pub fn visuals(&self) -> Visuals {
+        let mut visuals = match self.mode {
+            ThemeMode::Dark => Visuals::dark(),
+            ThemeMode::Light => Visuals::light(),
+        };
+        let [r, g, b] = self.accent;
+        let accent = Color32::from_rgb(r, g, b);
+        visuals.selection.bg_fill = accent;
+        visuals.hyperlink_color = accent;
+        visuals.widgets.active.bg_fill = accent;
+        visuals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_switches_mode_and_visuals() {
+        let mut theme = ThemeSettings::default();
+        assert!(theme.visuals().dark_mode);
+
+        theme.toggle();
+        assert_eq!(theme.mode, ThemeMode::Light);
+        assert!(!theme.visuals().dark_mode);
+
+        theme.toggle();
+        assert_eq!(theme.mode, ThemeMode::Dark);
+    }
+}
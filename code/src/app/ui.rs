@@ -1,13 +1,69 @@
-use super::state::{MyEguiApp, ViewMode};
+use super::i18n::{Language, Text};
+use super::playback::LoopMode;
+use super::state::{MyEguiApp, Primitive, ViewMode};
+use super::theme::ThemeMode;
+use crate::config::{GLOSS_MAX, GLOSS_MIN};
+use crate::objects::camera::PresetView;
+use crate::objects::light::Attenuation;
 use crate::objects::model3d;
 use crate::objects::model3d::Model3D;
-use eframe::egui::{CentralPanel, Color32, Context, ScrollArea, SidePanel, Ui, Vec2};
+use crate::render::{RendererKind, SpecularModel};
+use eframe::egui::{
+    CentralPanel, Color32, Context, ScrollArea, SidePanel, TopBottomPanel, Ui, Vec2,
+};
+use rfd::FileDialog;
 
 impl MyEguiApp {
     pub fn render_ui(&mut self, ctx: &Context) {
+        let lang = self.language;
+
         // Настройка глобальных стилей
         self.setup_custom_styles(ctx);
 
+        // Верхняя панель меню
+        TopBottomPanel::top("menu_panel").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(lang.tr(Text::SaveSession)).clicked() {
+                    self.save_session();
+                }
+                if ui.button(lang.tr(Text::LoadSession)).clicked() {
+                    self.load_session();
+                }
+                if ui.button(lang.tr(Text::SaveFrame)).clicked() {
+                    self.export_frame_png();
+                }
+                ui.separator();
+                if ui.button(lang.tr(Text::ExportObj)).clicked() {
+                    self.export_obj();
+                }
+                ui.checkbox(&mut self.obj_export.normals, lang.tr(Text::ExportNormals));
+                ui.checkbox(
+                    &mut self.obj_export.flip_winding,
+                    lang.tr(Text::FlipWinding),
+                )
+                .on_hover_text(lang.tr(Text::FlipWindingHint));
+                ui.separator();
+                let theme_label = match self.theme.mode {
+                    ThemeMode::Dark => Text::LightTheme,
+                    ThemeMode::Light => Text::DarkTheme,
+                };
+                if ui.button(lang.tr(theme_label)).clicked() {
+                    self.theme.toggle();
+                }
+                ui.color_edit_button_srgb(&mut self.theme.accent)
+                    .on_hover_text(lang.tr(Text::AccentColor));
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(self.language.name())
+                    .show_ui(ui, |ui| {
+                        for language in Language::ALL {
+                            ui.selectable_value(&mut self.language, language, language.name());
+                        }
+                    })
+                    .response
+                    .on_hover_text(lang.tr(Text::Language));
+            });
+        });
+
         // Правая панель с элементами управления
         SidePanel::right("controls_panel")
             .resizable(true)
@@ -18,7 +74,7 @@ impl MyEguiApp {
                     .auto_shrink([false; 2])
                     .show(ui, |ui| {
                         ui.style_mut().spacing.slider_width = 235.0;
-                        ui.heading("⚙ Управление");
+                        ui.heading(lang.tr(Text::Controls));
                         ui.add_space(10.0);
 
                         // UI для выбора файлов
@@ -30,12 +86,24 @@ impl MyEguiApp {
                         // Управление трансформациями
                         self.render_transform_controls(ui);
 
+                        // Обработка сетки
+                        self.render_mesh_processing_controls(ui);
+
                         // Параметры материала
                         self.render_material_controls(ui);
 
+                        // Параметры отображения
+                        self.render_display_controls(ui);
+
+                        // Чувствительность мыши и жестов
+                        self.render_input_settings(ui);
+
                         // Управление морфингом
                         self.render_morph_instructions(ui);
 
+                        // Отладочная информация о кадре
+                        self.render_timings_panel(ui);
+
                         // Добавляем немного пространства внизу для удобства прокрутки
                         ui.add_space(10.0);
                     });
@@ -43,7 +111,7 @@ impl MyEguiApp {
 
         // Центральная панель с окном просмотра
         CentralPanel::default().show(ctx, |ui| {
-            ui.heading("🍎 Морфинг фруктов");
+            ui.heading(lang.tr(Text::AppTitle));
             ui.add_space(5.0);
 
             // Режим просмотра сверху
@@ -55,7 +123,7 @@ impl MyEguiApp {
 
         // Модальное окно с ошибкой
         if let Some(error_msg) = &self.error_message.clone() {
-            egui::Window::new("⚠ Ошибка")
+            egui::Window::new(lang.tr(Text::Error))
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -77,6 +145,7 @@ impl MyEguiApp {
 
     fn setup_custom_styles(&self, ctx: &Context) {
         let mut style = (*ctx.style()).clone();
+        style.visuals = self.theme.visuals();
 
         // Увеличиваем размер текста для заголовков
         style.text_styles.insert(
@@ -111,33 +180,41 @@ impl MyEguiApp {
     }
 
     fn render_file_selection(&mut self, ui: &mut Ui) {
+        let lang = self.language;
         ui.separator();
         ui.add_space(5.0);
-        ui.label("📂 Выбор OBJ файлов:");
+        ui.label(lang.tr(Text::SelectObjFiles));
+        ui.add_space(5.0);
+        ui.checkbox(&mut self.weld_on_load, lang.tr(Text::WeldOnLoad));
+        ui.checkbox(&mut self.auto_fit_on_load, lang.tr(Text::AutoFitOnLoad));
         ui.add_space(8.0);
 
         // Исходный файл
         ui.group(|ui| {
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Исходный объект:");
+                    ui.label(lang.tr(Text::SourceObject));
                     ui.add_space(5.0);
                     if !self.selected_source_file.is_empty() {
                         ui.label(self.selected_source_file.to_string());
                     }
                 });
+                if !self.source_stats.is_empty() {
+                    ui.weak(&self.source_stats);
+                }
 
                 // Кнопка для выбора любого файла
                 if self
                     .styled_button(
                         ui,
-                        "📁 Выбрать файл...",
+                        lang.tr(Text::ChooseFile),
                         Vec2::new(ui.available_width(), 36.0),
                     )
                     .clicked()
                 {
                     self.open_file_dialog(false);
                 }
+                self.render_primitive_buttons(ui, false);
             });
         });
 
@@ -147,72 +224,152 @@ impl MyEguiApp {
         ui.group(|ui| {
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Целевой объект:");
+                    ui.label(lang.tr(Text::TargetObject));
                     ui.add_space(5.0);
                     if !self.selected_target_file.is_empty() {
                         ui.label(self.selected_target_file.to_string());
                     }
                 });
+                if !self.target_stats.is_empty() {
+                    ui.weak(&self.target_stats);
+                }
 
                 // Кнопка для выбора любого файла
                 if self
                     .styled_button(
                         ui,
-                        "📁 Выбрать файл...",
+                        lang.tr(Text::ChooseFile),
                         Vec2::new(ui.available_width(), 36.0),
                     )
                     .clicked()
                 {
                     self.open_file_dialog(true);
                 }
+                self.render_primitive_buttons(ui, true);
             });
         });
     }
 
+    fn render_primitive_buttons(&mut self, ui: &mut Ui, is_target: bool) {
+        let lang = self.language;
+        ui.horizontal(|ui| {
+            for primitive in Primitive::ALL {
+                if ui.button(lang.tr(Text::from(primitive))).clicked() {
+                    self.load_primitive(primitive, is_target);
+                }
+            }
+        });
+    }
+
     fn render_morph_controls(&mut self, ui: &mut Ui) {
+        let lang = self.language;
         ui.separator();
         ui.add_space(10.0);
 
         let can_create_morph = self.source_mesh.is_some() && self.target_mesh.is_some();
         let button_text = if self.morph_created {
-            "🔄 Пересоздать морфинг"
+            lang.tr(Text::RebuildMorph)
         } else {
-            "✨ Создать морфинг"
+            lang.tr(Text::CreateMorph)
         };
 
         ui.vertical(|ui| {
+            if self.morph_build.is_some() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(lang.tr(Text::BuildingMorph));
+                });
+                let width = ui.available_width();
+                if self
+                    .styled_button(ui, lang.tr(Text::Cancel), Vec2::new(width, 32.0))
+                    .clicked()
+                {
+                    self.cancel_morph_build();
+                }
+                return;
+            }
+
             let response = ui.add_enabled(
                 can_create_morph,
                 egui::Button::new(button_text).min_size(Vec2::new(ui.available_width(), 40.0)),
             );
 
             if response.clicked() {
-                self.create_morph_object();
+                self.start_morph_build();
+            }
+
+            if let Some(ratio) = self.scale_warning {
+                ui.add_space(3.0);
+                ui.colored_label(Color32::from_rgb(220, 170, 60), lang.scale_mismatch(ratio));
+                if ui
+                    .button(lang.tr(Text::NormalizeScales))
+                    .on_hover_text(lang.tr(Text::NormalizeScalesHint))
+                    .clicked()
+                {
+                    self.normalize_mesh_scales();
+                }
+            }
+
+            if let Some(morph) = self.morph_object.as_ref().filter(|_| self.morph_created) {
+                let reports = *morph.borrow().relaxation_reports();
+                let names = [Text::SourceShort, Text::TargetShort];
+                for (name, report) in names.into_iter().zip(reports) {
+                    let status = if report.converged {
+                        Text::Converged
+                    } else {
+                        Text::NotConverged
+                    };
+                    ui.label(format!(
+                        "{} ({}): {} {}, {}, {} {:.1e}",
+                        lang.tr(Text::Relaxation),
+                        lang.tr(name),
+                        report.rounds,
+                        lang.tr(Text::Rounds),
+                        lang.tr(status),
+                        lang.tr(Text::Residual),
+                        report.final_residual
+                    ));
+                }
+            }
+
+            if self.morph_created
+                && ui
+                    .button(lang.tr(Text::ExportCorrespondence))
+                    .on_hover_text(lang.tr(Text::ExportCorrespondenceHint))
+                    .clicked()
+            {
+                self.export_correspondence_csv();
             }
 
             if !can_create_morph {
                 ui.add_space(3.0);
-                ui.colored_label(Color32::from_rgb(200, 100, 100), "⚠ Выберите оба объекта");
+                ui.colored_label(
+                    Color32::from_rgb(200, 100, 100),
+                    lang.tr(Text::SelectBothObjects),
+                );
             }
         });
     }
 
     fn render_transform_controls(&mut self, ui: &mut Ui) {
+        let lang = self.language;
         ui.separator();
         ui.add_space(10.0);
-        ui.label("🎯 Управление объектом:");
+        ui.label(lang.tr(Text::ObjectControls));
         ui.add_space(5.0);
 
         let has_object = match self.view_mode {
             ViewMode::Source => self.source_mesh.is_some(),
             ViewMode::Target => self.target_mesh.is_some(),
             ViewMode::Morph => self.morph_object.is_some(),
+            ViewMode::SourceSphere => self.source_sphere.is_some(),
+            ViewMode::TargetSphere => self.target_sphere.is_some(),
         };
 
         if !has_object {
             ui.colored_label(
                 Color32::from_rgb(200, 100, 100),
-                "⚠ Загрузите объект для управления",
+                lang.tr(Text::LoadObjectFirst),
             );
             return;
         }
@@ -220,7 +377,7 @@ impl MyEguiApp {
         // Поворот
         ui.group(|ui| {
             ui.vertical(|ui| {
-                ui.label("🔄 Поворот (градусы):");
+                ui.label(lang.tr(Text::RotationDegrees));
                 ui.add_space(5.0);
 
                 ui.horizontal(|ui| {
@@ -267,6 +424,16 @@ impl MyEguiApp {
                         self.apply_button_rotation(0.0, 0.0, -15.0);
                     }
                 });
+
+                ui.add_space(5.0);
+                ui.label(lang.tr(Text::PresetViews));
+                ui.horizontal_wrapped(|ui| {
+                    for view in PresetView::ALL {
+                        if ui.button(lang.tr(Text::from(view))).clicked() {
+                            self.snap_to_view(view);
+                        }
+                    }
+                });
             });
         });
 
@@ -275,23 +442,50 @@ impl MyEguiApp {
         // Масштабирование
         ui.group(|ui| {
             ui.vertical(|ui| {
-                ui.label("🔍 Масштабирование:");
+                ui.label(lang.tr(Text::Scaling));
                 ui.add_space(5.0);
 
                 ui.horizontal(|ui| {
                     if self
-                        .styled_button(ui, "➕ Увеличить x1.1", Vec2::new(140.0, 32.0))
+                        .styled_button(ui, lang.tr(Text::ScaleUp), Vec2::new(140.0, 32.0))
                         .clicked()
                     {
                         self.apply_button_scale(1.1);
                     }
                     if self
-                        .styled_button(ui, "➖ Уменьшить x0.9", Vec2::new(140.0, 32.0))
+                        .styled_button(ui, lang.tr(Text::ScaleDown), Vec2::new(140.0, 32.0))
                         .clicked()
                     {
                         self.apply_button_scale(0.9);
                     }
                 });
+
+                // Растяжение и сжатие вдоль осей объекта
+                for (axis, name) in ["X", "Y", "Z"].into_iter().enumerate() {
+                    let factors = |factor: f64| {
+                        let mut factors = [1.0; 3];
+                        factors[axis] = factor;
+                        factors
+                    };
+                    ui.horizontal(|ui| {
+                        let stretch = format!("↔ {} x1.1", name);
+                        if self
+                            .styled_button(ui, &stretch, Vec2::new(140.0, 32.0))
+                            .clicked()
+                        {
+                            let [x, y, z] = factors(1.1);
+                            self.apply_button_scale_xyz(x, y, z);
+                        }
+                        let squash = format!("↕ {} x0.9", name);
+                        if self
+                            .styled_button(ui, &squash, Vec2::new(140.0, 32.0))
+                            .clicked()
+                        {
+                            let [x, y, z] = factors(0.9);
+                            self.apply_button_scale_xyz(x, y, z);
+                        }
+                    });
+                }
             });
         });
 
@@ -301,7 +495,7 @@ impl MyEguiApp {
         if self
             .styled_button(
                 ui,
-                "🔄 Сбросить преобразования",
+                lang.tr(Text::ResetTransforms),
                 Vec2::new(ui.available_width(), 36.0),
             )
             .clicked()
@@ -310,17 +504,294 @@ impl MyEguiApp {
         }
     }
 
+    fn render_mesh_processing_controls(&mut self, ui: &mut Ui) {
+        let lang = self.language;
+        if self.current_mesh().is_none() {
+            return;
+        }
+
+        ui.separator();
+        ui.add_space(10.0);
+        ui.label(lang.tr(Text::MeshProcessing));
+        ui.add_space(5.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(lang.tr(Text::SubdivisionIterations));
+                ui.add_space(3.0);
+                ui.add_sized(
+                    Vec2::new(ui.available_width(), 20.0),
+                    egui::Slider::new(&mut self.subdivision_iterations, 1..=3),
+                );
+
+                ui.add_space(5.0);
+
+                if self
+                    .styled_button(
+                        ui,
+                        lang.tr(Text::SmoothMesh),
+                        Vec2::new(ui.available_width(), 32.0),
+                    )
+                    .clicked()
+                {
+                    self.subdivide_current_mesh();
+                }
+            });
+        });
+
+        ui.add_space(8.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(lang.tr(Text::DecimationRatio));
+                ui.add_space(3.0);
+                ui.add_sized(
+                    Vec2::new(ui.available_width(), 20.0),
+                    egui::Slider::new(&mut self.decimation_ratio, 0.05..=1.0)
+                        .step_by(0.05)
+                        .fixed_decimals(2),
+                );
+
+                ui.add_space(5.0);
+
+                if self
+                    .styled_button(
+                        ui,
+                        lang.tr(Text::DecimateMesh),
+                        Vec2::new(ui.available_width(), 32.0),
+                    )
+                    .clicked()
+                {
+                    self.decimate_current_mesh();
+                }
+            });
+        });
+    }
+
+    fn render_display_controls(&mut self, ui: &mut Ui) {
+        let lang = self.language;
+        ui.separator();
+        ui.add_space(10.0);
+        ui.label(lang.tr(Text::Display));
+        ui.add_space(5.0);
+
+        let mut renderer_kind = self.renderer_kind();
+        let renderers = [
+            (RendererKind::Solid, lang.tr(Text::RendererSolid)),
+            (RendererKind::Hybrid, lang.tr(Text::RendererHybrid)),
+            (
+                RendererKind::Transparent,
+                lang.tr(Text::RendererTransparent),
+            ),
+            (RendererKind::Wireframe, lang.tr(Text::RendererWireframe)),
+        ];
+        ui.horizontal(|ui| {
+            ui.label(lang.tr(Text::Renderer));
+            let selected = renderers.iter().find(|(kind, _)| *kind == renderer_kind);
+            egui::ComboBox::from_id_salt("renderer_kind")
+                .selected_text(selected.map_or("", |(_, label)| *label))
+                .show_ui(ui, |ui| {
+                    for (kind, label) in renderers {
+                        ui.selectable_value(&mut renderer_kind, kind, label);
+                    }
+                });
+        });
+        self.set_renderer_kind(renderer_kind);
+        ui.add_space(5.0);
+
+        let options = &mut self.scene.render_options;
+        let camera = &mut self.scene.camera;
+        let light = &mut self.scene.light_source;
+        let mut changed = false;
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(lang.tr(Text::FieldOfView));
+                ui.add_space(3.0);
+                let mut fov_degrees = camera.fov_radians().to_degrees();
+                if ui
+                    .add_sized(
+                        Vec2::new(ui.available_width(), 20.0),
+                        egui::Slider::new(&mut fov_degrees, 20.0..=110.0)
+                            .step_by(1.0)
+                            .suffix("°"),
+                    )
+                    .changed()
+                {
+                    camera.set_fov(fov_degrees.to_radians());
+                    changed = true;
+                }
+                ui.add_space(5.0);
+
+                changed |= ui
+                    .checkbox(
+                        &mut options.show_non_manifold_edges,
+                        lang.tr(Text::ShowNonManifoldEdges),
+                    )
+                    .changed();
+                changed |= ui
+                    .checkbox(
+                        &mut options.ambient_occlusion,
+                        lang.tr(Text::AmbientOcclusion),
+                    )
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut options.two_sided, lang.tr(Text::TwoSided))
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut options.explode_factor, 0.0..=0.5)
+                            .text(lang.tr(Text::ExplodeFactor)),
+                    )
+                    .changed();
+                if renderer_kind == RendererKind::Wireframe {
+                    changed |= ui
+                        .checkbox(&mut options.vertex_markers, lang.tr(Text::VertexMarkers))
+                        .changed();
+                }
+                changed |= ui
+                    .checkbox(
+                        &mut options.show_supermesh_origin,
+                        lang.tr(Text::SupermeshOrigin),
+                    )
+                    .on_hover_text(lang.tr(Text::SupermeshOriginHint))
+                    .changed();
+                ui.horizontal(|ui| {
+                    ui.label(lang.tr(Text::Specular));
+                    changed |= ui
+                        .selectable_value(
+                            &mut options.specular_model,
+                            SpecularModel::Phong,
+                            lang.tr(Text::Phong),
+                        )
+                        .changed();
+                    changed |= ui
+                        .selectable_value(
+                            &mut options.specular_model,
+                            SpecularModel::BlinnPhong,
+                            lang.tr(Text::BlinnPhong),
+                        )
+                        .changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label(lang.tr(Text::LightAttenuation));
+                    for (attenuation, label) in [
+                        (Attenuation::None, lang.tr(Text::None)),
+                        (Attenuation::Linear, lang.tr(Text::AttenuationLinear)),
+                        (Attenuation::InverseSquare, "1/d²"),
+                    ] {
+                        changed |= ui
+                            .selectable_value(&mut light.attenuation, attenuation, label)
+                            .changed();
+                    }
+                });
+
+                changed |= ui
+                    .checkbox(&mut options.outline_enabled, lang.tr(Text::Outline))
+                    .changed();
+                if options.outline_enabled {
+                    let outline = &mut options.outline;
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut outline.thickness, 1..=5)
+                                .text(lang.tr(Text::Thickness)),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut outline.threshold, 0.001..=0.5)
+                                .logarithmic(true)
+                                .text(lang.tr(Text::Threshold)),
+                        )
+                        .changed();
+                    ui.horizontal(|ui| {
+                        ui.label(lang.tr(Text::OutlineColor));
+                        let mut color = outline.color.0.map(|c| c as f32 / 255.0);
+                        if ui.color_edit_button_rgb(&mut color).changed() {
+                            outline.color = image::Rgb(color.map(|c| (c * 255.0) as u8));
+                            changed = true;
+                        }
+                    });
+                }
+
+                changed |= ui
+                    .checkbox(&mut options.fog_enabled, lang.tr(Text::Fog))
+                    .changed();
+                if options.fog_enabled {
+                    let fog = &mut options.fog;
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut fog.start, 0.0..=20.0)
+                                .text(lang.tr(Text::FogStart)),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut fog.end, 0.0..=20.0).text(lang.tr(Text::FogEnd)),
+                        )
+                        .changed();
+                    ui.horizontal(|ui| {
+                        ui.label(lang.tr(Text::FogColor));
+                        let mut color = fog.color.0.map(|c| c as f32 / 255.0);
+                        if ui.color_edit_button_rgb(&mut color).changed() {
+                            fog.color = image::Rgb(color.map(|c| (c * 255.0) as u8));
+                            changed = true;
+                        }
+                    });
+                }
+            });
+        });
+
+        if changed {
+            self.needs_redraw = true; // Требуется перерисовка при изменении параметров отображения
+        }
+    }
+
+    fn render_input_settings(&mut self, ui: &mut Ui) {
+        let lang = self.language;
+        ui.separator();
+        ui.add_space(10.0);
+
+        // Настройки управления не влияют на изображение
+        egui::CollapsingHeader::new(lang.tr(Text::InputSettings)).show(ui, |ui| {
+            ui.add(
+                egui::Slider::new(&mut self.scroll_zoom.step, 0.0005..=0.01)
+                    .logarithmic(true)
+                    .text(lang.tr(Text::ZoomStep)),
+            );
+            ui.checkbox(&mut self.scroll_zoom.invert, lang.tr(Text::InvertScroll));
+            ui.add_space(5.0);
+
+            ui.add(
+                egui::Slider::new(&mut self.rotation.sensitivity, 0.1..=5.0)
+                    .logarithmic(true)
+                    .text(lang.tr(Text::RotationSensitivity)),
+            );
+            ui.checkbox(&mut self.rotation.invert, lang.tr(Text::InvertRotation));
+        });
+    }
+
     fn render_view_mode_controls(&mut self, ui: &mut Ui) {
+        let lang = self.language;
         ui.separator();
         ui.add_space(5.0);
 
         // Строка с заголовком и FPS справа
         ui.horizontal(|ui| {
-            ui.label("👁 Режим просмотра:");
+            ui.label(lang.tr(Text::ViewMode));
 
             // Прижимаем FPS к правому краю
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 ui.label(format!("FPS: {}", self.fps as u32));
+                // FPS включает ожидание vsync и работу UI, время рендера - только отрисовку
+                if let Some(render_time) = self.render_time {
+                    ui.label(format!(
+                        "{} {:.1} {}",
+                        lang.tr(Text::RenderTime),
+                        render_time.as_secs_f64() * 1000.0,
+                        lang.tr(Text::Milliseconds)
+                    ));
+                }
                 ui.label("📊");
             });
         });
@@ -333,22 +804,50 @@ impl MyEguiApp {
             ui.spacing_mut().item_spacing.x = 8.0;
 
             // Кнопка исходного режима
-            ui.selectable_value(&mut self.view_mode, ViewMode::Source, "📦 Исходный");
+            ui.selectable_value(
+                &mut self.view_mode,
+                ViewMode::Source,
+                lang.tr(Text::ViewSource),
+            );
 
             // Кнопка целевого режима
-            ui.selectable_value(&mut self.view_mode, ViewMode::Target, "🎯 Целевой");
+            ui.selectable_value(
+                &mut self.view_mode,
+                ViewMode::Target,
+                lang.tr(Text::ViewTarget),
+            );
 
             // Кнопка морфинга - добавляем enabled wrapper
             ui.add_enabled_ui(self.morph_created, |ui| {
-                let response =
-                    ui.selectable_value(&mut self.view_mode, ViewMode::Morph, "✨ Морфинг");
+                let response = ui.selectable_value(
+                    &mut self.view_mode,
+                    ViewMode::Morph,
+                    lang.tr(Text::ViewMorph),
+                );
 
                 if !self.morph_created {
-                    response.on_disabled_hover_text("Создайте морфинг для активации");
+                    response.on_disabled_hover_text(lang.tr(Text::CreateMorphToEnable));
                 }
             });
         });
 
+        // Параметризации на сфере доступны после построения морфинга
+        ui.add_enabled_ui(self.morph_created, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 8.0;
+                ui.selectable_value(
+                    &mut self.view_mode,
+                    ViewMode::SourceSphere,
+                    lang.tr(Text::ViewSourceSphere),
+                );
+                ui.selectable_value(
+                    &mut self.view_mode,
+                    ViewMode::TargetSphere,
+                    lang.tr(Text::ViewTargetSphere),
+                );
+            });
+        });
+
         // Обновляем объекты сцены при смене режима
         if old_view_mode != self.view_mode {
             self.update_scene_object();
@@ -356,40 +855,268 @@ impl MyEguiApp {
     }
 
     fn render_morph_instructions(&mut self, ui: &mut Ui) {
+        let lang = self.language;
         if self.view_mode == ViewMode::Morph && self.morph_created {
             ui.separator();
             ui.add_space(10.0);
 
             ui.group(|ui| {
                 ui.vertical(|ui| {
-                    ui.label("⏱ Управление морфингом:");
+                    ui.label(lang.tr(Text::MorphControls));
+                    ui.add_space(8.0);
+
+                    // Каркас суперсетки на любой стадии - для проверки триангуляции
+                    if let Some(morph) = &self.morph_object {
+                        let count = morph.borrow().triangles().len();
+                        ui.label(format!("{} {}", lang.tr(Text::SupermeshTriangles), count));
+                    }
+                    let mut wireframe = self.renderer_kind() == RendererKind::Wireframe;
+                    if ui
+                        .checkbox(&mut wireframe, lang.tr(Text::SupermeshWireframe))
+                        .changed()
+                    {
+                        self.set_renderer_kind(if wireframe {
+                            RendererKind::Wireframe
+                        } else {
+                            RendererKind::Solid
+                        });
+                    }
                     ui.add_space(8.0);
 
-                    // Ползунок для управления стадией морфинга
-                    let old_phase = self.morph_phase;
+                    // Ползунок и поле точного ввода стадии морфинга
+                    let mut phase = self.morph_phase;
                     ui.vertical(|ui| {
-                        ui.label("Стадия:");
+                        ui.label(lang.tr(Text::Phase));
                         ui.add_space(3.0);
                         ui.add_sized(
                             Vec2::new(ui.available_width(), 20.0),
-                            egui::Slider::new(&mut self.morph_phase, 0.0..=1.0)
+                            egui::Slider::new(&mut phase, 0.0..=1.0)
                                 .step_by(0.01)
                                 .fixed_decimals(2),
                         );
+                        ui.horizontal(|ui| {
+                            ui.label(lang.tr(Text::Exact));
+                            ui.add(
+                                egui::DragValue::new(&mut phase)
+                                    .range(0.0..=1.0)
+                                    .speed(0.001)
+                                    .max_decimals(4),
+                            );
+                        });
+                        ui.add(
+                            egui::Slider::new(&mut self.morph_phase_step, 0.001..=0.1)
+                                .logarithmic(true)
+                                .text(lang.tr(Text::ArrowStep)),
+                        )
+                        .on_hover_text(lang.tr(Text::ArrowStepHint));
                     });
 
                     // Обновляем морф-объект, если фаза изменилась
-                    if (old_phase - self.morph_phase).abs() > f64::EPSILON {
-                        if let Some(ref morph) = self.morph_object {
-                            morph.borrow_mut().update(self.morph_phase);
-                        }
-                        self.needs_redraw = true; // Требуется перерисовка при изменении фазы морфинга
-                    }
+                    self.set_morph_phase(phase);
+
+                    ui.add_space(8.0);
+                    self.render_morph_playback(ui);
+
+                    ui.add_space(8.0);
+                    self.render_morph_animation_export(ui);
+
+                    ui.add_space(8.0);
+                    self.render_morph_metrics(ui);
                 });
             });
         }
     }
 
+    fn render_morph_animation_export(&mut self, ui: &mut Ui) {
+        let lang = self.language;
+        ui.horizontal(|ui| {
+            ui.label(lang.tr(Text::Frames));
+            ui.add(egui::DragValue::new(&mut self.animation_frame_count).range(2..=10000));
+            ui.label("FPS:");
+            ui.add(egui::DragValue::new(&mut self.animation_fps).range(1..=60));
+        });
+
+        if let Some(export) = &self.animation_export {
+            ui.label(lang.tr(Text::ExportingAnimation));
+            ui.add(egui::ProgressBar::new(export.progress()).show_percentage());
+            if ui.button(lang.tr(Text::Cancel)).clicked() {
+                self.cancel_animation_export();
+            }
+            return;
+        }
+
+        if ui.button(lang.tr(Text::SaveGif)).clicked() {
+            self.save_morph_gif();
+        }
+
+        #[cfg(feature = "video")]
+        {
+            ui.horizontal(|ui| {
+                ui.label(lang.tr(Text::Video));
+                ui.add(egui::DragValue::new(&mut self.video_width).range(16..=3840));
+                ui.label("x");
+                ui.add(egui::DragValue::new(&mut self.video_height).range(16..=2160));
+            });
+            if ui
+                .button(lang.tr(Text::SaveVideo))
+                .on_hover_text(lang.tr(Text::SaveVideoHint))
+                .clicked()
+            {
+                self.save_morph_video();
+            }
+        }
+    }
+
+    fn render_morph_playback(&mut self, ui: &mut Ui) {
+        let lang = self.language;
+        let label = if self.morph_playback.playing {
+            lang.tr(Text::Pause)
+        } else {
+            lang.tr(Text::Play)
+        };
+        if ui.button(label).clicked() {
+            self.toggle_morph_playback();
+        }
+
+        let playback = &mut self.morph_playback;
+        egui::ComboBox::from_label(lang.tr(Text::Repeat))
+            .selected_text(lang.tr(Text::from(playback.loop_mode)))
+            .show_ui(ui, |ui| {
+                for mode in LoopMode::ALL {
+                    ui.selectable_value(&mut playback.loop_mode, mode, lang.tr(Text::from(mode)));
+                }
+            });
+        ui.add(
+            egui::Slider::new(&mut playback.duration, 0.5..=20.0)
+                .suffix(lang.tr(Text::SecondsSuffix))
+                .text(lang.tr(Text::Duration)),
+        );
+        ui.add(
+            egui::Slider::new(&mut playback.end_dwell, 0.0..=5.0)
+                .suffix(lang.tr(Text::SecondsSuffix))
+                .text(lang.tr(Text::EndDwell)),
+        );
+    }
+
+    fn render_morph_metrics(&mut self, ui: &mut Ui) {
+        let lang = self.language;
+        if ui
+            .button(lang.tr(Text::MorphMetrics))
+            .on_hover_text(lang.tr(Text::MorphMetricsHint))
+            .clicked()
+        {
+            self.compute_morph_metrics();
+        }
+        let Some(metrics) = &self.morph_metrics else {
+            return;
+        };
+        if metrics.is_empty() {
+            return;
+        }
+
+        const VOLUME_COLOR: Color32 = Color32::from_rgb(80, 160, 255);
+        const AREA_COLOR: Color32 = Color32::from_rgb(255, 160, 60);
+        let volumes: Vec<f64> = metrics.iter().map(|m| m.volume).collect();
+        let areas: Vec<f64> = metrics.iter().map(|m| m.surface_area).collect();
+        let range = |values: &[f64]| {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            (min, max)
+        };
+        let (volume_min, volume_max) = range(&volumes);
+        let (area_min, area_max) = range(&areas);
+
+        let size = Vec2::new(ui.available_width(), 120.0);
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+        let rect = response.rect;
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        // Каждая кривая нормируется на свой максимум: ось Y от 0 до максимума
+        let curve = |values: &[f64], max: f64| -> Vec<egui::Pos2> {
+            metrics
+                .iter()
+                .zip(values)
+                .map(|(m, &value)| {
+                    let y = if max > 0.0 { value / max } else { 0.0 };
+                    egui::pos2(
+                        rect.left() + rect.width() * m.phase as f32,
+                        rect.bottom() - rect.height() * y as f32,
+                    )
+                })
+                .collect()
+        };
+        painter.add(egui::Shape::line(
+            curve(&volumes, volume_max),
+            egui::Stroke::new(1.5, VOLUME_COLOR),
+        ));
+        painter.add(egui::Shape::line(
+            curve(&areas, area_max),
+            egui::Stroke::new(1.5, AREA_COLOR),
+        ));
+
+        // Текущая стадия
+        let x = rect.left() + rect.width() * self.morph_phase as f32;
+        painter.line_segment(
+            [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+            egui::Stroke::new(1.0, ui.visuals().text_color()),
+        );
+
+        ui.colored_label(
+            VOLUME_COLOR,
+            format!(
+                "{} {:.4} … {:.4}",
+                lang.tr(Text::Volume),
+                volume_min,
+                volume_max
+            ),
+        );
+        ui.colored_label(
+            AREA_COLOR,
+            format!("{} {:.4} … {:.4}", lang.tr(Text::Area), area_min, area_max),
+        );
+    }
+
+    fn render_timings_panel(&mut self, ui: &mut Ui) {
+        let lang = self.language;
+        ui.separator();
+        ui.add_space(10.0);
+
+        let timings = self.render_timings;
+        let response = egui::CollapsingHeader::new(lang.tr(Text::RenderTimings)).show(ui, |ui| {
+            let Some(timings) = timings else {
+                ui.label(lang.tr(Text::WaitingForFrame));
+                return;
+            };
+            egui::Grid::new("render_timings_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (stage, duration) in [
+                        (lang.tr(Text::StageTransform), timings.transform),
+                        (lang.tr(Text::StageShading), timings.shading),
+                        (lang.tr(Text::StageRasterization), timings.rasterization),
+                        (lang.tr(Text::StagePostProcessing), timings.post_processing),
+                        (lang.tr(Text::StageTotal), timings.total),
+                    ] {
+                        ui.label(stage);
+                        ui.label(format!(
+                            "{:.2} {}",
+                            duration.as_secs_f64() * 1000.0,
+                            lang.tr(Text::Milliseconds)
+                        ));
+                        ui.end_row();
+                    }
+                });
+        });
+
+        // Время замеряется, только пока панель открыта
+        let open = response.body_returned.is_some();
+        if self.scene.render_options.collect_timings != open {
+            self.scene.render_options.collect_timings = open;
+            self.render_timings = None;
+            self.needs_redraw = true;
+        }
+    }
+
     fn render_viewport(&mut self, ui: &mut Ui) {
         ui.separator();
 
@@ -403,30 +1130,31 @@ impl MyEguiApp {
         let viewport_height = (available_size.y * pixels_per_point) as u32;
 
         // Обновляем размер viewport и камеру, если размер изменился
-        if viewport_width > 0 && viewport_height > 0 {
-            self.update_viewport_size(viewport_width, viewport_height);
-        }
+        self.update_viewport_size(viewport_width, viewport_height);
 
         if let Some(texture) = &self.texture {
             // Отображаем изображение на весь доступный размер
             let resp = ui.image((texture.id(), available_size));
             // Обновляем флаг наличия курсора над viewport
             self.viewport_has_pointer = resp.contains_pointer();
+            self.viewport_rect = Some(resp.rect);
         } else {
             // Текстуры нет — курсор над viewport отсутствует
             self.viewport_has_pointer = false;
+            self.viewport_rect = None;
         }
     }
 
     fn render_material_controls(&mut self, ui: &mut Ui) {
-        // Не показываем параметры материала в режиме морфинга
-        if self.view_mode == ViewMode::Morph {
+        let lang = self.language;
+        // Не показываем параметры материала в режиме морфинга и параметризаций
+        if !matches!(self.view_mode, ViewMode::Source | ViewMode::Target) {
             return;
         }
 
         ui.separator();
         ui.add_space(10.0);
-        ui.label("🎨 Параметры материала:");
+        ui.label(lang.tr(Text::MaterialParams));
         ui.add_space(5.0);
 
         // Показываем параметры только для исходного или целевого объекта
@@ -437,11 +1165,12 @@ impl MyEguiApp {
                 if let Some(ref mut mesh) = self.source_mesh {
                     ui.group(|ui| {
                         ui.vertical(|ui| {
-                            ui.label("Исходный объект:");
+                            ui.label(lang.tr(Text::SourceObject));
                             ui.add_space(5.0);
                             material_changed = Self::render_material_sliders_static(
                                 ui,
                                 &mut mesh.borrow_mut().material,
+                                lang,
                             );
                         });
                     });
@@ -451,17 +1180,18 @@ impl MyEguiApp {
                 if let Some(ref mut mesh) = self.target_mesh {
                     ui.group(|ui| {
                         ui.vertical(|ui| {
-                            ui.label("Целевой объект:");
+                            ui.label(lang.tr(Text::TargetObject));
                             ui.add_space(5.0);
                             material_changed = Self::render_material_sliders_static(
                                 ui,
                                 &mut mesh.borrow_mut().material,
+                                lang,
                             );
                         });
                     });
                 }
             }
-            ViewMode::Morph => {
+            ViewMode::Morph | ViewMode::SourceSphere | ViewMode::TargetSphere => {
                 // В режиме морфинга не показываем редактирование материала
             }
         }
@@ -472,11 +1202,15 @@ impl MyEguiApp {
         }
     }
 
-    fn render_material_sliders_static(ui: &mut Ui, material: &mut model3d::Material) -> bool {
+    fn render_material_sliders_static(
+        ui: &mut Ui,
+        material: &mut model3d::Material,
+        lang: Language,
+    ) -> bool {
         let mut changed = false;
 
         ui.horizontal(|ui| {
-            ui.label("Цвет:");
+            ui.label(lang.tr(Text::Color));
             ui.add_space(3.0);
             let mut color = [
                 material.color.0[0] as f32 / 255.0,
@@ -496,7 +1230,7 @@ impl MyEguiApp {
         ui.add_space(8.0);
 
         ui.vertical(|ui| {
-            ui.label("Диффузное отражение:");
+            ui.label(lang.tr(Text::Diffuse));
             ui.add_space(3.0);
             if ui
                 .add_sized(
@@ -514,7 +1248,7 @@ impl MyEguiApp {
         ui.add_space(5.0);
 
         ui.vertical(|ui| {
-            ui.label("Зеркальное отражение:");
+            ui.label(lang.tr(Text::SpecularReflectance));
             ui.add_space(3.0);
             if ui
                 .add_sized(
@@ -532,12 +1266,119 @@ impl MyEguiApp {
         ui.add_space(5.0);
 
         ui.vertical(|ui| {
-            ui.label("Глянцевость:");
+            ui.label(lang.tr(Text::Gloss));
+            ui.add_space(3.0);
+            if ui
+                .add_sized(
+                    Vec2::new(ui.available_width(), 20.0),
+                    egui::Slider::new(&mut material.gloss, GLOSS_MIN..=GLOSS_MAX)
+                        .logarithmic(true)
+                        .step_by(1.0)
+                        .fixed_decimals(0),
+                )
+                .changed()
+            {
+                changed = true;
+            }
+        });
+
+        ui.add_space(5.0);
+
+        ui.vertical(|ui| {
+            ui.label(lang.tr(Text::Opacity));
+            ui.add_space(3.0);
+            if ui
+                .add_sized(
+                    Vec2::new(ui.available_width(), 20.0),
+                    egui::Slider::new(&mut material.opacity, 0.0..=1.0)
+                        .step_by(0.01)
+                        .fixed_decimals(2),
+                )
+                .on_hover_text(lang.tr(Text::OpacityHint))
+                .changed()
+            {
+                changed = true;
+            }
+        });
+
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.label(lang.tr(Text::Pattern));
+            for (pattern, name) in [
+                (model3d::Pattern::Solid, lang.tr(Text::None)),
+                (
+                    model3d::Pattern::Checkerboard,
+                    lang.tr(Text::PatternCheckerboard),
+                ),
+                (model3d::Pattern::Stripes, lang.tr(Text::PatternStripes)),
+            ] {
+                changed |= ui
+                    .selectable_value(&mut material.pattern, pattern, name)
+                    .changed();
+            }
+        });
+
+        if material.pattern != model3d::Pattern::Solid {
+            ui.add_space(3.0);
+            ui.vertical(|ui| {
+                ui.label(lang.tr(Text::PatternScale));
+                ui.add_space(3.0);
+                if ui
+                    .add_sized(
+                        Vec2::new(ui.available_width(), 20.0),
+                        egui::Slider::new(&mut material.pattern_scale, 0.05..=2.0)
+                            .step_by(0.01)
+                            .fixed_decimals(2),
+                    )
+                    .changed()
+                {
+                    changed = true;
+                }
+            });
+        }
+
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            if ui.button(lang.tr(Text::LoadTexture)).clicked()
+                && let Some(path) = FileDialog::new()
+                    .add_filter(lang.tr(Text::Images), &["png", "jpg", "jpeg"])
+                    .pick_file()
+            {
+                match material.load_texture(&path.to_string_lossy()) {
+                    Ok(()) => changed = true,
+                    Err(e) => eprintln!("Ошибка загрузки текстуры: {}", e),
+                }
+            }
+            if material.texture.is_some() && ui.button(lang.tr(Text::RemoveTexture)).clicked() {
+                material.texture = None;
+                material.texture_path = None;
+                changed = true;
+            }
+        });
+
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.label(lang.tr(Text::RimColor));
+            ui.add_space(3.0);
+            let mut color = material.rim_color.0.map(|c| c as f32 / 255.0);
+            if ui.color_edit_button_rgb(&mut color).changed() {
+                material.rim_color = image::Rgb(color.map(|c| (c * 255.0) as u8));
+                changed = true;
+            }
+        });
+
+        ui.add_space(5.0);
+
+        ui.vertical(|ui| {
+            ui.label(lang.tr(Text::RimStrength));
             ui.add_space(3.0);
             if ui
                 .add_sized(
                     Vec2::new(ui.available_width(), 20.0),
-                    egui::Slider::new(&mut material.gloss, 0.1..=15.0)
+                    egui::Slider::new(&mut material.rim_strength, 0.0..=1.0)
                         .step_by(0.01)
                         .fixed_decimals(2),
                 )
@@ -547,6 +1388,24 @@ impl MyEguiApp {
             }
         });
 
+        ui.add_space(5.0);
+
+        ui.vertical(|ui| {
+            ui.label(lang.tr(Text::RimPower));
+            ui.add_space(3.0);
+            if ui
+                .add_sized(
+                    Vec2::new(ui.available_width(), 20.0),
+                    egui::Slider::new(&mut material.rim_power, 0.5..=8.0)
+                        .step_by(0.1)
+                        .fixed_decimals(1),
+                )
+                .changed()
+            {
+                changed = true;
+            }
+        });
+
         changed
     }
 }
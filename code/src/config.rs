@@ -1,6 +1,17 @@
 use image::Rgb;
 
 pub const BACKGROUND_COLOR: Rgb<u8> = Rgb([30, 30, 30]);
+pub const NON_MANIFOLD_EDGE_COLOR: Rgb<u8> = Rgb([255, 0, 255]);
+pub const WIREFRAME_COLOR: Rgb<u8> = Rgb([255, 200, 0]);
+// Допуск теста глубины рёбер каркаса в NDC, чтобы рёбра не перекрывались своими гранями
+pub const WIREFRAME_DEPTH_BIAS: f64 = 1e-3;
+// Цвет каркаса выделенного объекта
+pub const SELECTION_COLOR: Rgb<u8> = Rgb([80, 220, 255]);
+
+// Отладочная раскраска треугольников суперсетки по происхождению рёбер
+pub const SUPERMESH_SOURCE_COLOR: Rgb<u8> = Rgb([220, 60, 60]);
+pub const SUPERMESH_TARGET_COLOR: Rgb<u8> = Rgb([60, 90, 220]);
+pub const SUPERMESH_INTERSECTION_COLOR: Rgb<u8> = Rgb([60, 200, 80]);
 
 // Camera settings
 pub const FOV_DEGREES: f64 = 60.0;
@@ -12,10 +23,68 @@ pub const FAR_PLANE: f64 = 1000.0;
 pub const AMBIENT_INTENSITY: f32 = 0.1;
 pub const DIFFUSION_FACTOR: f32 = 0.1;
 pub const LIGHT_SCATTERING: f32 = 2.;
+// Коэффициент k в ослаблении I / (1 + k * d^2)
+pub const INVERSE_SQUARE_FACTOR: f64 = 0.5;
 
 // User interaction settings
 pub const SCALING_SENSITIVITY_FACTOR: f32 = 0.002;
-pub const ROTATION_SENSITIVITY_FACTOR: f32 = 0.2;
+// Множитель угла поворота объекта мышью (1 - объект следует за курсором по трекболу)
+pub const ROTATION_SENSITIVITY_FACTOR: f64 = 1.;
+// Инерция вращения после отпускания: скорость затухания (1/с), скорость остановки (рад/с)
+// и число последних перемещений мыши, по которым оценивается скорость
+pub const ROTATION_DAMPING: f64 = 3.;
+pub const INERTIA_STOP_SPEED: f64 = 0.05;
+pub const INERTIA_SAMPLES: usize = 4;
+// Пределы накопленного масштаба объекта
+pub const MIN_MODEL_SCALE: f64 = 1e-4;
+pub const MAX_MODEL_SCALE: f64 = 1e4;
+// Начиная с этого числа вершин мировые координаты при повороте и масштабировании
+// пересчитываются параллельно; для мелких сеток накладные расходы потоков не окупаются
+pub const PARALLEL_TRANSFORM_THRESHOLD: usize = 4096;
+// Число вершин, умножаемых на матрицу преобразования одним матричным произведением.
+// Блок хранится на стеке, поэтому преобразование не выделяет память
+pub const TRANSFORM_BATCH_SIZE: usize = 64;
+// Шаг стадии морфинга клавишами-стрелками; с Shift шаг умножается на множитель
+pub const MORPH_PHASE_STEP: f64 = 0.01;
+pub const MORPH_PHASE_COARSE_FACTOR: f64 = 10.;
+// Длительность прохода морфинга от 0 до 1 и пауза на концах при проигрывании, секунды
+pub const MORPH_PLAYBACK_DURATION: f64 = 3.;
+pub const MORPH_END_DWELL: f64 = 0.5;
+// Число стадий, на которых строится график объёма и площади морфинга
+pub const MORPH_METRICS_SAMPLES: usize = 50;
+// Число кадров и частота кадров экспортируемой анимации морфинга по умолчанию
+pub const ANIMATION_FRAME_COUNT: usize = 60;
+pub const ANIMATION_FPS: u32 = 20;
+// Размер кадра экспортируемого видео по умолчанию
+#[cfg(feature = "video")]
+pub const VIDEO_WIDTH: u32 = 1280;
+#[cfg(feature = "video")]
+pub const VIDEO_HEIGHT: u32 = 720;
+
+// Material settings
+// Показатель блеска Фонга (степень косинуса в cos.max(0).powf(gloss)):
+// 1 — широкий матовый блик на всё полушарие, 128 — маленькое зеркальное пятно
+pub const GLOSS_MIN: f64 = 1.0;
+pub const GLOSS_MAX: f64 = 128.0;
+
+// Mesh loading settings
+pub const WELD_TOLERANCE: f64 = 1e-6;
+// Доля видимой области, которую занимает объект после автоподгонки при загрузке
+pub const AUTO_FIT_FILL: f64 = 0.8;
 
 // Morphing settings
 pub const RELAXATION_ROUNDS_LIMIT: usize = 10000;
+// Грани, нормали которых расходятся больше чем на этот угол, не сглаживаются
+// при интерполяции нормалей морфинга (рёбра куба остаются острыми)
+pub const NORMAL_SMOOTHING_ANGLE_DEGREES: f64 = 60.0;
+// Отношение размеров объектов, начиная с которого морфинг выглядит как резкий рост
+pub const SCALE_MISMATCH_RATIO: f64 = 10.0;
+// Компактность 6√π·V / A^(3/2) (1 у шара), ниже которой сетка считается почти
+// вырожденной: сплющенной в плоскость или вытянутой в нить
+pub const DEGENERATE_COMPACTNESS: f64 = 1e-3;
+
+// Цвет выделения интерфейса по умолчанию (как в тёмной теме egui)
+pub const ACCENT_COLOR: [u8; 3] = [0, 92, 128];
+
+// Session settings
+pub const SESSION_FILE_PATH: &str = "session.json";
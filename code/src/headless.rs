@@ -0,0 +1,371 @@
+// Консольный режим: построение морфингов и рендеринг кадров без графического интерфейса
+use crate::objects::model3d::{InteractiveModel, Model3D};
+use crate::objects::morph::Morph;
+use crate::objects::triangle_mesh::TriangleMesh;
+use crate::render::Renderer;
+use crate::render::z_buffer::ZBufferPerformer;
+use crate::scene::Scene;
+use image::RgbImage;
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::rc::Rc;
+
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 800;
+const DEFAULT_PHASE: f64 = 0.5;
+
+#[derive(Debug, PartialEq)]
+pub struct HeadlessArgs {
+    pub source: String,
+    pub target: String,
+    pub phase: f64,
+    pub out: PathBuf,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl HeadlessArgs {
+    /// Разбирает аргументы командной строки (без имени программы)
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut source = None;
+        let mut target = None;
+        let mut out = None;
+        let mut phase = DEFAULT_PHASE;
+        let mut width = DEFAULT_WIDTH;
+        let mut height = DEFAULT_HEIGHT;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .ok_or_else(|| format!("Не указано значение аргумента {}", arg))
+            };
+
+            match arg.as_str() {
+                "--headless" => {}
+                "--source" => source = Some(value()?.clone()),
+                "--target" => target = Some(value()?.clone()),
+                "--out" => out = Some(PathBuf::from(value()?)),
+                "--phase" => phase = Self::parse_value(arg, value()?)?,
+                "--width" => width = Self::parse_value(arg, value()?)?,
+                "--height" => height = Self::parse_value(arg, value()?)?,
+                other => return Err(format!("Неизвестный аргумент: {}", other)),
+            }
+        }
+
+        if !(0.0..=1.0).contains(&phase) {
+            return Err(format!(
+                "Стадия морфинга должна быть в диапазоне [0, 1], получено: {}",
+                phase
+            ));
+        }
+        if width == 0 || height == 0 {
+            return Err(format!(
+                "Размер изображения должен быть положительным, получено: {}x{}",
+                width, height
+            ));
+        }
+
+        Ok(Self {
+            source: source.ok_or("Не указан исходный объект (--source)")?,
+            target: target.ok_or("Не указан целевой объект (--target)")?,
+            phase,
+            out: out.ok_or("Не указан выходной файл (--out)")?,
+            width,
+            height,
+        })
+    }
+
+    fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+        value
+            .parse::<T>()
+            .map_err(|_| format!("Некорректное значение аргумента {}: {}", arg, value))
+    }
+}
+
+/// Аргументы пакетного построения морфингов всех пар моделей из папки
+#[derive(Debug, PartialEq)]
+pub struct BatchArgs {
+    pub dir: PathBuf,
+    pub phase: f64,
+    pub out: PathBuf,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BatchArgs {
+    /// Разбирает аргументы команды batch-morph (без имени программы и команды)
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut dir = None;
+        let mut out = None;
+        let mut phase = DEFAULT_PHASE;
+        let mut width = DEFAULT_WIDTH;
+        let mut height = DEFAULT_HEIGHT;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .ok_or_else(|| format!("Не указано значение аргумента {}", arg))
+            };
+
+            match arg.as_str() {
+                "--dir" => dir = Some(PathBuf::from(value()?)),
+                "--out" => out = Some(PathBuf::from(value()?)),
+                "--phase" => phase = HeadlessArgs::parse_value(arg, value()?)?,
+                "--width" => width = HeadlessArgs::parse_value(arg, value()?)?,
+                "--height" => height = HeadlessArgs::parse_value(arg, value()?)?,
+                other => return Err(format!("Неизвестный аргумент: {}", other)),
+            }
+        }
+
+        if !(0.0..=1.0).contains(&phase) {
+            return Err(format!(
+                "Стадия морфинга должна быть в диапазоне [0, 1], получено: {}",
+                phase
+            ));
+        }
+        if width == 0 || height == 0 {
+            return Err(format!(
+                "Размер изображения должен быть положительным, получено: {}x{}",
+                width, height
+            ));
+        }
+
+        Ok(Self {
+            dir: dir.ok_or("Не указана папка с моделями (--dir)")?,
+            phase,
+            out: out.ok_or("Не указана папка для изображений (--out)")?,
+            width,
+            height,
+        })
+    }
+}
+
+/// Рендерит морфинг на заданной стадии
+fn render_frame(mut morph: Morph, phase: f64, width: u32, height: u32) -> RgbImage {
+    morph.update(phase);
+
+    let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(morph));
+    let mut scene = Scene::new(width as f64 / height as f64);
+    scene.object = Some(object);
+
+    let mut renderer = ZBufferPerformer::new(width, height);
+    renderer.create_frame(width, height, &scene)
+}
+
+/// Строит морфинг, рендерит кадр на заданной стадии и сохраняет его в PNG
+pub fn render_morph(args: &HeadlessArgs) -> Result<(), Box<dyn Error>> {
+    let source = TriangleMesh::from_obj(&args.source)?;
+    let target = TriangleMesh::from_obj(&args.target)?;
+
+    let morph = Morph::new(source, target)?;
+    render_frame(morph, args.phase, args.width, args.height).save(&args.out)?;
+
+    Ok(())
+}
+
+/// Итог пакетного построения морфингов
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchReport {
+    pub rendered: usize,
+    /// Модели, которые не удалось загрузить
+    pub load_failed: usize,
+    /// Пары, для которых не удалось построить или сохранить морфинг
+    pub morph_failed: usize,
+}
+
+impl BatchReport {
+    /// Пакет считается неудачным, если ошибки были, а ни одного кадра не построено
+    pub fn is_failure(&self) -> bool {
+        self.rendered == 0 && self.load_failed + self.morph_failed > 0
+    }
+}
+
+/// OBJ файлы папки в алфавитном порядке
+fn obj_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("obj"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Строит морфинг каждой неупорядоченной пары моделей папки и сохраняет кадр
+/// на заданной стадии в `<out>/<исходная>-<целевая>.png`. Ошибки загрузки и
+/// построения отдельных морфингов выводятся в консоль и не прерывают обработку.
+pub fn batch_morph(args: &BatchArgs) -> Result<BatchReport, Box<dyn Error>> {
+    let mut report = BatchReport::default();
+    let mut meshes = Vec::new();
+    for path in obj_files(&args.dir)? {
+        match TriangleMesh::from_obj(&path.to_string_lossy()) {
+            Ok(mesh) => meshes.push((file_stem(&path), mesh)),
+            Err(e) => {
+                eprintln!("Пропущена модель {}: {}", path.display(), e);
+                report.load_failed += 1;
+            }
+        }
+    }
+    fs::create_dir_all(&args.out)?;
+
+    for (i, (source_name, source)) in meshes.iter().enumerate() {
+        for (target_name, target) in &meshes[i + 1..] {
+            let name = format!("{}-{}", source_name, target_name);
+            let result = Morph::new(source.clone(), target.clone())
+                .map_err(Box::<dyn Error>::from)
+                .and_then(|morph| {
+                    let frame = render_frame(morph, args.phase, args.width, args.height);
+                    frame.save(args.out.join(format!("{}.png", name)))?;
+                    Ok(())
+                });
+
+            match result {
+                Ok(()) => {
+                    println!("Готово: {}", name);
+                    report.rendered += 1;
+                }
+                Err(e) => {
+                    eprintln!("Ошибка морфинга {}: {}", name, e);
+                    report.morph_failed += 1;
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Точка входа команды batch-morph
+pub fn run_batch(args: &[String]) -> ExitCode {
+    let result = BatchArgs::parse(args)
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|args| batch_morph(&args));
+
+    match result {
+        Ok(report) => {
+            println!(
+                "Построено морфингов: {}, не загружено моделей: {}, ошибок морфинга: {}",
+                report.rendered, report.load_failed, report.morph_failed
+            );
+            if report.is_failure() {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Err(e) => {
+            eprintln!("Ошибка: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let result = HeadlessArgs::parse(args)
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|args| render_morph(&args));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Ошибка: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_full_args() {
+        let parsed = HeadlessArgs::parse(&args(&[
+            "--headless",
+            "--source",
+            "a.obj",
+            "--target",
+            "b.obj",
+            "--phase",
+            "0.25",
+            "--out",
+            "out.png",
+            "--width",
+            "320",
+            "--height",
+            "240",
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            parsed,
+            HeadlessArgs {
+                source: "a.obj".into(),
+                target: "b.obj".into(),
+                phase: 0.25,
+                out: PathBuf::from("out.png"),
+                width: 320,
+                height: 240,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_missing_and_invalid_args() {
+        assert!(HeadlessArgs::parse(&args(&["--headless", "--source", "a.obj"])).is_err());
+        assert!(
+            HeadlessArgs::parse(&args(&[
+                "--source", "a.obj", "--target", "b.obj", "--out", "o.png", "--phase", "2"
+            ]))
+            .is_err()
+        );
+        assert!(HeadlessArgs::parse(&args(&["--width"])).is_err());
+    }
+
+    #[test]
+    fn parse_batch_args() {
+        let parsed = BatchArgs::parse(&args(&[
+            "--dir", "models", "--phase", "0.3", "--out", "frames",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.dir, PathBuf::from("models"));
+        assert_eq!(parsed.out, PathBuf::from("frames"));
+        assert_eq!(parsed.phase, 0.3);
+        assert!(BatchArgs::parse(&args(&["--dir", "models"])).is_err());
+    }
+
+    #[test]
+    fn batch_without_frames_fails_only_after_errors() {
+        assert!(!BatchReport::default().is_failure());
+
+        let load_failed = BatchReport {
+            load_failed: 1,
+            ..Default::default()
+        };
+        assert!(load_failed.is_failure());
+
+        let partial = BatchReport {
+            rendered: 1,
+            morph_failed: 2,
+            ..Default::default()
+        };
+        assert!(!partial.is_failure());
+    }
+}
@@ -1,3 +1,17 @@
+//! Морфинг трёхмерных моделей: сетки и их преобразования, построение морфинга
+//! и программные рендереры. Графическое приложение (`main.rs`) использует эту
+//! библиотеку так же, как бенчмарки и интеграционные тесты.
+//!
+//! ```
+//! use code::objects::model3d::Model3D;
+//! use code::objects::triangle_mesh::TriangleMesh;
+//!
+//! let cube = TriangleMesh::cube();
+//! assert_eq!(cube.triangles().len(), 12);
+//! assert!((cube.volume() - 1.).abs() < 1e-9);
+//! ```
 pub mod config;
 pub mod objects;
+pub mod render;
+pub mod scene;
 pub mod utils;
@@ -1,31 +1,68 @@
 mod app;
-mod config;
-mod objects;
-mod render;
-mod scene;
-mod utils;
+mod headless;
+
+// Модули библиотеки доступны приложению по путям `crate::...`
+use code::{config, objects, render, scene, utils};
 
 use app::MyEguiApp;
 use eframe::egui::Context;
 use eframe::{App, Frame, NativeOptions};
+use std::process::ExitCode;
 
 impl App for MyEguiApp {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
-        self.update_fps();
-        self.mouse_wheel_scaling(ctx);
-        self.mouse_drag_rotation(ctx);
+        let dt = self.update_fps();
+        self.poll_morph_build();
+        self.poll_animation_export();
+        self.advance_morph_playback(dt);
+        // Пока открыто окно с ошибкой, ввод обрабатывает только оно
+        if !self.is_error_shown() {
+            self.mouse_wheel_dolly(ctx);
+            self.mouse_drag_rotation(ctx);
+            self.mouse_click_picking(ctx);
+            self.keyboard_camera_commands(ctx);
+            self.keyboard_morph_phase(ctx);
+            self.touch_gestures(ctx);
+        }
+        self.apply_rotation_inertia(dt);
         self.render_ui(ctx);
 
-        ctx.request_repaint();
+        // Окно с ошибкой перерисовывается egui само при взаимодействии с ним
+        if !self.is_error_shown() {
+            ctx.request_repaint();
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.stop_morph_build();
+        self.stop_animation_export();
+        self.save_session();
     }
 }
 
-fn main() -> Result<(), eframe::Error> {
-    let app = MyEguiApp::default();
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "batch-morph") {
+        return headless::run_batch(&args[1..]);
+    }
+    if args.iter().any(|arg| arg == "--headless") {
+        return headless::run(&args);
+    }
+
+    let mut app = MyEguiApp::default();
+    app.restore_session();
     let native_options = NativeOptions::default();
-    eframe::run_native(
+    let result = eframe::run_native(
         "Морфинг фруктов",
         native_options,
         Box::new(|_cc| Ok(Box::new(app))),
-    )
+    );
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Ошибка запуска приложения: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -1,4 +1,47 @@
-use nalgebra::{Matrix4, Point3, Vector3};
+use crate::config::{ASPECT_RATIO, FAR_PLANE, FOV_DEGREES, NEAR_PLANE};
+use nalgebra::{Matrix3, Matrix4, Point3, Rotation3, UnitQuaternion, Vector3};
+use std::f64::consts::PI;
+
+/// Минимальный угол обзора
+const MIN_FOV_RADIANS: f64 = PI / 180.;
+/// Максимальный угол обзора
+const MAX_FOV_RADIANS: f64 = PI - PI / 180.;
+/// Минимальное расстояние до ближней плоскости отсечения
+const MIN_NEAR_PLANE: f64 = 1e-4;
+
+/// Стандартные виды объекта: какой стороной он повёрнут к камере
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PresetView {
+    Front,
+    Back,
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl PresetView {
+    pub const ALL: [PresetView; 6] = [
+        PresetView::Front,
+        PresetView::Back,
+        PresetView::Top,
+        PresetView::Bottom,
+        PresetView::Left,
+        PresetView::Right,
+    ];
+
+    /// Оси объекта, которые должны смотреть на камеру и вверх экрана
+    fn model_axes(self) -> (Vector3<f64>, Vector3<f64>) {
+        match self {
+            PresetView::Front => (Vector3::z(), Vector3::y()),
+            PresetView::Back => (-Vector3::z(), Vector3::y()),
+            PresetView::Top => (Vector3::y(), -Vector3::z()),
+            PresetView::Bottom => (-Vector3::y(), Vector3::z()),
+            PresetView::Left => (-Vector3::x(), Vector3::y()),
+            PresetView::Right => (Vector3::x(), Vector3::y()),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Camera {
@@ -15,6 +58,8 @@ pub struct Camera {
 }
 
 impl Camera {
+    /// Создаёт камеру. Некорректные параметры проекции (`fov` вне (0, π),
+    /// не выполняется 0 < near < far) заменяются допустимыми с предупреждением.
     pub fn new(
         pos: Point3<f64>,
         look_at: Point3<f64>,
@@ -24,6 +69,9 @@ impl Camera {
         near_plane: f64,
         far_plane: f64,
     ) -> Self {
+        let (fov_radians, aspect_ratio, near_plane, far_plane) =
+            Self::sanitize_projection(fov_radians, aspect_ratio, near_plane, far_plane);
+
         let perspective_matrix =
             Matrix4::new_perspective(aspect_ratio, fov_radians, near_plane, far_plane);
         let view_matrix = Matrix4::look_at_rh(&pos, &look_at, &up);
@@ -42,4 +90,322 @@ impl Camera {
             camera_matrix,
         }
     }
+
+    pub fn fov_radians(&self) -> f64 {
+        self.fov_radians
+    }
+
+    pub fn near_plane(&self) -> f64 {
+        self.near_plane
+    }
+
+    pub fn far_plane(&self) -> f64 {
+        self.far_plane
+    }
+
+    /// Точка, в которую направлена камера
+    pub fn look_at(&self) -> Point3<f64> {
+        self.look_at
+    }
+
+    /// Направление «вверх» камеры
+    pub fn up(&self) -> Vector3<f64> {
+        self.up
+    }
+
+    /// Направляет камеру на `target` и пересчитывает матрицы
+    pub fn set_look_at(&mut self, target: Point3<f64>) {
+        self.look_at = target;
+        self.update_view_matrix();
+    }
+
+    /// Задаёт направление «вверх» и пересчитывает матрицы
+    pub fn set_up(&mut self, up: Vector3<f64>) {
+        self.up = up;
+        self.update_view_matrix();
+    }
+
+    /// Перемещает камеру в `pos`, сохраняя точку наблюдения, и пересчитывает матрицы
+    pub fn set_position(&mut self, pos: Point3<f64>) {
+        self.pos = pos;
+        self.update_view_matrix();
+    }
+
+    /// Ориентация объекта, при которой он виден камере с выбранной стороны `view`
+    /// (без учёта накопленного поворота)
+    pub fn preset_orientation(&self, view: PresetView) -> UnitQuaternion<f64> {
+        // Базис камеры: направление на камеру и ортогонализованный «верх»
+        let toward_camera = (self.pos - self.look_at)
+            .try_normalize(f64::EPSILON)
+            .unwrap_or_else(Vector3::z);
+        let screen_up = (self.up - toward_camera * self.up.dot(&toward_camera))
+            .try_normalize(f64::EPSILON)
+            .unwrap_or_else(|| toward_camera.cross(&Vector3::x()).normalize());
+
+        let (model_toward, model_up) = view.model_axes();
+        let world =
+            Matrix3::from_columns(&[toward_camera, screen_up, toward_camera.cross(&screen_up)]);
+        let model = Matrix3::from_columns(&[model_toward, model_up, model_toward.cross(&model_up)]);
+
+        UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(
+            world * model.transpose(),
+        ))
+    }
+
+    /// Расстояние от камеры до точки наблюдения
+    pub fn distance(&self) -> f64 {
+        (self.pos - self.look_at).norm()
+    }
+
+    /// Приближает камеру к точке наблюдения на `delta` (отрицательное значение отдаляет)
+    /// и пересчитывает матрицы. Расстояние остаётся между удвоенной ближней и половиной
+    /// дальней плоскости отсечения.
+    pub fn dolly(&mut self, delta: f64) {
+        let offset = self.pos - self.look_at;
+        let distance = offset.norm();
+        if distance < f64::EPSILON || !delta.is_finite() {
+            return;
+        }
+
+        let min_distance = 2. * self.near_plane;
+        let max_distance = (self.far_plane / 2.).max(distance).max(min_distance);
+        let new_distance = (distance - delta).clamp(min_distance, max_distance);
+        self.pos = self.look_at + offset * (new_distance / distance);
+        self.update_view_matrix();
+    }
+
+    /// Радиус наибольшей сферы с центром в точке наблюдения, целиком видимой камерой
+    pub fn framing_radius(&self) -> f64 {
+        self.distance() * self.min_half_fov().sin()
+    }
+
+    /// Расстояние до центра сферы радиуса `radius`, на котором она целиком видна
+    /// (обратное к framing_radius)
+    pub fn framing_distance(&self, radius: f64) -> f64 {
+        radius / self.min_half_fov().sin()
+    }
+
+    /// Половина меньшего из вертикального и горизонтального углов обзора
+    fn min_half_fov(&self) -> f64 {
+        let half_vertical = self.fov_radians / 2.;
+        let half_horizontal = (half_vertical.tan() * self.aspect_ratio).atan();
+        half_vertical.min(half_horizontal)
+    }
+
+    /// Изменяет угол обзора и пересчитывает матрицы проекции
+    pub fn set_fov(&mut self, fov_radians: f64) {
+        let (fov_radians, ..) = Self::sanitize_projection(
+            fov_radians,
+            self.aspect_ratio,
+            self.near_plane,
+            self.far_plane,
+        );
+        self.fov_radians = fov_radians;
+        self.perspective_matrix = Matrix4::new_perspective(
+            self.aspect_ratio,
+            self.fov_radians,
+            self.near_plane,
+            self.far_plane,
+        );
+        self.camera_matrix = self.perspective_matrix * self.view_matrix;
+    }
+
+    /// Изменяет соотношение сторон кадра и пересчитывает матрицы проекции
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        let (_, aspect_ratio, ..) = Self::sanitize_projection(
+            self.fov_radians,
+            aspect_ratio,
+            self.near_plane,
+            self.far_plane,
+        );
+        self.aspect_ratio = aspect_ratio;
+        self.perspective_matrix = Matrix4::new_perspective(
+            self.aspect_ratio,
+            self.fov_radians,
+            self.near_plane,
+            self.far_plane,
+        );
+        self.camera_matrix = self.perspective_matrix * self.view_matrix;
+    }
+
+    fn update_view_matrix(&mut self) {
+        self.view_matrix = Matrix4::look_at_rh(&self.pos, &self.look_at, &self.up);
+        self.camera_matrix = self.perspective_matrix * self.view_matrix;
+    }
+
+    /// Приводит параметры перспективной проекции к допустимым значениям
+    fn sanitize_projection(
+        fov_radians: f64,
+        aspect_ratio: f64,
+        near_plane: f64,
+        far_plane: f64,
+    ) -> (f64, f64, f64, f64) {
+        let fov = if fov_radians.is_nan() {
+            FOV_DEGREES.to_radians()
+        } else {
+            fov_radians.clamp(MIN_FOV_RADIANS, MAX_FOV_RADIANS)
+        };
+        if fov != fov_radians {
+            eprintln!(
+                "Некорректный угол обзора камеры {}, используется {}",
+                fov_radians, fov
+            );
+        }
+
+        let aspect = if aspect_ratio.is_finite() && aspect_ratio > 0. {
+            aspect_ratio
+        } else {
+            eprintln!(
+                "Некорректное соотношение сторон {}, используется {}",
+                aspect_ratio, ASPECT_RATIO
+            );
+            ASPECT_RATIO
+        };
+
+        let near = if near_plane.is_finite() && near_plane > 0. {
+            near_plane
+        } else {
+            eprintln!(
+                "Некорректная ближняя плоскость отсечения {}, используется {}",
+                near_plane, MIN_NEAR_PLANE
+            );
+            MIN_NEAR_PLANE
+        };
+
+        let far = if far_plane.is_finite() && far_plane > near {
+            far_plane
+        } else {
+            // Сохраняем отношение плоскостей по умолчанию
+            let far = near * FAR_PLANE / NEAR_PLANE;
+            eprintln!(
+                "Дальняя плоскость отсечения {} не дальше ближней {}, используется {}",
+                far_plane, near, far
+            );
+            far
+        };
+
+        (fov, aspect, near, far)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::model3d::Model3D;
+    use crate::objects::triangle_mesh::TriangleMesh;
+
+    fn camera(fov_radians: f64, near_plane: f64, far_plane: f64) -> Camera {
+        Camera::new(
+            Point3::new(0., 0., 3.),
+            Point3::origin(),
+            Vector3::y(),
+            fov_radians,
+            1.,
+            near_plane,
+            far_plane,
+        )
+    }
+
+    fn assert_valid(camera: &Camera) {
+        assert!(camera.fov_radians > 0. && camera.fov_radians < PI);
+        assert!(camera.near_plane > 0.);
+        assert!(camera.far_plane > camera.near_plane);
+        assert!(camera.camera_matrix.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn valid_parameters_are_kept() {
+        let camera = camera(1., 0.5, 10.);
+        assert_eq!(
+            (camera.fov_radians, camera.near_plane, camera.far_plane),
+            (1., 0.5, 10.)
+        );
+    }
+
+    #[test]
+    fn invalid_planes_are_clamped() {
+        for (near, far) in [
+            (0., 10.),
+            (-1., 10.),
+            (5., 5.),
+            (5., 1.),
+            (f64::NAN, f64::NAN),
+        ] {
+            assert_valid(&camera(1., near, far));
+        }
+    }
+
+    #[test]
+    fn wider_fov_shrinks_projection() {
+        let point = Point3::new(0.5, 0.5, 0.);
+        let projected_size = |camera: &Camera| {
+            let p =
+                Point3::from_homogeneous(camera.camera_matrix * point.to_homogeneous()).unwrap();
+            p.x.abs() + p.y.abs()
+        };
+
+        let mut camera = camera(40f64.to_radians(), 0.1, 100.);
+        let narrow = projected_size(&camera);
+        camera.set_fov(100f64.to_radians());
+        let wide = projected_size(&camera);
+
+        assert!((camera.fov_radians() - 100f64.to_radians()).abs() < 1e-12);
+        assert!(wide < narrow, "narrow: {}, wide: {}", narrow, wide);
+    }
+
+    #[test]
+    fn invalid_fov_is_clamped() {
+        for fov in [0., -1., PI, 4., f64::NAN] {
+            assert_valid(&camera(fov, 0.1, 100.));
+        }
+    }
+
+    #[test]
+    fn dolly_in_enlarges_projection_without_moving_object() {
+        let mesh = TriangleMesh::unit_sphere(1);
+        let world_before = mesh.vertices_world().clone();
+        let projected_size = |camera: &Camera| {
+            mesh.vertices_world()
+                .iter()
+                .map(|v| {
+                    let p = Point3::from_homogeneous(camera.camera_matrix * v.to_homogeneous())
+                        .unwrap();
+                    p.x.abs().max(p.y.abs())
+                })
+                .fold(0., f64::max)
+        };
+
+        let mut camera = camera(60f64.to_radians(), 0.1, 100.);
+        let far = projected_size(&camera);
+        camera.dolly(1.);
+        let near = projected_size(&camera);
+
+        assert!((camera.distance() - 2.).abs() < 1e-12);
+        assert!(near > far, "far: {}, near: {}", far, near);
+        assert_eq!(mesh.vertices_world(), &world_before);
+
+        // Камера не проходит сквозь точку наблюдения
+        camera.dolly(100.);
+        assert!(camera.distance() > 0.);
+    }
+
+    #[test]
+    fn moved_and_reaimed_camera_has_expected_view_matrix() {
+        let mut camera = camera(60f64.to_radians(), 0.1, 100.);
+        let pos = Point3::new(2., 1., 4.);
+        let target = Point3::new(1., 0., -1.);
+        camera.set_position(pos);
+        camera.set_look_at(target);
+
+        let expected = Matrix4::look_at_rh(&pos, &target, &Vector3::y());
+        assert_eq!(camera.look_at(), target);
+        assert_eq!(camera.up(), Vector3::y());
+        assert!((camera.view_matrix - expected).norm() < 1e-12);
+        assert!((camera.camera_matrix - camera.perspective_matrix * expected).norm() < 1e-12);
+
+        // Точка наблюдения проецируется в центр кадра
+        let center =
+            Point3::from_homogeneous(camera.camera_matrix * target.to_homogeneous()).unwrap();
+        assert!(center.x.abs() < 1e-12 && center.y.abs() < 1e-12);
+    }
 }
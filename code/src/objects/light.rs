@@ -1,9 +1,57 @@
+use crate::config::{INVERSE_SQUARE_FACTOR, LIGHT_SCATTERING};
 use crate::objects::Point;
 use image::Rgb;
+use serde::{Deserialize, Serialize};
+
+/// Закон ослабления света с расстоянием
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Attenuation {
+    /// Без ослабления
+    None,
+    /// I / (d + LIGHT_SCATTERING)
+    #[default]
+    Linear,
+    /// I / (1 + k * d^2), физически правдоподобное ослабление
+    InverseSquare,
+}
 
 #[derive(Clone)]
 pub struct LightSource {
     pub pos: Point,
     pub intensity: f64,
     pub color: Rgb<u8>,
+    pub attenuation: Attenuation,
+}
+
+impl LightSource {
+    /// Интенсивность света, дошедшего до точки на расстоянии `dist` от источника
+    pub fn intensity_at(&self, dist: f64) -> f64 {
+        match self.attenuation {
+            Attenuation::None => self.intensity,
+            Attenuation::Linear => self.intensity / (dist + LIGHT_SCATTERING as f64),
+            Attenuation::InverseSquare => {
+                self.intensity / (1. + INVERSE_SQUARE_FACTOR * dist * dist)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imageproc::definitions::HasWhite;
+
+    #[test]
+    fn inverse_square_quarters_intensity_at_double_distance() {
+        let light = LightSource {
+            pos: Point::origin(),
+            intensity: 15.,
+            color: Rgb::white(),
+            attenuation: Attenuation::InverseSquare,
+        };
+
+        let near = light.intensity_at(100.);
+        let far = light.intensity_at(200.);
+        assert!((far / near - 0.25).abs() < 1e-3, "ratio: {}", far / near);
+    }
 }
@@ -4,6 +4,7 @@ pub mod camera;
 pub mod light;
 pub mod model3d;
 pub mod morph;
+pub mod transform;
 pub mod triangle_mesh;
 
 type Point = Point3<f64>;
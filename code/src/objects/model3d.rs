@@ -1,9 +1,20 @@
 use crate::objects::Point;
 use crate::utils::math::lerp;
-use image::Rgb;
-use nalgebra::{Matrix4, Vector3, Vector4};
+use crate::utils::morphing::SegmentOrigin;
+use crate::utils::triangles::triangle_area;
+use image::{Rgb, RgbImage};
+use nalgebra::{Matrix4, Vector2, Vector3, Vector4};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::error::Error;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
 pub type Triangle = (usize, usize, usize);
 
+/// Brightness of the dark cells of a procedural pattern
+const PATTERN_DARK_FACTOR: f64 = 0.5;
+
 pub trait Model3D {
     /// List of triangle faces
     fn triangles(&self) -> &Vec<Triangle>;
@@ -29,7 +40,129 @@ pub trait Model3D {
     /// Get model's transformation matrix
     fn model_matrix(&self) -> &Matrix4<f64>;
 
-    fn update(&mut self, t: f64) {}
+    /// Axis-aligned bounding box `(min, max)` of the world-space vertices,
+    /// `None` for a model without vertices
+    fn bounding_box(&self) -> Option<(Point, Point)> {
+        let first = *self.vertices_world().first()?;
+        Some(
+            self.vertices_world()
+                .iter()
+                .fold((first, first), |(min, max), v| (min.inf(v), max.sup(v))),
+        )
+    }
+
+    /// Total area of the world-space triangles
+    fn surface_area(&self) -> f64 {
+        let vertices = self.vertices_world();
+        self.triangles()
+            .iter()
+            .map(|tri| triangle_area(&vertices[tri.0], &vertices[tri.1], &vertices[tri.2]))
+            .sum()
+    }
+
+    /// Volume enclosed by a closed mesh as a sum of signed tetrahedra with the origin;
+    /// positive when the faces are wound outward
+    fn signed_volume(&self) -> f64 {
+        let vertices = self.vertices_world();
+        self.triangles()
+            .iter()
+            .map(|tri| {
+                let [a, b, c] = [tri.0, tri.1, tri.2].map(|v| vertices[v].coords);
+                a.dot(&b.cross(&c)) / 6.
+            })
+            .sum()
+    }
+
+    /// Enclosed volume regardless of the face orientation
+    fn volume(&self) -> f64 {
+        self.signed_volume().abs()
+    }
+
+    /// Texture coordinates of triangle corners, if the model has them
+    fn uvs(&self) -> Option<&[[Vector2<f64>; 3]]> {
+        None
+    }
+
+    /// Return true if the model is rendered with an image texture
+    fn has_texture(&self) -> bool {
+        self.material().texture.is_some() && self.uvs().is_some()
+    }
+
+    /// Sample the texture at a point of a triangle given by barycentric coordinates
+    fn sample_texture(&self, triangle: usize, bary: &Vector3<f64>) -> Option<Rgb<u8>> {
+        let [uv0, uv1, uv2] = self.uvs()?.get(triangle)?;
+        self.material()
+            .sample_texture(&(uv0 * bary.x + uv1 * bary.y + uv2 * bary.z))
+    }
+
+    /// Per-vertex ambient occlusion factors in [0, 1], if the model provides them
+    fn ambient_occlusion(&self) -> Option<&[f64]> {
+        None
+    }
+
+    /// Normalized per-vertex normals in world space, if the model provides them.
+    /// Triangles of such a model are shaded smoothly instead of with their face normals
+    fn vertex_normals(&self) -> Option<&[Vector4<f64>]> {
+        None
+    }
+
+    /// Per-triangle origin of a morph supermesh, used for debug coloring
+    fn triangle_origins(&self) -> Option<&[SegmentOrigin]> {
+        None
+    }
+
+    fn update(&mut self, _t: f64) {}
+
+    /// Wavefront OBJ text of the model in object space. Faces keep the stored
+    /// winding unless `options.flip_winding` is set; per-face normals are written
+    /// as `vn` lines when `options.normals` is set. Normals are brought back from
+    /// world space with the transpose of the model's linear part (the inverse of
+    /// the normal matrix), so they match the exported vertices.
+    fn to_obj_string(&self, options: &ObjExportOptions) -> String {
+        let mut obj = String::new();
+        for v in self.vertices() {
+            obj.push_str(&format!("v {} {} {}\n", v.x, v.y, v.z));
+        }
+
+        let sign = if options.flip_winding { -1. } else { 1. };
+        let normals = options.normals && self.normals().len() == self.triangles().len();
+        if normals {
+            let to_object = self.model_matrix().fixed_view::<3, 3>(0, 0).transpose();
+            for n in self.normals() {
+                let n = (to_object * n.xyz())
+                    .try_normalize(f64::EPSILON)
+                    .unwrap_or(n.xyz());
+                obj.push_str(&format!(
+                    "vn {} {} {}\n",
+                    sign * n.x,
+                    sign * n.y,
+                    sign * n.z
+                ));
+            }
+        }
+
+        // OBJ indices are 1-based
+        for (i, &(a, b, c)) in self.triangles().iter().enumerate() {
+            let (b, c) = if options.flip_winding { (c, b) } else { (b, c) };
+            if normals {
+                let n = i + 1;
+                obj.push_str(&format!("f {}//{n} {}//{n} {}//{n}\n", a + 1, b + 1, c + 1));
+            } else {
+                obj.push_str(&format!("f {} {} {}\n", a + 1, b + 1, c + 1));
+            }
+        }
+        obj
+    }
+}
+
+/// Options of [`Model3D::to_obj_string`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ObjExportOptions {
+    /// Write per-face normals
+    pub normals: bool,
+    /// Reverse the vertex order of every face (and the normals with it)
+    /// for tools expecting the opposite winding convention
+    pub flip_winding: bool,
 }
 
 pub trait Translate {
@@ -42,19 +175,56 @@ pub trait Rotate {
 
 pub trait Scale {
     fn scale(&mut self, scaling: f64);
+
+    /// Non-uniform scaling along the object's own axes
+    fn scale_xyz(&mut self, scaling: (f64, f64, f64));
 }
 
+/// Deferred constructor of an independent copy of a model that can be sent to another thread
+pub type ModelSnapshot = Box<dyn FnOnce() -> Rc<RefCell<dyn InteractiveModel>> + Send>;
+
 pub trait InteractiveModel: Model3D + Rotate + Scale {
     fn reset_transformations(&mut self);
+
+    /// Replace the accumulated transformation. The matrix must have the form
+    /// translation * rotation * scaling, as produced by `model_matrix`
+    fn set_model_matrix(&mut self, matrix: &Matrix4<f64>);
+
+    /// Copy of the model in its current state, e.g. for rendering on a worker thread
+    fn snapshot(&self) -> ModelSnapshot;
+}
+
+/// Procedural pattern sampled in object space
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Pattern {
+    #[default]
+    Solid,
+    Checkerboard,
+    /// Horizontal stripes along the Y axis
+    Stripes,
 }
 
 #[derive(Clone)]
 pub struct Material {
     pub diffuse_reflectance_factor: f64,
     pub specular_reflectance_factor: f64,
+    /// Phong exponent in `GLOSS_MIN..=GLOSS_MAX`; larger values give a smaller, sharper highlight
     pub gloss: f64,
     pub color: Rgb<u8>,
+    /// 1 is fully opaque; objects below 1 are blended with what lies behind them
     pub opacity: f64,
+    /// Rim light (Fresnel) strength, 0 disables the term
+    pub rim_strength: f64,
+    /// Rim light falloff exponent
+    pub rim_power: f64,
+    pub rim_color: Rgb<u8>,
+    pub pattern: Pattern,
+    /// Pattern cell size in object space units
+    pub pattern_scale: f64,
+    /// Image texture sampled with the model's UVs, replaces the base color
+    pub texture: Option<Arc<RgbImage>>,
+    /// File the texture was loaded from, kept so the session can reload it
+    pub texture_path: Option<PathBuf>,
 }
 
 impl Default for Material {
@@ -62,9 +232,16 @@ impl Default for Material {
         Self {
             diffuse_reflectance_factor: 0.45,
             specular_reflectance_factor: 0.02,
-            gloss: 1.,
+            gloss: 16.,
             color: Rgb([70, 70, 70]),
-            opacity: 0.1,
+            opacity: 1.,
+            rim_strength: 0.,
+            rim_power: 3.,
+            rim_color: Rgb([255, 255, 255]),
+            pattern: Pattern::Solid,
+            pattern_scale: 0.25,
+            texture: None,
+            texture_path: None,
         }
     }
 }
@@ -83,11 +260,21 @@ impl Material {
         );
         let gloss = lerp(a.gloss, b.gloss, t);
         let opacity = lerp(a.opacity, b.opacity, t);
+        let rim_strength = lerp(a.rim_strength, b.rim_strength, t);
+        let rim_power = lerp(a.rim_power, b.rim_power, t);
+        let pattern = if t < 0.5 { a.pattern } else { b.pattern };
+        let pattern_scale = lerp(a.pattern_scale, b.pattern_scale, t);
+        let (texture, texture_path) = if t < 0.5 {
+            (a.texture.clone(), a.texture_path.clone())
+        } else {
+            (b.texture.clone(), b.texture_path.clone())
+        };
 
-        let r = lerp(a.color[0] as f64, b.color[0] as f64, t).round() as u8;
-        let g = lerp(a.color[1] as f64, b.color[1] as f64, t).round() as u8;
-        let b = lerp(a.color[2] as f64, b.color[2] as f64, t).round() as u8;
-        let color = Rgb([r, g, b]);
+        let lerp_color = |from: &Rgb<u8>, to: &Rgb<u8>| {
+            Rgb([0, 1, 2].map(|i| lerp(from[i] as f64, to[i] as f64, t).round() as u8))
+        };
+        let color = lerp_color(&a.color, &b.color);
+        let rim_color = lerp_color(&a.rim_color, &b.rim_color);
 
         Material {
             diffuse_reflectance_factor,
@@ -95,6 +282,134 @@ impl Material {
             gloss,
             color,
             opacity,
+            rim_strength,
+            rim_power,
+            rim_color,
+            pattern,
+            pattern_scale,
+            texture,
+            texture_path,
+        }
+    }
+
+    /// Load a PNG/JPG texture from disk
+    pub fn load_texture(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        let texture = image::open(path)?.to_rgb8();
+        if texture.width() == 0 || texture.height() == 0 {
+            return Err(format!("Empty texture: {}", path).into());
+        }
+        self.texture = Some(Arc::new(texture));
+        self.texture_path = Some(PathBuf::from(path));
+        Ok(())
+    }
+
+    /// Nearest texel at the texture coordinates (repeat wrapping, V axis points up)
+    pub fn sample_texture(&self, uv: &Vector2<f64>) -> Option<Rgb<u8>> {
+        let texture = self.texture.as_ref()?;
+        let (width, height) = texture.dimensions();
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let x = ((uv.x.rem_euclid(1.) * width as f64) as u32).min(width - 1);
+        let y = (((1. - uv.y.rem_euclid(1.)) * height as f64) as u32).min(height - 1);
+        Some(*texture.get_pixel(x, y))
+    }
+
+    /// Base color modulated by the procedural pattern at an object space point
+    pub fn base_color_at(&self, point: &Point) -> Rgb<u8> {
+        let cell = |coord: f64| (coord / self.pattern_scale).floor() as i64;
+        let parity = match self.pattern {
+            Pattern::Solid => return self.color,
+            Pattern::Checkerboard => cell(point.x) + cell(point.y) + cell(point.z),
+            Pattern::Stripes => cell(point.y),
+        };
+
+        if parity.rem_euclid(2) == 0 {
+            self.color
+        } else {
+            Rgb(self
+                .color
+                .0
+                .map(|c| (c as f64 * PATTERN_DARK_FACTOR).round() as u8))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::triangle_mesh::TriangleMesh;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn unit_cube_area_and_volume() {
+        let cube = TriangleMesh::cube();
+        assert!((cube.surface_area() - 6.).abs() < 1e-12);
+        assert!((cube.volume() - 1.).abs() < 1e-12);
+        assert!((cube.signed_volume() - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sphere_metrics_approach_exact_values() {
+        let errors: Vec<(f64, f64)> = (1..=3)
+            .map(|subdivisions| {
+                let sphere = TriangleMesh::unit_sphere(subdivisions);
+                (
+                    (sphere.surface_area() - 4. * PI).abs(),
+                    (sphere.volume() - 4. / 3. * PI).abs(),
+                )
+            })
+            .collect();
+
+        // Вписанный многогранник меньше сферы, и с подразбиением погрешность убывает
+        assert!(
+            errors
+                .windows(2)
+                .all(|w| w[1].0 < w[0].0 && w[1].1 < w[0].1)
+        );
+        assert!(errors[2].0 / (4. * PI) < 0.01);
+        assert!(errors[2].1 / (4. / 3. * PI) < 0.01);
+    }
+
+    #[test]
+    fn pattern_alternates_every_cell() {
+        let material = Material {
+            pattern: Pattern::Checkerboard,
+            pattern_scale: 0.5,
+            ..Material::default()
+        };
+        let colors = [0.1, 0.6, 1.1].map(|x| material.base_color_at(&Point::new(x, 0.1, 0.1)));
+
+        assert_ne!(colors[0], colors[1]);
+        assert_eq!(colors[0], colors[2]);
+        assert_eq!(colors[0], material.color);
+
+        let solid = Material::default();
+        assert_eq!(solid.base_color_at(&Point::new(0.6, 0.1, 0.1)), solid.color);
+    }
+
+    #[test]
+    fn texture_sampling_uses_nearest_texel() {
+        let mut texture = RgbImage::new(2, 2);
+        texture.put_pixel(0, 0, Rgb([255, 0, 0])); // Верхний левый угол: v = 1
+        texture.put_pixel(1, 1, Rgb([0, 0, 255])); // Нижний правый угол: v = 0
+        let material = Material {
+            texture: Some(Arc::new(texture)),
+            ..Material::default()
+        };
+
+        assert_eq!(
+            material.sample_texture(&Vector2::new(0.25, 0.75)),
+            Some(Rgb([255, 0, 0]))
+        );
+        assert_eq!(
+            material.sample_texture(&Vector2::new(0.75, 0.25)),
+            Some(Rgb([0, 0, 255]))
+        );
+        assert_eq!(
+            Material::default().sample_texture(&Vector2::new(0.5, 0.5)),
+            None
+        );
+    }
+}
@@ -1,140 +1,322 @@
 use crate::objects::Point;
-use crate::objects::model3d::{InteractiveModel, Material, Model3D, Rotate, Scale, Triangle};
+use crate::objects::model3d::{
+    InteractiveModel, Material, Model3D, ModelSnapshot, Rotate, Scale, Triangle,
+};
+use crate::objects::transform::Transform;
 use crate::objects::triangle_mesh::TriangleMesh;
-use crate::utils::math::lerp;
+use crate::utils::math::{
+    all_finite, lerp, normal_matrix, slerp_normal, transform_into, transform_points,
+};
 use crate::utils::morphing::{
-    create_supermesh, find_normals, parametrize_mesh, relocate_vertices_on_mesh,
+    MorphParams, ParametrizeReport, SegmentOrigin, create_supermesh, find_normals, find_uvs,
+    parametrize_mesh, relocate_vertices_on_mesh,
 };
-use nalgebra::{Matrix4, Point3, Vector3, Vector4};
-
-pub type Lerp<T> = Box<dyn Fn(f64) -> T>;
+use image::Rgb;
+use nalgebra::{Matrix4, Vector2, Vector3, Vector4};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub type Lerp<T> = Arc<dyn Fn(f64) -> T + Send + Sync>;
 pub type VertexInterpolation = Lerp<Point>;
 pub type NormalInterpolation = Lerp<Vector4<f64>>;
 pub type MaterialInterpolation = Lerp<Material>;
 
+/// Площадь поверхности и объём морфинга на одной стадии
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhaseMetrics {
+    pub phase: f64,
+    pub surface_area: f64,
+    pub volume: f64,
+}
+
+/// Соответствие вершины суперсетки точкам исходной и целевой сеток:
+/// (треугольник исходной сетки, барицентрические координаты,
+///  треугольник целевой сетки, барицентрические координаты)
+pub type VertexCorrespondence = (usize, Vector3<f64>, usize, Vector3<f64>);
+
+/// Результаты построения морфинга, которые не меняются со стадией и преобразованием.
+/// Копии морфинга, например для потока отрисовки, разделяют их через `Arc`.
+struct MorphData {
+    triangles: Vec<Triangle>,
+
+    vertex_interpolations: Vec<VertexInterpolation>,
+    // Нормали вершин суперсетки, по одной интерполяции на вершину
+    normals_interpolations: Vec<NormalInterpolation>,
+    material_interpolation: MaterialInterpolation,
+
+    // Текстуры исходного и целевого объектов с координатами углов треугольников суперсетки
+    src_material: Material,
+    dst_material: Material,
+    src_uvs: Vec<[Vector2<f64>; 3]>,
+    dst_uvs: Vec<[Vector2<f64>; 3]>,
+
+    correspondence: Vec<VertexCorrespondence>,
+    // Происхождение треугольников суперсетки (для отладки пересечения сеток)
+    triangle_origins: Vec<SegmentOrigin>,
+    // Итоги релаксации исходной и целевой сеток
+    relaxation_reports: [ParametrizeReport; 2],
+    // Параметризации исходной и целевой сеток на единичной сфере (для отладки)
+    parametrized_meshes: [TriangleMesh; 2],
+}
+
+#[derive(Clone)]
 pub struct Morph {
     vertices: Vec<Point>,
     vertices_world: Vec<Point>,
-    triangles: Vec<Triangle>,
+    vertex_normals: Vec<Vector4<f64>>,
+    vertex_normals_world: Vec<Vector4<f64>>,
+    // Нормали граней - нормированные средние нормалей их вершин
     normals: Vec<Vector4<f64>>,
     normals_world: Vec<Vector4<f64>>,
     material: Material,
+    phase: f64,
 
-    vertex_interpolations: Vec<VertexInterpolation>,
-    normals_interpolations: Vec<NormalInterpolation>,
-    material_interpolation: MaterialInterpolation,
+    data: Arc<MorphData>,
 
+    transform: Transform,
     model_matrix: Matrix4<f64>,
 }
 
+// Интерполяции - замыкания, поэтому выводятся только их количества
+impl fmt::Debug for Morph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Morph")
+            .field("phase", &self.phase)
+            .field("vertices", &self.vertices.len())
+            .field("triangles", &self.data.triangles.len())
+            .field(
+                "vertex_interpolations",
+                &self.data.vertex_interpolations.len(),
+            )
+            .field(
+                "normals_interpolations",
+                &self.data.normals_interpolations.len(),
+            )
+            .field("relaxation_reports", &self.data.relaxation_reports)
+            .field("transform", &self.transform)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Morph {
     pub fn new(source_object: TriangleMesh, target_object: TriangleMesh) -> Result<Self, String> {
+        Self::with_params(source_object, target_object, &MorphParams::default())
+    }
+
+    /// Построить морфинг с заданными допусками алгоритма
+    pub fn with_params(
+        source_object: TriangleMesh,
+        target_object: TriangleMesh,
+        params: &MorphParams,
+    ) -> Result<Self, String> {
+        // 0. Параметризация через сферу возможна только для замкнутых многообразий одного рода
+        let source_genus = source_object
+            .validate_manifold()
+            .map_err(|e| format!("Исходный объект не является замкнутым многообразием: {}", e))?;
+        let target_genus = target_object
+            .validate_manifold()
+            .map_err(|e| format!("Целевой объект не является замкнутым многообразием: {}", e))?;
+        if source_genus != target_genus {
+            return Err(format!(
+                "Невозможно построить морфинг между объектами разного рода: \
+                 исходный объект имеет род {}, целевой - {}",
+                source_genus, target_genus
+            ));
+        }
+
         // 1. Параметризация исходных сеток
         let mut parametrized_source_mesh = source_object.clone();
-        parametrize_mesh(&mut parametrized_source_mesh);
+        let source_report = parametrize_mesh(&mut parametrized_source_mesh, params)?;
 
         let mut parametrized_target_mesh = target_object.clone();
-        parametrize_mesh(&mut parametrized_target_mesh);
+        let target_report = parametrize_mesh(&mut parametrized_target_mesh, params)?;
 
         // 2. Построение суперсетки
-        let (vertices, triangles) =
-            create_supermesh(&parametrized_source_mesh, &parametrized_target_mesh)?;
+        let (vertices, triangles, triangle_origins) =
+            create_supermesh(&parametrized_source_mesh, &parametrized_target_mesh, params)?;
 
         // 3. Находим положения точек на исходной и целевой сетках
-        let src_vertices = relocate_vertices_on_mesh(
+        let (src_vertices, src_locations) = relocate_vertices_on_mesh(
             &vertices,
             &parametrized_source_mesh,
             source_object.vertices_world(),
+            params,
         )?;
-        let dst_vertices = relocate_vertices_on_mesh(
+        let (dst_vertices, dst_locations) = relocate_vertices_on_mesh(
             &vertices,
             &parametrized_target_mesh,
             target_object.vertices_world(),
+            params,
         )?;
-
         let src_normals = find_normals(
-            &vertices,
-            &triangles,
-            &parametrized_source_mesh,
-            source_object.normals(),
-        )?;
+            &src_locations,
+            &source_object.corner_normals(params.smoothing_angle),
+        );
         let dst_normals = find_normals(
-            &vertices,
-            &triangles,
-            &parametrized_target_mesh,
-            target_object.normals(),
-        )?;
+            &dst_locations,
+            &target_object.corner_normals(params.smoothing_angle),
+        );
+        let correspondence = src_locations
+            .into_iter()
+            .zip(dst_locations)
+            .map(|((src_tri, src_bary), (dst_tri, dst_bary))| {
+                (src_tri, src_bary, dst_tri, dst_bary)
+            })
+            .collect();
+
+        let find_object_uvs =
+            |object: &TriangleMesh, parametrized: &TriangleMesh| match object.uvs() {
+                Some(uvs) => find_uvs(&vertices, &triangles, parametrized, uvs, params),
+                None => Ok(Vec::new()),
+            };
+        let src_uvs = find_object_uvs(&source_object, &parametrized_source_mesh)?;
+        let dst_uvs = find_object_uvs(&target_object, &parametrized_target_mesh)?;
 
         // 4. Строим интерполяции
         let vertex_interpolations: Vec<VertexInterpolation> = src_vertices
             .into_iter()
-            .zip(dst_vertices.into_iter())
+            .zip(dst_vertices)
             .map(|(src_v, dst_v)| -> VertexInterpolation {
-                Box::new(move |t: f64| Point::from((1. - t) * src_v.coords + t * dst_v.coords))
+                Arc::new(move |t: f64| Point::from((1. - t) * src_v.coords + t * dst_v.coords))
             })
             .collect();
 
         let normals_interpolations: Vec<NormalInterpolation> = src_normals
             .into_iter()
-            .zip(dst_normals.into_iter())
+            .zip(dst_normals)
             .map(|(src_n, dst_n)| -> NormalInterpolation {
-                Box::new(move |t: f64| lerp(src_n, dst_n, t))
+                Arc::new(move |t: f64| slerp_normal(src_n, dst_n, t))
             })
             .collect();
 
         let src_material = source_object.material().clone();
         let dst_material = target_object.material().clone();
-        let material_interpolation: MaterialInterpolation =
-            Box::new(move |t: f64| Material::lerp(&src_material, &dst_material, t));
-
-        // 5. Строим интерполяции при t=0
-        // 5.1 Строим вершины
-        let vertices: Vec<Point> = vertex_interpolations.iter().map(|lerp| lerp(0.)).collect();
-        let vertices_world = vertices.clone();
-
-        // 5.2 Строим нормали
-        let normals: Vec<Vector4<f64>> =
-            normals_interpolations.iter().map(|lerp| lerp(0.)).collect();
-        let normals_world = normals.clone();
-
-        // 5.3 Строим материал
-        let material = material_interpolation(0.);
+        let material_interpolation: MaterialInterpolation = {
+            let (src_material, dst_material) = (src_material.clone(), dst_material.clone());
+            Arc::new(move |t: f64| Material::lerp(&src_material, &dst_material, t))
+        };
 
-        Ok(Morph {
-            vertices,
-            vertices_world,
+        let data = MorphData {
             triangles,
-            normals,
-            normals_world,
-            material,
             vertex_interpolations,
             normals_interpolations,
             material_interpolation,
-            model_matrix: Matrix4::identity(),
-        })
+            src_material,
+            dst_material,
+            src_uvs,
+            dst_uvs,
+            correspondence,
+            triangle_origins,
+            relaxation_reports: [source_report, target_report],
+            parametrized_meshes: [
+                sphere_view(&parametrized_source_mesh, source_object.material()),
+                sphere_view(&parametrized_target_mesh, target_object.material()),
+            ],
+        };
+
+        // 5. Строим интерполяции при t=0
+        Ok(Self::from_data(Arc::new(data), 0., Transform::default()))
+    }
+
+    /// Морфинг на стадии `phase` с преобразованием `transform`
+    fn from_data(data: Arc<MorphData>, phase: f64, transform: Transform) -> Self {
+        let mut morph = Morph {
+            vertices: vec![Point::origin(); data.vertex_interpolations.len()],
+            vertices_world: vec![Point::origin(); data.vertex_interpolations.len()],
+            vertex_normals: vec![Vector4::zeros(); data.normals_interpolations.len()],
+            vertex_normals_world: vec![Vector4::zeros(); data.normals_interpolations.len()],
+            normals: vec![Vector4::zeros(); data.triangles.len()],
+            normals_world: vec![Vector4::zeros(); data.triangles.len()],
+            material: (data.material_interpolation)(phase),
+            phase,
+            model_matrix: transform.matrix(),
+            transform,
+            data,
+        };
+        morph.update(phase);
+        morph
+    }
+
+    /// Для каждой вершины суперсетки - треугольник и барицентрические координаты
+    /// на параметризованных исходной и целевой сетках
+    pub fn export_correspondence(&self) -> Vec<VertexCorrespondence> {
+        self.data.correspondence.clone()
+    }
+
+    /// Параметризация исходной сетки на единичной сфере
+    pub fn parametrized_source(&self) -> &TriangleMesh {
+        &self.data.parametrized_meshes[0]
+    }
+
+    /// Параметризация целевой сетки на единичной сфере
+    pub fn parametrized_target(&self) -> &TriangleMesh {
+        &self.data.parametrized_meshes[1]
+    }
+
+    /// Итоги релаксации исходной и целевой сеток на сфере
+    pub fn relaxation_reports(&self) -> &[ParametrizeReport; 2] {
+        &self.data.relaxation_reports
+    }
+
+    /// Площадь и объём на `samples` равномерно распределённых стадиях от 0 до 1.
+    /// Провал объёма к нулю на промежуточной стадии указывает на плохое
+    /// соответствие вершин: объект схлопывается в тонкую пластину
+    pub fn sample_metrics(&self, samples: usize) -> Vec<PhaseMetrics> {
+        let mut probe = self.clone();
+        (0..samples)
+            .map(|i| {
+                let phase = if samples > 1 {
+                    i as f64 / (samples - 1) as f64
+                } else {
+                    self.phase
+                };
+                probe.update(phase);
+                PhaseMetrics {
+                    phase,
+                    surface_area: probe.surface_area(),
+                    volume: probe.volume(),
+                }
+            })
+            .collect()
     }
 }
 
+// Параметризованная сетка для просмотра: нормали пересчитываются по сфере
+fn sphere_view(parametrized: &TriangleMesh, material: &Material) -> TriangleMesh {
+    let mut mesh = TriangleMesh::from_parts(
+        parametrized.vertices().clone(),
+        parametrized.triangles().clone(),
+        None,
+    );
+    mesh.material = material.clone();
+    mesh
+}
+
 impl Morph {
     fn update_vertices_world(&mut self) {
-        for (vw, v) in self.vertices_world.iter_mut().zip(self.vertices.iter()) {
-            *vw = Point3::from_homogeneous(self.model_matrix * v.to_homogeneous()).unwrap();
-        }
+        transform_points(&self.model_matrix, &self.vertices, &mut self.vertices_world);
     }
 
     fn update_normals_world(&mut self) {
-        for (nw, n) in self.normals_world.iter_mut().zip(self.normals.iter()) {
-            *nw = self.model_matrix * n;
-            nw.normalize_mut();
-        }
+        let normal_matrix = normal_matrix(&self.model_matrix);
+        transform_into(&mut self.normals_world, &self.normals, |n| {
+            (normal_matrix * n).normalize()
+        });
+        transform_into(&mut self.vertex_normals_world, &self.vertex_normals, |n| {
+            (normal_matrix * n).normalize()
+        });
     }
 }
 
 impl Model3D for Morph {
     fn triangles(&self) -> &Vec<Triangle> {
-        &self.triangles
+        &self.data.triangles
     }
 
+    // Модель отдаёт нормали в мировых координатах, как и вершины
+    #[allow(clippy::misnamed_getters)]
     fn normals(&self) -> &Vec<Vector4<f64>> {
         &self.normals_world
     }
@@ -155,6 +337,10 @@ impl Model3D for Morph {
         !self.normals.is_empty()
     }
 
+    fn vertex_normals(&self) -> Option<&[Vector4<f64>]> {
+        Some(&self.vertex_normals_world)
+    }
+
     fn compute_normals(&mut self) {
         todo!()
     }
@@ -163,33 +349,72 @@ impl Model3D for Morph {
         &self.model_matrix
     }
 
+    fn has_texture(&self) -> bool {
+        (self.data.src_material.texture.is_some() && !self.data.src_uvs.is_empty())
+            || (self.data.dst_material.texture.is_some() && !self.data.dst_uvs.is_empty())
+    }
+
+    /// Смешивает текстуры исходного и целевого объектов в соответствии со стадией морфинга.
+    /// Объект без текстуры участвует в смешивании своим базовым цветом.
+    fn sample_texture(&self, triangle: usize, bary: &Vector3<f64>) -> Option<Rgb<u8>> {
+        if !self.has_texture() {
+            return None;
+        }
+
+        let sample = |material: &Material, uvs: &[[Vector2<f64>; 3]]| {
+            uvs.get(triangle)
+                .and_then(|[uv0, uv1, uv2]| {
+                    material.sample_texture(&(uv0 * bary.x + uv1 * bary.y + uv2 * bary.z))
+                })
+                .unwrap_or(material.color)
+        };
+        let src = sample(&self.data.src_material, &self.data.src_uvs);
+        let dst = sample(&self.data.dst_material, &self.data.dst_uvs);
+
+        Some(Rgb([0, 1, 2].map(|c| {
+            lerp(src.0[c] as f64, dst.0[c] as f64, self.phase).round() as u8
+        })))
+    }
+
+    fn triangle_origins(&self) -> Option<&[SegmentOrigin]> {
+        Some(&self.data.triangle_origins)
+    }
+
     fn update(&mut self, t: f64) {
+        self.phase = t;
+
         // Рассчитать вершины
         for i in 0..self.vertices.len() {
-            self.vertices[i] = self.vertex_interpolations[i](t);
+            self.vertices[i] = self.data.vertex_interpolations[i](t);
         }
 
-        // Рассчитать нормали
-        for i in 0..self.normals.len() {
-            self.normals[i] = self.normals_interpolations[i](t);
+        // Рассчитать нормали вершин, а по ним - нормали граней
+        for i in 0..self.vertex_normals.len() {
+            self.vertex_normals[i] = self.data.normals_interpolations[i](t);
         }
+        for (normal, tri) in self.normals.iter_mut().zip(&self.data.triangles) {
+            let [n0, n1, n2] = [tri.0, tri.1, tri.2].map(|v| self.vertex_normals[v]);
+            *normal = (n0 + n1 + n2).try_normalize(f64::EPSILON).unwrap_or(n0);
+        }
+        debug_assert!(
+            self.vertices.iter().all(|v| all_finite(v.iter())),
+            "Morph::update({}): NaN в вершинах",
+            t
+        );
 
         self.update_vertices_world();
         self.update_normals_world();
 
         // Рассчитать материал
-        self.material = (self.material_interpolation)(t);
+        self.material = (self.data.material_interpolation)(t);
     }
 }
 
 impl Rotate for Morph {
     fn rotate(&mut self, axis_angle_radians: (f64, f64, f64)) {
-        let rotation_matrix = Matrix4::new_rotation(Vector3::new(
-            axis_angle_radians.0,
-            axis_angle_radians.1,
-            axis_angle_radians.2,
-        ));
-        self.model_matrix *= rotation_matrix;
+        let (x, y, z) = axis_angle_radians;
+        self.transform.rotate(&Vector3::new(x, y, z));
+        self.model_matrix = self.transform.matrix();
 
         self.update_vertices_world();
         self.update_normals_world();
@@ -198,7 +423,16 @@ impl Rotate for Morph {
 
 impl Scale for Morph {
     fn scale(&mut self, scaling: f64) {
-        self.model_matrix *= Matrix4::new_scaling(scaling);
+        self.transform.scale(scaling);
+        self.model_matrix = self.transform.matrix();
+        self.update_vertices_world();
+        self.update_normals_world();
+    }
+
+    fn scale_xyz(&mut self, scaling: (f64, f64, f64)) {
+        let (x, y, z) = scaling;
+        self.transform.scale_xyz(&Vector3::new(x, y, z));
+        self.model_matrix = self.transform.matrix();
         self.update_vertices_world();
         self.update_normals_world();
     }
@@ -206,8 +440,272 @@ impl Scale for Morph {
 
 impl InteractiveModel for Morph {
     fn reset_transformations(&mut self) {
+        self.transform = Transform::default();
         self.model_matrix = Matrix4::identity();
         self.update_vertices_world();
         self.update_normals_world();
     }
+
+    fn set_model_matrix(&mut self, matrix: &Matrix4<f64>) {
+        self.transform = Transform::from_matrix(matrix);
+        self.model_matrix = self.transform.matrix();
+        self.update_vertices_world();
+        self.update_normals_world();
+    }
+
+    /// Копия разделяет результаты построения, а вершины для стадии морфинга
+    /// вычисляются уже в потоке, который её восстанавливает
+    fn snapshot(&self) -> ModelSnapshot {
+        let data = Arc::clone(&self.data);
+        let (phase, transform) = (self.phase, self.transform.clone());
+        Box::new(move || Rc::new(RefCell::new(Morph::from_data(data, phase, transform))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn textured_model(name: &str, color: Rgb<u8>) -> TriangleMesh {
+        let path = format!("{}/models/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let mut mesh = TriangleMesh::from_obj(&path).unwrap();
+        mesh.material.texture = Some(Arc::new(RgbImage::from_pixel(1, 1, color)));
+        mesh
+    }
+
+    #[test]
+    fn different_genus_is_rejected() {
+        let result = Morph::new(
+            TriangleMesh::torus(1., 0.4, 16),
+            TriangleMesh::unit_sphere(2),
+        );
+        let Err(message) = result else {
+            panic!("Морфинг тора в сферу должен завершаться ошибкой");
+        };
+        assert!(message.contains("род"), "{}", message);
+    }
+
+    #[test]
+    fn non_manifold_object_is_rejected() {
+        let sphere = TriangleMesh::unit_sphere(2);
+        let mut triangles = sphere.triangles().clone();
+        triangles.pop();
+        let open_sphere = TriangleMesh::from_parts(sphere.vertices().clone(), triangles, None);
+
+        let Err(message) = Morph::new(TriangleMesh::unit_sphere(2), open_sphere) else {
+            panic!("Морфинг в незамкнутую сетку должен завершаться ошибкой");
+        };
+        assert!(message.contains("Целевой объект"), "{}", message);
+    }
+
+    #[test]
+    fn parametrized_meshes_lie_on_unit_sphere() {
+        let path = |name: &str| format!("{}/models/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let source = TriangleMesh::from_obj(&path("apple2.obj")).unwrap();
+        let target = TriangleMesh::from_obj(&path("lemon.obj")).unwrap();
+        let morph = Morph::new(source, target).unwrap();
+
+        // Параметризация показывается как есть, без перепроецирования: центрирование
+        // в последнем раунде релаксации сдвигает вершины с единичной сферы на доли процента
+        for mesh in [morph.parametrized_source(), morph.parametrized_target()] {
+            assert!(!mesh.vertices().is_empty());
+            for v in mesh.vertices() {
+                assert!(
+                    (v.coords.norm() - 1.).abs() < 1e-3,
+                    "|v| = {}",
+                    v.coords.norm()
+                );
+            }
+        }
+    }
+
+    /// Нормали в углах треугольника, которыми его освещает растеризатор
+    fn shading_normals(model: &dyn Model3D, face: usize) -> [Vector4<f64>; 3] {
+        let tri = model.triangles()[face];
+        [tri.0, tri.1, tri.2].map(|v| {
+            model
+                .vertex_normals()
+                .map_or(model.normals()[face], |normals| normals[v])
+        })
+    }
+
+    /// Пары треугольников с общим ребром и общие вершины этого ребра
+    fn shared_edges(triangles: &[Triangle]) -> Vec<([usize; 2], [usize; 2])> {
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (i, tri) in triangles.iter().enumerate() {
+            for (a, b) in [(tri.0, tri.1), (tri.1, tri.2), (tri.2, tri.0)] {
+                edge_faces.entry((a.min(b), a.max(b))).or_default().push(i);
+            }
+        }
+        edge_faces
+            .into_iter()
+            .filter(|(_, faces)| faces.len() == 2)
+            .map(|((a, b), faces)| ([faces[0], faces[1]], [a, b]))
+            .collect()
+    }
+
+    /// Нормаль треугольника `face` в его вершине `vertex`
+    fn normal_at(model: &dyn Model3D, face: usize, vertex: usize) -> Vector4<f64> {
+        let tri = model.triangles()[face];
+        let corner = [tri.0, tri.1, tri.2]
+            .iter()
+            .position(|&v| v == vertex)
+            .unwrap();
+        shading_normals(model, face)[corner]
+    }
+
+    #[test]
+    fn smooth_sphere_morph_has_continuous_normals() {
+        // Все грани подразбитого икосаэдра вокруг вершины укладываются в угол
+        // сглаживания, а его плоские грани отличаются на общем ребре на ~20°
+        let source = TriangleMesh::unit_sphere(1);
+        let faceted = shared_edges(source.triangles())
+            .into_iter()
+            .any(|([f, g], [a, _])| normal_at(&source, f, a) != normal_at(&source, g, a));
+        assert!(faceted);
+
+        let morph = Morph::new(source, TriangleMesh::unit_sphere(3)).unwrap();
+
+        // Треугольники с общим ребром освещаются в общих вершинах одной нормалью
+        for ([f, g], edge) in shared_edges(morph.triangles()) {
+            for v in edge {
+                assert_eq!(normal_at(&morph, f, v), normal_at(&morph, g, v));
+            }
+        }
+
+        // ...и внутри треугольника нормаль меняется: по углам она близка к сфере,
+        // а не к плоской грани икосаэдра
+        let vertices = morph.vertices_world();
+        let max_deviation = morph
+            .vertex_normals()
+            .unwrap()
+            .iter()
+            .zip(vertices)
+            .map(|(n, v)| n.xyz().angle(&v.coords))
+            .fold(0., f64::max);
+        assert!(
+            max_deviation < 1f64.to_radians(),
+            "отклонение нормали от сферы {:.1}°",
+            max_deviation.to_degrees()
+        );
+    }
+
+    #[test]
+    fn supermesh_has_at_least_as_many_triangles_as_inputs() {
+        let source = TriangleMesh::unit_sphere(1);
+        let target = TriangleMesh::cube();
+        let input_count = source.triangles().len().max(target.triangles().len());
+        let morph = Morph::new(source, target).unwrap();
+
+        assert!(
+            morph.triangles().len() >= input_count,
+            "{} < {}",
+            morph.triangles().len(),
+            input_count
+        );
+    }
+
+    #[test]
+    fn growing_sphere_keeps_volume_positive_and_increasing() {
+        let source = TriangleMesh::unit_sphere(1);
+        let mut target = TriangleMesh::unit_sphere(1);
+        target.scale(2.);
+        let morph = Morph::new(source, target).unwrap();
+
+        let metrics = morph.sample_metrics(11);
+        assert_eq!(metrics.len(), 11);
+        assert!(metrics.iter().all(|m| m.volume > 0. && m.surface_area > 0.));
+        assert!(metrics.windows(2).all(|w| w[1].volume > w[0].volume));
+
+        // Объём растёт как куб масштаба
+        let ratio = metrics[10].volume / metrics[0].volume;
+        assert!((ratio - 8.).abs() < 0.1, "отношение объёмов {ratio}");
+    }
+
+    #[test]
+    fn identity_morph_maps_vertices_to_themselves() {
+        let path = format!("{}/models/apple2.obj", env!("CARGO_MANIFEST_DIR"));
+        let mesh = TriangleMesh::from_obj(&path).unwrap();
+        let morph = Morph::new(mesh.clone(), mesh).unwrap();
+
+        let correspondence = morph.export_correspondence();
+        assert_eq!(correspondence.len(), morph.vertices().len());
+        for (src_tri, src_bary, dst_tri, dst_bary) in correspondence {
+            assert_eq!(src_tri, dst_tri);
+            assert!((src_bary - dst_bary).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn texture_blends_with_phase() {
+        let (red, blue) = (Rgb([255, 0, 0]), Rgb([0, 0, 255]));
+        let source = textured_model("apple2.obj", red);
+        let target = textured_model("lemon.obj", blue);
+        assert!(source.uvs().is_some() && target.uvs().is_some());
+
+        let mut morph = Morph::new(source, target).unwrap();
+        let bary = Vector3::repeat(1. / 3.);
+        assert!(morph.has_texture());
+
+        morph.update(0.);
+        assert_eq!(morph.sample_texture(0, &bary), Some(red));
+
+        morph.update(1.);
+        assert_eq!(morph.sample_texture(0, &bary), Some(blue));
+
+        morph.update(0.5);
+        let mid = morph.sample_texture(0, &bary).unwrap();
+        assert!(mid.0[0] > 0 && mid.0[2] > 0);
+    }
+
+    #[test]
+    fn snapshot_shares_supermesh_and_restores_phase() {
+        let mut morph = Morph::new(TriangleMesh::unit_sphere(1), TriangleMesh::cube()).unwrap();
+        morph.update(0.3);
+        morph.rotate((0.2, 0.5, 0.));
+
+        let copy = morph.snapshot()();
+        let copy = copy.borrow();
+        assert_eq!(copy.triangles().as_ptr(), morph.triangles().as_ptr());
+        assert_eq!(copy.vertices_world(), morph.vertices_world());
+        assert_eq!(copy.normals(), morph.normals());
+        assert_eq!(copy.material().color, morph.material().color);
+    }
+
+    #[test]
+    fn supermesh_topology_does_not_depend_on_model_scale() {
+        // Неровные сферы: у симметричных сеток много точек на общей окружности, и
+        // триангуляция таких граней зависит от ошибок округления, а не от допусков
+        let bumpy = |subdivisions: usize, frequency: f64| {
+            let sphere = TriangleMesh::unit_sphere(subdivisions);
+            let vertices = sphere
+                .vertices()
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v * (1. + 0.1 * (frequency * i as f64).sin()))
+                .collect();
+            TriangleMesh::from_parts(vertices, sphere.triangles().to_vec(), None)
+        };
+        let build = |scale: f64| {
+            let mut source = bumpy(1, 7.);
+            let mut target = bumpy(2, 3.);
+            source.scale_geometry(scale);
+            target.scale_geometry(scale);
+            Morph::new(source, target).unwrap()
+        };
+
+        let reference = build(1.);
+        for scale in [1e3, 1e-3] {
+            let scaled = build(scale);
+            assert_eq!(
+                scaled.vertices().len(),
+                reference.vertices().len(),
+                "×{scale}"
+            );
+            assert_eq!(scaled.triangles(), reference.triangles(), "×{scale}");
+        }
+    }
 }
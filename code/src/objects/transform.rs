@@ -0,0 +1,114 @@
+use nalgebra::{Matrix3, Matrix4, Rotation3, UnitQuaternion, Vector3};
+
+/// Преобразование модели: масштабирование по осям объекта, затем поворот, затем перенос.
+///
+/// Ориентация накапливается кватернионом и перенормируется после каждого поворота,
+/// поэтому при долгом вращении мышью матрица не перекашивается.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub orientation: UnitQuaternion<f64>,
+    pub scaling: Vector3<f64>,
+    pub translation: Vector3<f64>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            orientation: UnitQuaternion::identity(),
+            scaling: Vector3::repeat(1.),
+            translation: Vector3::zeros(),
+        }
+    }
+}
+
+impl Transform {
+    /// Поворот вокруг осей мировой системы координат (экрана), как у трекбола.
+    /// Направление вектора задаёт ось, длина — угол в радианах.
+    pub fn rotate(&mut self, axis_angle: &Vector3<f64>) {
+        self.orientation = UnitQuaternion::from_scaled_axis(*axis_angle) * self.orientation;
+        self.orientation.renormalize();
+    }
+
+    /// Равномерное масштабирование
+    pub fn scale(&mut self, scaling: f64) {
+        self.scale_xyz(&Vector3::repeat(scaling));
+    }
+
+    /// Масштабирование по осям объекта. Пределы масштаба здесь не проверяются:
+    /// их соблюдают обработчики пользовательского ввода
+    pub fn scale_xyz(&mut self, scaling: &Vector3<f64>) {
+        self.scaling = self.scaling.component_mul(scaling);
+    }
+
+    pub fn translate(&mut self, translation: &Vector3<f64>) {
+        self.translation += translation;
+    }
+
+    /// Преобразование по матрице вида перенос * поворот * масштаб (такой, как у matrix()).
+    /// Масштаб по оси - длина соответствующего столбца, поворот - столбцы, делённые на масштаб
+    pub fn from_matrix(matrix: &Matrix4<f64>) -> Self {
+        let linear: Matrix3<f64> = matrix.fixed_view::<3, 3>(0, 0).into_owned();
+        let scaling = Vector3::from_fn(|i, _| linear.column(i).norm());
+        // Вырожденный (нулевой) столбец не задаёт направления, берём ось без поворота
+        let rotation = Matrix3::from_fn(|i, j| match scaling[j] > 0. {
+            true => linear[(i, j)] / scaling[j],
+            false => Matrix3::<f64>::identity()[(i, j)],
+        });
+
+        Self {
+            orientation: UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix(&rotation)),
+            scaling,
+            translation: matrix.fixed_view::<3, 1>(0, 3).into_owned(),
+        }
+    }
+
+    /// Матрица модели
+    pub fn matrix(&self) -> Matrix4<f64> {
+        Matrix4::new_translation(&self.translation)
+            * self.orientation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&self.scaling)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn matrix_decomposition_restores_transform() {
+        let mut transform = Transform::default();
+        transform.rotate(&Vector3::new(0.4, -1.1, 0.7));
+        transform.scale_xyz(&Vector3::new(2., 0.5, 3.));
+        transform.translate(&Vector3::new(1., -2., 0.25));
+
+        let restored = Transform::from_matrix(&transform.matrix());
+        assert!(restored.orientation.angle_to(&transform.orientation) < 1e-9);
+        assert!((restored.scaling - transform.scaling).norm() < 1e-12);
+        assert!((restored.translation - transform.translation).norm() < 1e-12);
+    }
+
+    #[test]
+    fn extreme_scaling_is_kept_as_is() {
+        let mut transform = Transform::default();
+        transform.scale_xyz(&Vector3::new(1e-6, 1e6, 1.));
+        assert_eq!(transform.scaling, Vector3::new(1e-6, 1e6, 1.));
+
+        let restored = Transform::from_matrix(&transform.matrix());
+        assert!((restored.scaling - transform.scaling).norm() < 1e-6);
+    }
+
+    #[test]
+    fn opposite_rotations_restore_orientation() {
+        let mut transform = Transform::default();
+        transform.rotate(&Vector3::new(0.3, 0., 0.));
+
+        let original = transform.clone();
+        let axis = Vector3::new(1., 2., -0.5).normalize();
+        transform.rotate(&(axis * FRAC_PI_2));
+        transform.rotate(&(axis * -FRAC_PI_2));
+
+        assert!(transform.orientation.angle_to(&original.orientation) < 1e-12);
+        assert!((transform.matrix() - original.matrix()).norm() < 1e-12);
+    }
+}
@@ -1,60 +1,108 @@
 use crate::objects::Point;
-use crate::objects::model3d::{InteractiveModel, Material, Model3D, Rotate, Scale, Triangle};
+use crate::objects::model3d::Translate;
+use crate::objects::model3d::{
+    InteractiveModel, Material, Model3D, ModelSnapshot, Rotate, Scale, Triangle,
+};
+use crate::objects::transform::Transform;
 use crate::utils::dcel::DCEL;
+use crate::utils::decimation::decimate_mesh;
+use crate::utils::math::{normal_matrix, transform_into, transform_points};
 use crate::utils::morphing::triangulate_dcel;
-use crate::utils::triangles::triangle_area;
+use crate::utils::occlusion::vertex_ambient_occlusion;
+use crate::utils::triangles::{edge_key, non_manifold_edges, oriented_face_normal, triangle_area};
 use image::Rgb;
-use nalgebra::{Matrix4, Point3, Vector3, Vector4};
+use nalgebra::{Matrix4, Vector2, Vector3, Vector4};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::error::Error;
+use std::f64::consts::TAU;
+use std::fmt;
 use std::fs;
 use std::io::{BufRead, BufReader};
-use crate::objects::model3d::Translate;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Индексы вершины, текстурных координат и нормали одного угла грани .obj
+type FaceCorner = (usize, Option<usize>, Option<usize>);
+
+/// Количество элементов каждого вида в .obj файле
+#[derive(Default)]
+struct ObjCounts {
+    vertices: usize,
+    normals: usize,
+    uvs: usize,
+    faces: usize,
+}
 
+/// Сетка треугольников. Геометрия в координатах объекта хранится за `Arc`
+/// и копируется только при изменении, поэтому копии сетки для потока отрисовки
+/// разделяют её с исходной сеткой.
 #[derive(Clone)]
 pub struct TriangleMesh {
-    pub vertices: Vec<Point>,
+    pub vertices: Arc<Vec<Point>>,
     pub vertices_world: Vec<Point>, // Вершины умноженные на матрицу преобразования
-    normals: Vec<Vector4<f64>>,
+    normals: Arc<Vec<Vector4<f64>>>,
     normals_world: Vec<Vector4<f64>>, // Нормали умноженные на матрицу преобразования
-    triangles: Vec<Triangle>,
+    triangles: Arc<Vec<Triangle>>,
+    uvs: Arc<Vec<[Vector2<f64>; 3]>>, // Текстурные координаты углов треугольников (пусто, если нет)
     pub material: Material,
 
-    pub model_matrix: Matrix4<f64>,
+    transform: Transform,
+    model_matrix: Matrix4<f64>,       // Производная от transform
+    ambient_occlusion: Arc<Vec<f64>>, // Вычисляется при загрузке и изменении сетки
 }
 
 impl Default for TriangleMesh {
     fn default() -> Self {
         Self {
-            vertices: Vec::default(),
+            vertices: Arc::default(),
             vertices_world: Vec::default(),
-            normals: Vec::default(),
+            normals: Arc::default(),
             normals_world: Vec::default(),
-            triangles: Vec::default(),
+            triangles: Arc::default(),
+            uvs: Arc::default(),
             material: Material::default(),
+            transform: Transform::default(),
             model_matrix: Matrix4::identity(),
+            ambient_occlusion: Arc::default(),
         }
     }
 }
 
+// Вместо координат выводятся размеры буферов, чтобы сообщения тестов оставались читаемыми
+impl fmt::Debug for TriangleMesh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TriangleMesh")
+            .field("vertices", &self.vertices.len())
+            .field("triangles", &self.triangles.len())
+            .field("normals", &self.normals.len())
+            .field("uvs", &self.uvs.len())
+            .field("transform", &self.transform)
+            .finish_non_exhaustive()
+    }
+}
+
 impl TriangleMesh {
     pub fn update_normals_world(&mut self) {
-        for i in 0..self.normals.len() {
-            self.normals_world[i] = (self.model_matrix * self.normals[i]).normalize();
-        }
+        // При неравномерном масштабе нормали преобразуются обратной транспонированной
+        let normal_matrix = normal_matrix(&self.model_matrix);
+        transform_into(&mut self.normals_world, &self.normals, |n| {
+            (normal_matrix * n).normalize()
+        });
     }
 
     pub fn update_vertices_world(&mut self) {
-        for i in 0..self.vertices.len() {
-            if let Some(point) =
-                Point3::from_homogeneous(self.model_matrix * self.vertices[i].to_homogeneous())
-            {
-                self.vertices_world[i] = point;
-            }
-        }
+        transform_points(&self.model_matrix, &self.vertices, &mut self.vertices_world);
     }
 
     pub fn reset_transformations(&mut self) {
-        self.model_matrix = Matrix4::identity();
+        self.transform = Transform::default();
+        self.apply_transform();
+    }
+
+    /// Пересчитывает матрицу модели и мировые буферы после изменения преобразования
+    fn apply_transform(&mut self) {
+        self.model_matrix = self.transform.matrix();
         self.update_vertices_world();
         self.update_normals_world();
     }
@@ -65,6 +113,8 @@ impl Model3D for TriangleMesh {
         &self.triangles
     }
 
+    // Модель отдаёт нормали в мировых координатах, как и вершины
+    #[allow(clippy::misnamed_getters)]
     fn normals(&self) -> &Vec<Vector4<f64>> {
         &self.normals_world
     }
@@ -85,39 +135,64 @@ impl Model3D for TriangleMesh {
         !self.normals.is_empty()
     }
 
+    /// Нормали граней по порядку обхода вершин (против часовой стрелки - наружу)
     fn compute_normals(&mut self) {
-        todo!()
+        let normals = self
+            .triangles
+            .iter()
+            .map(|tri| {
+                let [a, b, c] = [tri.0, tri.1, tri.2].map(|v| self.vertices[v]);
+                (b - a)
+                    .cross(&(c - a))
+                    .try_normalize(f64::EPSILON)
+                    .unwrap_or_else(Vector3::zeros)
+                    .to_homogeneous()
+            })
+            .collect();
+        self.normals = Arc::new(normals);
+        self.normals_world = self.normals.to_vec();
+        self.update_normals_world();
     }
 
     fn model_matrix(&self) -> &Matrix4<f64> {
         &self.model_matrix
     }
+
+    fn uvs(&self) -> Option<&[[Vector2<f64>; 3]]> {
+        (!self.uvs.is_empty()).then_some(self.uvs.as_slice())
+    }
+
+    fn ambient_occlusion(&self) -> Option<&[f64]> {
+        (!self.ambient_occlusion.is_empty()).then_some(self.ambient_occlusion.as_slice())
+    }
 }
 
 impl Rotate for TriangleMesh {
     fn rotate(&mut self, axis_angle_radians: (f64, f64, f64)) {
-        let rotation_matrix = Matrix4::new_rotation(Vector3::new(
-            axis_angle_radians.0,
-            axis_angle_radians.1,
-            axis_angle_radians.2,
-        ));
-        self.model_matrix *= rotation_matrix;
-
-        self.update_normals_world();
-        self.update_vertices_world();
+        let (x, y, z) = axis_angle_radians;
+        self.transform.rotate(&Vector3::new(x, y, z));
+        self.apply_transform();
     }
 }
 
 impl Scale for TriangleMesh {
     fn scale(&mut self, scaling: f64) {
-        self.model_matrix *= Matrix4::new_scaling(scaling);
+        self.transform.scale(scaling);
+        self.model_matrix = self.transform.matrix();
         self.update_vertices_world()
     }
+
+    fn scale_xyz(&mut self, scaling: (f64, f64, f64)) {
+        let (x, y, z) = scaling;
+        self.transform.scale_xyz(&Vector3::new(x, y, z));
+        self.apply_transform();
+    }
 }
 
 impl Translate for TriangleMesh {
     fn translate(&mut self, translation: &Vector3<f64>) {
-        self.model_matrix = Matrix4::new_translation(translation) * self.model_matrix;
+        self.transform.translate(translation);
+        self.model_matrix = self.transform.matrix();
         self.update_vertices_world();
     }
 }
@@ -128,7 +203,7 @@ impl TriangleMesh {
         let mut total_area = 0.0;
         let mut weighted_center = Vector3::zeros();
 
-        for tri in &self.triangles {
+        for tri in self.triangles.iter() {
             let v1 = &self.vertices[tri.0];
             let v2 = &self.vertices[tri.1];
             let v3 = &self.vertices[tri.2];
@@ -150,23 +225,24 @@ impl TriangleMesh {
     /// Совместить центр масс фигуры с началом координат
     fn center(&mut self) {
         let center = self.center_of_mass();
-        for v in &mut self.vertices {
+        for v in Arc::make_mut(&mut self.vertices) {
             *v -= center;
         }
     }
 
     /// Helper function for parsing faces
     /// Parses a single component of a face line (`v`, `v/vt`, `v//vn`, `v/vt/vn`)
-    /// and validates the vertex and normal indices.
+    /// and validates the vertex, texture and normal indices.
     ///
-    /// This function ignores the texture index (`vt`) and returns the normal index as an `Option<usize>`,
-    /// which will be `None` if a normal index is not present in the face component.
+    /// Returns the texture and normal indices as `Option<usize>`,
+    /// which will be `None` if the index is not present in the face component.
     fn parse_and_validate_face_part(
         part: &str,
         total_vertices: usize,
+        total_uvs: usize,
         total_normals: usize,
         line_number: usize,
-    ) -> Result<(usize, Option<usize>), Box<dyn Error>> {
+    ) -> Result<FaceCorner, Box<dyn Error>> {
         let indices: Vec<&str> = part.split('/').collect();
 
         if indices.is_empty() {
@@ -181,6 +257,23 @@ impl TriangleMesh {
             );
         }
 
+        // The texture index is the second part if it exists and is not empty.
+        let t_idx = match indices.get(1) {
+            Some(t_str) if !t_str.is_empty() => {
+                let t_idx_parsed = t_str.parse::<usize>()? - 1;
+                if t_idx_parsed >= total_uvs {
+                    return Err(format!(
+                        "Invalid texture index {} on line {}",
+                        t_idx_parsed + 1,
+                        line_number
+                    )
+                    .into());
+                }
+                Some(t_idx_parsed)
+            }
+            _ => None,
+        };
+
         // The normal index is the third part if it exists and is not empty.
         let n_idx = match indices.get(2) {
             Some(n_str) if !n_str.is_empty() => {
@@ -198,16 +291,94 @@ impl TriangleMesh {
             _ => None,
         };
 
-        Ok((v_idx, n_idx))
+        Ok((v_idx, t_idx, n_idx))
+    }
+
+    /// Parses the first `N` numbers after the keyword of a `v`/`vn`/`vt` line.
+    /// Errors mention the line number and the line itself.
+    fn parse_coordinates<const N: usize>(
+        parts: &[&str],
+        line_number: usize,
+        line: &str,
+    ) -> Result<[f64; N], Box<dyn Error>> {
+        if parts.len() <= N {
+            return Err(format!(
+                "Expected {} numbers on line {}: \"{}\"",
+                N,
+                line_number,
+                line.trim()
+            )
+            .into());
+        }
+
+        let mut coordinates = [0.; N];
+        for (coordinate, part) in coordinates.iter_mut().zip(&parts[1..]) {
+            *coordinate = part.parse::<f64>().map_err(|e| {
+                format!(
+                    "Invalid number \"{}\" on line {}: \"{}\" ({})",
+                    part,
+                    line_number,
+                    line.trim(),
+                    e
+                )
+            })?;
+        }
+        Ok(coordinates)
+    }
+
+    /// Counts `v`, `vn`, `vt` and `f` lines without parsing them, so that the
+    /// buffers can be allocated once before the real pass.
+    fn count_obj_elements(path: &str) -> Result<ObjCounts, Box<dyn Error>> {
+        let mut reader = BufReader::new(fs::File::open(path)?);
+        let mut counts = ObjCounts::default();
+        let mut line = Vec::new();
+
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            let keyword = line
+                .trim_ascii_start()
+                .split(|b| b.is_ascii_whitespace())
+                .next()
+                .unwrap_or_default();
+            match keyword {
+                b"v" => counts.vertices += 1,
+                b"vn" => counts.normals += 1,
+                b"vt" => counts.uvs += 1,
+                b"f" => counts.faces += 1,
+                _ => {}
+            }
+            line.clear();
+        }
+
+        Ok(counts)
     }
 
     /// Read from .obj file
     pub fn from_obj(path: &str) -> Result<Self, Box<dyn Error>> {
+        Self::load_obj(path, true)
+    }
+
+    /// Reads an .obj file. With `reserve_capacity` the file is scanned twice:
+    /// the first pass only counts elements, which avoids reallocating the buffers
+    /// of large meshes while they grow.
+    fn load_obj(path: &str, reserve_capacity: bool) -> Result<Self, Box<dyn Error>> {
+        let counts = if reserve_capacity {
+            Self::count_obj_elements(path)?
+        } else {
+            ObjCounts::default()
+        };
+
         let file = fs::File::open(path)?;
         let reader = BufReader::new(file);
 
-        let mut mesh = TriangleMesh::default();
-        let mut temp_normals: Vec<Vector4<f64>> = Vec::new();
+        let mut vertices: Vec<Point> = Vec::with_capacity(counts.vertices);
+        let mut triangles: Vec<Triangle> = Vec::with_capacity(counts.faces);
+        let face_normals = if counts.normals > 0 { counts.faces } else { 0 };
+        let mut normals: Vec<Vector4<f64>> = Vec::with_capacity(face_normals);
+        let face_uvs = if counts.uvs > 0 { counts.faces } else { 0 };
+        let mut uvs: Vec<[Vector2<f64>; 3]> = Vec::with_capacity(face_uvs);
+        let mut temp_normals: Vec<Vector4<f64>> = Vec::with_capacity(counts.normals);
+        let mut temp_uvs: Vec<Vector2<f64>> = Vec::with_capacity(counts.uvs);
+        let mut all_faces_have_uvs = true;
 
         for (i, line) in reader.lines().enumerate() {
             let line = line?;
@@ -221,54 +392,70 @@ impl TriangleMesh {
             match parts[0] {
                 // Parse vertex line: `v x y z`
                 "v" => {
-                    let x = parts[1].parse::<f64>()?;
-                    let y = parts[2].parse::<f64>()?;
-                    let z = parts[3].parse::<f64>()?;
-                    mesh.vertices.push(Point::new(x, y, z));
+                    let [x, y, z] = Self::parse_coordinates::<3>(&parts, i + 1, &line)?;
+                    vertices.push(Point::new(x, y, z));
                 }
                 // Parse a normal line: `vn x y z`
                 "vn" => {
-                    let x = parts[1].parse::<f64>()?;
-                    let y = parts[2].parse::<f64>()?;
-                    let z = parts[3].parse::<f64>()?;
+                    let [x, y, z] = Self::parse_coordinates::<3>(&parts, i + 1, &line)?;
                     temp_normals.push(Vector4::new(x, y, z, 0.).normalize());
                 }
-                // Parse a face line: `f v1//vn1 v2//vn2 v3//vn3`
+                // Parse a texture coordinate line: `vt u [v]`
+                "vt" => {
+                    let [u, v] = match parts.len() {
+                        2 => [Self::parse_coordinates::<1>(&parts, i + 1, &line)?[0], 0.],
+                        _ => Self::parse_coordinates::<2>(&parts, i + 1, &line)?,
+                    };
+                    temp_uvs.push(Vector2::new(u, v));
+                }
+                // Parse a face line: `f v1/vt1/vn1 v2/vt2/vn2 v3/vt3/vn3`
                 "f" => {
                     if parts.len() != 4 {
                         return Err("Face must have at 3 vertices (Quads are not supported)".into());
                     }
 
-                    let total_vertices = mesh.vertices.len();
+                    let total_vertices = vertices.len();
+                    let total_uvs = temp_uvs.len();
                     let total_normals = temp_normals.len();
 
                     // Parse the first three vertices and normals for the first triangle.
-                    let (v1_idx, n1_opt) = Self::parse_and_validate_face_part(
+                    let (v1_idx, t1_opt, n1_opt) = Self::parse_and_validate_face_part(
                         parts[1],
                         total_vertices,
+                        total_uvs,
                         total_normals,
                         i + 1,
                     )?;
-                    let (v2_idx, n2_opt) = Self::parse_and_validate_face_part(
+                    let (v2_idx, t2_opt, n2_opt) = Self::parse_and_validate_face_part(
                         parts[2],
                         total_vertices,
+                        total_uvs,
                         total_normals,
                         i + 1,
                     )?;
-                    let (v3_idx, n3_opt) = Self::parse_and_validate_face_part(
+                    let (v3_idx, t3_opt, n3_opt) = Self::parse_and_validate_face_part(
                         parts[3],
                         total_vertices,
+                        total_uvs,
                         total_normals,
                         i + 1,
                     )?;
 
                     // Push the first triangle's vertex indices.
-                    mesh.triangles.push((v1_idx, v2_idx, v3_idx));
+                    triangles.push((v1_idx, v2_idx, v3_idx));
 
                     // Push the normal vector it exists.
                     let n_idx = n1_opt.or(n2_opt).or(n3_opt);
                     if let Some(n_idx) = n_idx {
-                        mesh.normals.push(temp_normals[n_idx]);
+                        normals.push(temp_normals[n_idx]);
+                    }
+
+                    // Push the texture coordinates if all corners have them.
+                    match (t1_opt, t2_opt, t3_opt) {
+                        (Some(t1), Some(t2), Some(t3)) => {
+                            uvs.push([temp_uvs[t1], temp_uvs[t2], temp_uvs[t3]]);
+                        }
+                        _ => all_faces_have_uvs = false,
                     }
                 }
                 // Ignore other lines like `g` (group) or comments (`#`)
@@ -276,30 +463,633 @@ impl TriangleMesh {
             }
         }
 
+        if !all_faces_have_uvs {
+            uvs.clear();
+        }
+        let mut mesh = TriangleMesh {
+            vertices: Arc::new(vertices),
+            triangles: Arc::new(triangles),
+            normals: Arc::new(normals),
+            uvs: Arc::new(uvs),
+            ..TriangleMesh::default()
+        };
         if !mesh.has_normals() {
             mesh.compute_normals();
         }
 
         mesh.center();
-        mesh.vertices_world = mesh.vertices.clone();
-        mesh.normals_world = mesh.normals.clone();
+        mesh.refresh_world_buffers();
 
         Ok(mesh)
     }
 
+    /// Создаёт сетку из готовых вершин и треугольников. Если нормали граней не заданы
+    /// или их количество не совпадает с количеством треугольников, они вычисляются
+    /// по порядку обхода вершин.
+    pub fn from_parts(
+        vertices: Vec<Point>,
+        triangles: Vec<Triangle>,
+        normals: Option<Vec<Vector4<f64>>>,
+    ) -> Self {
+        let mut mesh = TriangleMesh {
+            vertices: Arc::new(vertices),
+            triangles: Arc::new(triangles),
+            normals: Arc::new(normals.unwrap_or_default()),
+            ..TriangleMesh::default()
+        };
+
+        if mesh.normals.len() != mesh.triangles.len() {
+            mesh.compute_normals();
+        }
+        mesh.refresh_world_buffers();
+
+        mesh
+    }
+
+    /// Возвращает сетку, центр масс которой совмещён с началом координат
+    pub fn centered(mut self) -> Self {
+        self.center();
+        self.refresh_world_buffers();
+        self
+    }
+
+    /// Равномерно масштабирует саму сетку относительно начала координат, не меняя
+    /// преобразование модели, поэтому сброс преобразований масштаб сохраняет.
+    /// Нормали и затенение от масштаба не зависят и не пересчитываются
+    pub fn scale_geometry(&mut self, factor: f64) {
+        for v in Arc::make_mut(&mut self.vertices) {
+            v.coords *= factor;
+        }
+        self.update_vertices_world();
+    }
+
     pub fn vertices_mut(&mut self) -> &mut Vec<Point> {
-        &mut self.vertices
+        Arc::make_mut(&mut self.vertices)
     }
     pub fn vertices_world_mut(&mut self) -> &mut Vec<Point> {
         &mut self.vertices_world
     }
 }
 
+impl TriangleMesh {
+    /// Подразбиение сетки по схеме Лупа.
+    ///
+    /// На каждой итерации в середину каждого ребра добавляется вершина, исходные вершины
+    /// смещаются с весами Лупа, а каждый треугольник разбивается на четыре.
+    pub fn subdivide_loop(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.subdivide_loop_once();
+        }
+
+        self.refresh_world_buffers();
+    }
+
+    /// Упрощение сетки стягиванием рёбер с квадратичной метрикой ошибки
+    /// до `target_triangles` треугольников
+    pub fn decimate(&mut self, target_triangles: usize) {
+        let (vertices, triangles, normals) = decimate_mesh(
+            &self.vertices,
+            &self.triangles,
+            &self.normals,
+            target_triangles,
+        );
+
+        self.vertices = Arc::new(vertices);
+        self.triangles = Arc::new(triangles);
+        self.normals = Arc::new(normals);
+        self.uvs = Arc::default(); // Текстурные координаты не переносятся при упрощении
+        self.refresh_world_buffers();
+    }
+
+    /// Сваривание вершин: объединяет вершины, расположенные ближе `tolerance`,
+    /// перенумеровывает треугольники и удаляет ставшие вырожденными.
+    pub fn weld_vertices(&mut self, tolerance: f64) {
+        let cell_size = tolerance.max(f64::EPSILON);
+        let cell_of = |p: &Point| (p.coords / cell_size).map(|c| c.floor() as i64);
+
+        // 1. Находим для каждой вершины представителя с помощью пространственной сетки
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut welded_vertices: Vec<Point> = Vec::new();
+        let mut remap = Vec::with_capacity(self.vertices.len());
+
+        for v in self.vertices.iter() {
+            let cell = cell_of(v);
+            let mut found = None;
+
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let key = [cell.x + dx, cell.y + dy, cell.z + dz];
+                        let Some(candidates) = grid.get(&key) else {
+                            continue;
+                        };
+                        if let Some(&idx) = candidates
+                            .iter()
+                            .find(|&&idx| (welded_vertices[idx] - v).norm() <= tolerance)
+                        {
+                            found = Some(idx);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+
+            let idx = found.unwrap_or_else(|| {
+                welded_vertices.push(*v);
+                grid.entry([cell.x, cell.y, cell.z])
+                    .or_default()
+                    .push(welded_vertices.len() - 1);
+                welded_vertices.len() - 1
+            });
+            remap.push(idx);
+        }
+
+        // 2. Перенумеровываем треугольники, отбрасывая вырожденные
+        let has_normals = self.normals.len() == self.triangles.len();
+        let has_uvs = self.uvs.len() == self.triangles.len();
+        let mut triangles = Vec::with_capacity(self.triangles.len());
+        let mut normals = Vec::with_capacity(self.normals.len());
+        let mut uvs = Vec::with_capacity(self.uvs.len());
+
+        for (i, tri) in self.triangles.iter().enumerate() {
+            let welded = (remap[tri.0], remap[tri.1], remap[tri.2]);
+            if welded.0 == welded.1 || welded.1 == welded.2 || welded.2 == welded.0 {
+                continue;
+            }
+
+            triangles.push(welded);
+            if has_normals {
+                normals.push(self.normals[i]);
+            }
+            if has_uvs {
+                uvs.push(self.uvs[i]);
+            }
+        }
+
+        self.vertices = Arc::new(welded_vertices);
+        self.triangles = Arc::new(triangles);
+        if has_normals {
+            self.normals = Arc::new(normals);
+        }
+        self.uvs = Arc::new(uvs);
+        self.refresh_world_buffers();
+    }
+
+    /// Согласует порядок обхода треугольников: соседние треугольники проходят общее
+    /// ребро в противоположных направлениях, а каждая связная часть сетки обходится
+    /// против часовой стрелки при взгляде снаружи (положительный объём). После этого
+    /// знак площади треугольника на экране отличает лицевые грани от задних.
+    /// Нормали граней ориентируются по новому обходу. Возвращает число перевёрнутых
+    /// треугольников.
+    pub fn fix_winding(&mut self) -> usize {
+        let mut edge_triangles: HashMap<[usize; 2], Vec<usize>> = HashMap::new();
+        for (i, &(a, b, c)) in self.triangles.iter().enumerate() {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                edge_triangles.entry(edge_key(u, v)).or_default().push(i);
+            }
+        }
+
+        let has_directed_edge = |tri: &Triangle, u: usize, v: usize| {
+            [(tri.0, tri.1), (tri.1, tri.2), (tri.2, tri.0)].contains(&(u, v))
+        };
+
+        let mut flipped = vec![false; self.triangles.len()];
+        let mut visited = vec![false; self.triangles.len()];
+        for start in 0..self.triangles.len() {
+            if visited[start] {
+                continue;
+            }
+
+            // Обход связной части в ширину: сосед, проходящий общее ребро в том же
+            // направлении, переворачивается до того, как станет опорным
+            visited[start] = true;
+            let mut component = vec![start];
+            let mut queue = VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                let (a, b, c) = self.triangles[current];
+                for (u, v) in [(a, b), (b, c), (c, a)] {
+                    for &neighbour in &edge_triangles[&edge_key(u, v)] {
+                        if visited[neighbour] {
+                            continue;
+                        }
+                        visited[neighbour] = true;
+                        if has_directed_edge(&self.triangles[neighbour], u, v) {
+                            self.flip_triangle(neighbour);
+                            flipped[neighbour] ^= true;
+                        }
+                        component.push(neighbour);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            // Отрицательный объём относительно центра части - грани смотрят внутрь
+            let center = component
+                .iter()
+                .map(|&i| self.vertices[self.triangles[i].0].coords)
+                .sum::<Vector3<f64>>()
+                / component.len() as f64;
+            let volume: f64 = component
+                .iter()
+                .map(|&i| {
+                    let (a, b, c) = self.triangles[i];
+                    let [a, b, c] = [a, b, c].map(|v| self.vertices[v].coords - center);
+                    a.dot(&b.cross(&c))
+                })
+                .sum();
+            if volume < 0. {
+                for &i in &component {
+                    self.flip_triangle(i);
+                    flipped[i] ^= true;
+                }
+            }
+        }
+
+        if self.normals.len() == self.triangles.len() {
+            let normals = Arc::make_mut(&mut self.normals);
+            for (normal, tri) in normals.iter_mut().zip(self.triangles.iter()) {
+                let [a, b, c] = [tri.0, tri.1, tri.2].map(|v| self.vertices[v]);
+                if normal.xyz().dot(&(b - a).cross(&(c - a))) < 0. {
+                    *normal = -*normal;
+                }
+            }
+            self.refresh_world_buffers();
+        }
+
+        flipped.into_iter().filter(|&f| f).count()
+    }
+
+    /// Меняет порядок обхода треугольника вместе с его текстурными координатами
+    fn flip_triangle(&mut self, i: usize) {
+        let tri = &mut Arc::make_mut(&mut self.triangles)[i];
+        std::mem::swap(&mut tri.1, &mut tri.2);
+        if let Some(uv) = Arc::make_mut(&mut self.uvs).get_mut(i) {
+            uv.swap(1, 2);
+        }
+    }
+
+    /// Рёбра, принадлежащие не ровно двум треугольникам
+    pub fn non_manifold_edges(&self) -> Vec<(usize, usize)> {
+        non_manifold_edges(&self.triangles)
+    }
+
+    /// Пересоздаёт мировые буферы и пересчитывает затенение после изменения сетки,
+    /// чтобы отрисовка получала его готовым
+    fn refresh_world_buffers(&mut self) {
+        self.ambient_occlusion = if self.normals.len() == self.triangles.len() {
+            Arc::new(vertex_ambient_occlusion(
+                &self.vertices,
+                &self.triangles,
+                &self.normals,
+            ))
+        } else {
+            Arc::default()
+        };
+        if self.uvs.len() != self.triangles.len() {
+            self.uvs = Arc::default();
+        }
+        self.vertices_world = self.vertices.to_vec();
+        self.normals_world = self.normals.to_vec();
+        self.update_vertices_world();
+        self.update_normals_world();
+    }
+
+    fn subdivide_loop_once(&mut self) {
+        let vertex_count = self.vertices.len();
+
+        // 1. Собираем соседей вершин и противолежащие вершины для каждого ребра
+        let mut neighbors = vec![BTreeSet::new(); vertex_count];
+        let mut edge_opposites: HashMap<[usize; 2], Vec<usize>> = HashMap::new();
+        for &(a, b, c) in self.triangles.iter() {
+            for (u, v, opposite) in [(a, b, c), (b, c, a), (c, a, b)] {
+                neighbors[u].insert(v);
+                neighbors[v].insert(u);
+                edge_opposites
+                    .entry(edge_key(u, v))
+                    .or_default()
+                    .push(opposite);
+            }
+        }
+
+        // 2. Смещаем исходные вершины
+        let mut new_vertices: Vec<Point> = (0..vertex_count)
+            .map(|i| {
+                let boundary_neighbors: Vec<usize> = neighbors[i]
+                    .iter()
+                    .copied()
+                    .filter(|&j| edge_opposites[&edge_key(i, j)].len() != 2)
+                    .collect();
+
+                let v = self.vertices[i].coords;
+                if boundary_neighbors.len() == 2 {
+                    // Граничная вершина
+                    let sum = self.vertices[boundary_neighbors[0]].coords
+                        + self.vertices[boundary_neighbors[1]].coords;
+                    return Point::from(v * 0.75 + sum * 0.125);
+                }
+
+                let n = neighbors[i].len();
+                if n < 3 || !boundary_neighbors.is_empty() {
+                    return self.vertices[i];
+                }
+
+                let beta = if n == 3 {
+                    3. / 16.
+                } else {
+                    3. / (8. * n as f64)
+                };
+                let sum: Vector3<f64> = neighbors[i].iter().map(|&j| self.vertices[j].coords).sum();
+                Point::from(v * (1. - n as f64 * beta) + sum * beta)
+            })
+            .collect();
+
+        // 3. Добавляем вершины на рёбрах и разбиваем треугольники
+        let mut edge_points: HashMap<[usize; 2], usize> = HashMap::new();
+        let mut new_triangles = Vec::with_capacity(self.triangles.len() * 4);
+        let mut new_normals = Vec::with_capacity(self.normals.len() * 4);
+        let mut new_uvs = Vec::with_capacity(self.uvs.len() * 4);
+
+        for (tri_idx, &(a, b, c)) in self.triangles.iter().enumerate() {
+            let mut midpoints = [0; 3];
+            for (k, (u, v)) in [(a, b), (b, c), (c, a)].into_iter().enumerate() {
+                let key = edge_key(u, v);
+                midpoints[k] = match edge_points.get(&key) {
+                    Some(&idx) => idx,
+                    None => {
+                        let p = self.vertices[u].coords + self.vertices[v].coords;
+                        let opposites = &edge_opposites[&key];
+                        let position = if opposites.len() == 2 {
+                            p * 0.375
+                                + (self.vertices[opposites[0]].coords
+                                    + self.vertices[opposites[1]].coords)
+                                    * 0.125
+                        } else {
+                            p * 0.5
+                        };
+
+                        new_vertices.push(Point::from(position));
+                        edge_points.insert(key, new_vertices.len() - 1);
+                        new_vertices.len() - 1
+                    }
+                };
+            }
+
+            let [ab, bc, ca] = midpoints;
+            let children = [(a, ab, ca), (ab, b, bc), (ca, bc, c), (ab, bc, ca)];
+            new_triangles.extend(children);
+
+            if let Some(parent_normal) = self.normals.get(tri_idx) {
+                new_normals.extend(
+                    children
+                        .iter()
+                        .map(|child| oriented_face_normal(&new_vertices, child, parent_normal)),
+                );
+            }
+
+            if let Some(&[uv_a, uv_b, uv_c]) = self.uvs.get(tri_idx) {
+                let uv_ab = (uv_a + uv_b) / 2.;
+                let uv_bc = (uv_b + uv_c) / 2.;
+                let uv_ca = (uv_c + uv_a) / 2.;
+                new_uvs.extend([
+                    [uv_a, uv_ab, uv_ca],
+                    [uv_ab, uv_b, uv_bc],
+                    [uv_ca, uv_bc, uv_c],
+                    [uv_ab, uv_bc, uv_ca],
+                ]);
+            }
+        }
+
+        self.vertices = Arc::new(new_vertices);
+        self.triangles = Arc::new(new_triangles);
+        self.normals = Arc::new(new_normals);
+        self.uvs = Arc::new(new_uvs);
+    }
+}
+
+impl TriangleMesh {
+    /// Сфера единичного радиуса: икосаэдр, каждая грань которого
+    /// `subdivisions` раз разбивается на четыре с проекцией новых вершин на сферу
+    pub fn unit_sphere(subdivisions: usize) -> Self {
+        let t = (1. + 5f64.sqrt()) / 2.;
+        let mut vertices: Vec<Point> = [
+            (-1., t, 0.),
+            (1., t, 0.),
+            (-1., -t, 0.),
+            (1., -t, 0.),
+            (0., -1., t),
+            (0., 1., t),
+            (0., -1., -t),
+            (0., 1., -t),
+            (t, 0., -1.),
+            (t, 0., 1.),
+            (-t, 0., -1.),
+            (-t, 0., 1.),
+        ]
+        .into_iter()
+        .map(|(x, y, z)| Point::from(Vector3::new(x, y, z).normalize()))
+        .collect();
+
+        let mut triangles: Vec<Triangle> = vec![
+            (0, 11, 5),
+            (0, 5, 1),
+            (0, 1, 7),
+            (0, 7, 10),
+            (0, 10, 11),
+            (1, 5, 9),
+            (5, 11, 4),
+            (11, 10, 2),
+            (10, 7, 6),
+            (7, 1, 8),
+            (3, 9, 4),
+            (3, 4, 2),
+            (3, 2, 6),
+            (3, 6, 8),
+            (3, 8, 9),
+            (4, 9, 5),
+            (2, 4, 11),
+            (6, 2, 10),
+            (8, 6, 7),
+            (9, 8, 1),
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<[usize; 2], usize> = HashMap::new();
+            let mut midpoint = |a: usize, b: usize, vertices: &mut Vec<Point>| {
+                *midpoints.entry(edge_key(a, b)).or_insert_with(|| {
+                    let p = (vertices[a].coords + vertices[b].coords).normalize();
+                    vertices.push(Point::from(p));
+                    vertices.len() - 1
+                })
+            };
+
+            triangles = triangles
+                .iter()
+                .flat_map(|&(a, b, c)| {
+                    let ab = midpoint(a, b, &mut vertices);
+                    let bc = midpoint(b, c, &mut vertices);
+                    let ca = midpoint(c, a, &mut vertices);
+                    [(a, ab, ca), (ab, b, bc), (ca, bc, c), (ab, bc, ca)]
+                })
+                .collect();
+        }
+
+        Self::from_parts(vertices, triangles, None)
+    }
+
+    /// Куб с ребром 1 и центром в начале координат
+    pub fn cube() -> Self {
+        let vertices = (0..8)
+            .map(|i| {
+                let coord = |bit: usize| if i & bit == 0 { -0.5 } else { 0.5 };
+                Point::new(coord(1), coord(2), coord(4))
+            })
+            .collect();
+
+        // Грани перечислены против часовой стрелки при взгляде снаружи
+        let triangles = vec![
+            (0, 2, 3),
+            (0, 3, 1), // z = -0.5
+            (4, 5, 7),
+            (4, 7, 6), // z = 0.5
+            (0, 1, 5),
+            (0, 5, 4), // y = -0.5
+            (2, 6, 7),
+            (2, 7, 3), // y = 0.5
+            (0, 4, 6),
+            (0, 6, 2), // x = -0.5
+            (1, 3, 7),
+            (1, 7, 5), // x = 0.5
+        ];
+
+        Self::from_parts(vertices, triangles, None)
+    }
+
+    /// Тор в плоскости XY с радиусом центральной окружности `major` и радиусом трубки `minor`.
+    /// `segments` - количество разбиений вдоль каждой из окружностей.
+    pub fn torus(major: f64, minor: f64, segments: usize) -> Self {
+        let segments = segments.max(3);
+        let index = |i: usize, j: usize| (i % segments) * segments + j % segments;
+
+        let mut vertices = Vec::with_capacity(segments * segments);
+        for i in 0..segments {
+            let u = TAU * i as f64 / segments as f64;
+            for j in 0..segments {
+                let v = TAU * j as f64 / segments as f64;
+                let ring = major + minor * v.cos();
+                vertices.push(Point::new(ring * u.cos(), ring * u.sin(), minor * v.sin()));
+            }
+        }
+
+        let mut triangles = Vec::with_capacity(2 * segments * segments);
+        for i in 0..segments {
+            for j in 0..segments {
+                let a = index(i, j);
+                let b = index(i + 1, j);
+                let c = index(i + 1, j + 1);
+                let d = index(i, j + 1);
+                triangles.push((a, b, c));
+                triangles.push((a, c, d));
+            }
+        }
+
+        Self::from_parts(vertices, triangles, None)
+    }
+
+    /// Проверяет, что сетка является замкнутым связным ориентированным многообразием:
+    /// каждое ребро принадлежит ровно двум треугольникам, обходящим его в разных направлениях.
+    /// Возвращает род поверхности, вычисленный по эйлеровой характеристике V - E + F = 2 - 2g.
+    pub fn validate_manifold(&self) -> Result<usize, String> {
+        if self.triangles.is_empty() {
+            return Err("Сетка не содержит треугольников".to_string());
+        }
+
+        if let Some(&(a, b)) = self.non_manifold_edges().first() {
+            return Err(format!(
+                "Ребро ({}, {}) принадлежит не двум треугольникам",
+                a, b
+            ));
+        }
+
+        let mut directed_edges = HashSet::new();
+        for &(a, b, c) in self.triangles.iter() {
+            for edge in [(a, b), (b, c), (c, a)] {
+                if !directed_edges.insert(edge) {
+                    return Err(format!(
+                        "Несогласованная ориентация треугольников на ребре ({}, {})",
+                        edge.0, edge.1
+                    ));
+                }
+            }
+        }
+
+        let used_vertices: HashSet<usize> = self
+            .triangles
+            .iter()
+            .flat_map(|&(a, b, c)| [a, b, c])
+            .collect();
+        let euler_characteristic = used_vertices.len() as i64 - (directed_edges.len() / 2) as i64
+            + self.triangles.len() as i64;
+
+        if euler_characteristic > 2 || euler_characteristic % 2 != 0 {
+            return Err(format!(
+                "Некорректная эйлерова характеристика {} (сетка состоит из нескольких частей?)",
+                euler_characteristic
+            ));
+        }
+
+        Ok(((2 - euler_characteristic) / 2) as usize)
+    }
+
+    /// Радиус сферы с центром в центре ограничивающего параллелепипеда,
+    /// содержащей все вершины сетки в мировых координатах
+    pub fn bounding_radius(&self) -> f64 {
+        let (min, max) = self.vertices_world.iter().fold(
+            (Vector3::repeat(f64::MAX), Vector3::repeat(f64::MIN)),
+            |(min, max), v| (min.inf(&v.coords), max.sup(&v.coords)),
+        );
+        let center = (min + max) / 2.;
+        self.vertices_world
+            .iter()
+            .map(|v| (v.coords - center).norm())
+            .fold(0., f64::max)
+    }
+
+    /// Нормали в углах треугольников (в мировых координатах): среднее нормалей
+    /// граней, инцидентных вершине угла и отклонённых от нормали самого
+    /// треугольника не больше чем на `smoothing_angle` радиан. Более резкие
+    /// изломы остаются острыми.
+    pub fn corner_normals(&self, smoothing_angle: f64) -> Vec<[Vector4<f64>; 3]> {
+        let normals = self.normals();
+        let mut incident_faces = vec![Vec::new(); self.vertices.len()];
+        for (i, tri) in self.triangles.iter().enumerate() {
+            for v in [tri.0, tri.1, tri.2] {
+                incident_faces[v].push(i);
+            }
+        }
+
+        let min_cos = smoothing_angle.cos();
+        self.triangles
+            .iter()
+            .zip(normals)
+            .map(|(tri, face_normal)| {
+                [tri.0, tri.1, tri.2].map(|v| {
+                    let sum: Vector4<f64> = incident_faces[v]
+                        .iter()
+                        .map(|&f| normals[f])
+                        .filter(|n| n.dot(face_normal) >= min_cos)
+                        .sum();
+                    sum.try_normalize(f64::EPSILON).unwrap_or(*face_normal)
+                })
+            })
+            .collect()
+    }
+}
+
 impl From<DCEL> for TriangleMesh {
     fn from(dcel: DCEL) -> Self {
         let mut mesh = Self::default();
 
-        mesh.triangles = triangulate_dcel(&dcel).unwrap_or_else(|e| {
+        let triangles = triangulate_dcel(&dcel).unwrap_or_else(|e| {
             eprintln!(
                 "DEBUG: TriangleMesh::from(DCEL) - ошибка триангуляции: {}",
                 e
@@ -307,9 +1097,10 @@ impl From<DCEL> for TriangleMesh {
             eprintln!("ВНИМАНИЕ: Не удалось триангулировать DCEL, создаётся пустая сетка");
             Vec::new()
         });
+        mesh.triangles = Arc::new(triangles);
 
-        mesh.vertices = dcel.vertices;
-        mesh.vertices_world = mesh.vertices.clone();
+        mesh.vertices_world = dcel.vertices.clone();
+        mesh.vertices = Arc::new(dcel.vertices);
         mesh.material.color = Rgb([0, 255, 0]);
 
         mesh
@@ -318,8 +1109,459 @@ impl From<DCEL> for TriangleMesh {
 
 impl InteractiveModel for TriangleMesh {
     fn reset_transformations(&mut self) {
-        self.model_matrix = Matrix4::identity();
-        self.update_vertices_world();
-        self.update_normals_world();
+        self.transform = Transform::default();
+        self.apply_transform();
+    }
+
+    fn set_model_matrix(&mut self, matrix: &Matrix4<f64>) {
+        self.transform = Transform::from_matrix(matrix);
+        self.apply_transform();
+    }
+
+    /// Геометрия разделяется с сеткой через `Arc`, а мировые буферы заполняются
+    /// уже в потоке, который восстанавливает копию
+    fn snapshot(&self) -> ModelSnapshot {
+        let mut copy = TriangleMesh {
+            vertices: Arc::clone(&self.vertices),
+            vertices_world: Vec::new(),
+            normals: Arc::clone(&self.normals),
+            normals_world: Vec::new(),
+            triangles: Arc::clone(&self.triangles),
+            uvs: Arc::clone(&self.uvs),
+            material: self.material.clone(),
+            transform: self.transform.clone(),
+            model_matrix: self.model_matrix,
+            ambient_occlusion: Arc::clone(&self.ambient_occlusion),
+        };
+        Box::new(move || {
+            copy.vertices_world = vec![Point::origin(); copy.vertices.len()];
+            copy.normals_world = vec![Vector4::zeros(); copy.normals.len()];
+            copy.update_vertices_world();
+            copy.update_normals_world();
+            Rc::new(RefCell::new(copy))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::morph::Morph;
+    use crate::scene::Scene;
+    use crate::utils::testing::TempDir;
+    use nalgebra::Point3;
+
+    /// Выпуклая сетка с центром в начале координат и внешними нормалями
+    fn convex_mesh(vertices: Vec<Point>, triangles: Vec<Triangle>) -> TriangleMesh {
+        let mut mesh = TriangleMesh {
+            vertices: Arc::new(vertices),
+            triangles: Arc::new(triangles),
+            ..TriangleMesh::default()
+        };
+        let normals = mesh
+            .triangles
+            .iter()
+            .map(|tri| {
+                let center = (mesh.vertices[tri.0].coords
+                    + mesh.vertices[tri.1].coords
+                    + mesh.vertices[tri.2].coords)
+                    / 3.;
+                center.normalize().to_homogeneous()
+            })
+            .collect();
+        mesh.normals = Arc::new(normals);
+        mesh.vertices_world = mesh.vertices.to_vec();
+        mesh.normals_world = mesh.normals.to_vec();
+        mesh
+    }
+
+    fn tetrahedron() -> TriangleMesh {
+        convex_mesh(
+            vec![
+                Point::new(1., 1., 1.),
+                Point::new(1., -1., -1.),
+                Point::new(-1., 1., -1.),
+                Point::new(-1., -1., 1.),
+            ],
+            vec![(0, 1, 2), (0, 3, 1), (0, 2, 3), (1, 3, 2)],
+        )
+    }
+
+    fn octahedron() -> TriangleMesh {
+        convex_mesh(
+            vec![
+                Point::new(1., 0., 0.),
+                Point::new(-1., 0., 0.),
+                Point::new(0., 1., 0.),
+                Point::new(0., -1., 0.),
+                Point::new(0., 0., 1.),
+                Point::new(0., 0., -1.),
+            ],
+            vec![
+                (0, 2, 4),
+                (2, 1, 4),
+                (1, 3, 4),
+                (3, 0, 4),
+                (2, 0, 5),
+                (1, 2, 5),
+                (3, 1, 5),
+                (0, 3, 5),
+            ],
+        )
+    }
+
+    fn bounding_box(vertices: &[Point]) -> (Vector3<f64>, Vector3<f64>) {
+        vertices.iter().fold(
+            (Vector3::repeat(f64::MAX), Vector3::repeat(f64::MIN)),
+            |(min, max), v| (min.inf(&v.coords), max.sup(&v.coords)),
+        )
+    }
+
+    /// Количество треугольников, которым принадлежит каждое ребро
+    fn edge_usage(mesh: &TriangleMesh) -> HashMap<[usize; 2], usize> {
+        let mut usage = HashMap::new();
+        for &(a, b, c) in mesh.triangles() {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *usage.entry(edge_key(u, v)).or_insert(0) += 1;
+            }
+        }
+        usage
+    }
+
+    #[test]
+    fn loop_subdivision_of_tetrahedron() {
+        let mut mesh = tetrahedron();
+        let edges = edge_usage(&mesh).len();
+
+        mesh.subdivide_loop(1);
+
+        assert_eq!(mesh.vertices().len(), 4 + edges);
+        assert_eq!(mesh.triangles().len(), 16);
+        assert_eq!(mesh.normals().len(), 16);
+        assert_eq!(mesh.vertices_world().len(), mesh.vertices().len());
+        assert!(edge_usage(&mesh).values().all(|&count| count == 2));
+    }
+
+    #[test]
+    fn open_cube_has_four_boundary_edges() {
+        let path = format!("{}/models/cube.obj", env!("CARGO_MANIFEST_DIR"));
+        let mut mesh = TriangleMesh::from_obj(&path).unwrap();
+        assert!(mesh.non_manifold_edges().is_empty());
+
+        // Удаляем последнюю грань куба (два треугольника)
+        Arc::make_mut(&mut mesh.triangles).truncate(10);
+
+        let edges = mesh.non_manifold_edges();
+        assert_eq!(edges.len(), 4);
+        assert!(edges.iter().all(|&(a, b)| a < b));
+    }
+
+    #[test]
+    fn welding_makes_split_mesh_manifold() {
+        let solid = tetrahedron();
+
+        // Каждая грань хранит собственные копии вершин
+        let (mut vertices, mut triangles, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+        for (i, tri) in solid.triangles().iter().enumerate() {
+            for v in [tri.0, tri.1, tri.2] {
+                vertices.push(solid.vertices()[v] + Vector3::repeat(1e-9));
+            }
+            triangles.push((3 * i, 3 * i + 1, 3 * i + 2));
+            normals.push(solid.normals()[i]);
+        }
+
+        // Вырожденный треугольник из почти совпадающих вершин
+        let base = vertices.len();
+        vertices.extend([
+            Point::new(1., 1., 1.),
+            Point::new(1., 1., 1. + 1e-8),
+            Point::new(1. + 1e-8, 1., 1.),
+        ]);
+        triangles.push((base, base + 1, base + 2));
+        normals.push(Vector4::new(0., 0., 1., 0.));
+        let mut mesh = TriangleMesh {
+            vertices: Arc::new(vertices),
+            triangles: Arc::new(triangles),
+            normals: Arc::new(normals),
+            ..TriangleMesh::default()
+        };
+
+        mesh.weld_vertices(1e-6);
+
+        assert_eq!(mesh.vertices().len(), 4);
+        assert_eq!(mesh.triangles().len(), 4);
+        assert_eq!(mesh.normals().len(), 4);
+        assert_eq!(mesh.vertices_world().len(), 4);
+        assert!(edge_usage(&mesh).values().all(|&count| count == 2));
+    }
+
+    #[test]
+    fn decimation_halves_subdivided_sphere() {
+        let mut mesh = octahedron();
+        mesh.subdivide_loop(3);
+        let triangles_before = mesh.triangles().len();
+        let (min_before, max_before) = bounding_box(mesh.vertices());
+
+        mesh.decimate(triangles_before / 2);
+
+        assert!(mesh.triangles().len() <= triangles_before / 2);
+        assert_eq!(mesh.normals().len(), mesh.triangles().len());
+        assert!(edge_usage(&mesh).values().all(|&count| count == 2));
+
+        let (min_after, max_after) = bounding_box(mesh.vertices());
+        let tolerance = 0.1 * (max_before - min_before).norm();
+        assert!((min_after - min_before).norm() < tolerance);
+        assert!((max_after - max_before).norm() < tolerance);
+    }
+
+    #[test]
+    fn from_parts_tetrahedra_morph() {
+        let vertices = vec![
+            Point::new(1., 1., 1.),
+            Point::new(1., -1., -1.),
+            Point::new(-1., 1., -1.),
+            Point::new(-1., -1., 1.),
+        ];
+        let triangles = vec![(0, 1, 2), (0, 3, 1), (0, 2, 3), (1, 3, 2)];
+
+        let source = TriangleMesh::from_parts(vertices.clone(), triangles.clone(), None);
+        assert_eq!(source.normals().len(), 4);
+        for (tri, normal) in source.triangles().iter().zip(source.normals()) {
+            let center = source.vertices[tri.0].coords
+                + source.vertices[tri.1].coords
+                + source.vertices[tri.2].coords;
+            assert!(normal.xyz().dot(&center) > 0.); // Нормали направлены наружу
+        }
+
+        // Двойственный тетраэдр: вершины в центрах граней исходного
+        let target = TriangleMesh::from_parts(
+            vertices.iter().map(|v| Point::from(-v.coords)).collect(),
+            triangles.iter().map(|&(a, b, c)| (a, c, b)).collect(),
+            None,
+        )
+        .centered();
+
+        let mut morph = Morph::new(source, target).unwrap();
+        morph.update(0.5);
+        assert!(!morph.triangles().is_empty());
+        assert!(
+            morph
+                .vertices()
+                .iter()
+                .all(|v| v.coords.iter().all(|c| c.is_finite()))
+        );
+    }
+
+    #[test]
+    fn obj_with_crlf_line_endings_loads() {
+        // Последняя строка без перевода строки, вершины грани с нормалями
+        let obj = "v 0 0 0\r\nv 1 0 0\r\nv 0 1 0\r\nvn 0 0 1\r\nf 1//1 2//1 3//1";
+        let dir = TempDir::new("crlf_triangle");
+        let path = dir.join("crlf_triangle.obj");
+        fs::write(&path, obj).unwrap();
+
+        let mesh = TriangleMesh::from_obj(&path.to_string_lossy()).unwrap();
+        assert_eq!(mesh.vertices().len(), 3);
+        assert_eq!(mesh.triangles(), &vec![(0, 1, 2)]);
+        assert_eq!(mesh.normals(), &vec![Vector4::new(0., 0., 1., 0.)]);
+    }
+
+    #[test]
+    fn reserved_obj_load_matches_unreserved() {
+        let path = format!("{}/models/apple2.obj", env!("CARGO_MANIFEST_DIR"));
+
+        let counts = TriangleMesh::count_obj_elements(&path).unwrap();
+        let reserved = TriangleMesh::load_obj(&path, true).unwrap();
+        let unreserved = TriangleMesh::load_obj(&path, false).unwrap();
+
+        assert_eq!(reserved.vertices().len(), counts.vertices);
+        assert_eq!(reserved.triangles().len(), counts.faces);
+        assert_eq!(reserved.vertices(), unreserved.vertices());
+        assert_eq!(reserved.triangles(), unreserved.triangles());
+        assert_eq!(reserved.normals(), unreserved.normals());
+        assert_eq!(reserved.uvs, unreserved.uvs);
+    }
+
+    #[test]
+    fn bad_vertex_line_is_reported_with_line_number() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 one 0\nf 1 2 3\n";
+        let dir = TempDir::new("bad_vertex");
+        let path = dir.join("bad_vertex.obj");
+        fs::write(&path, obj).unwrap();
+
+        let error = TriangleMesh::from_obj(&path.to_string_lossy())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("line 3"), "{error}");
+        assert!(error.contains("v 0 one 0"), "{error}");
+    }
+
+    #[test]
+    fn snapshot_shares_geometry_and_keeps_transform() {
+        let mut mesh = TriangleMesh::torus(1., 0.3, 12);
+        mesh.rotate((0.3, 0., 0.7));
+        mesh.scale_xyz((1., 2., 0.5));
+
+        let copy = mesh.snapshot()();
+        let copy = copy.borrow();
+        assert_eq!(copy.vertices().as_ptr(), mesh.vertices().as_ptr());
+        assert_eq!(copy.triangles().as_ptr(), mesh.triangles().as_ptr());
+        assert_eq!(copy.vertices_world(), mesh.vertices_world());
+        assert_eq!(copy.normals(), mesh.normals());
+        assert_eq!(copy.ambient_occlusion(), mesh.ambient_occlusion());
+    }
+
+    #[test]
+    fn debug_output_summarizes_counts() {
+        let mesh = TriangleMesh::cube();
+        let debug = format!("{:?}", mesh);
+
+        let counts = [
+            format!("vertices: {}", mesh.vertices().len()),
+            format!("triangles: {}", mesh.triangles().len()),
+        ];
+        for count in counts {
+            assert!(debug.contains(&count), "{}", debug);
+        }
+    }
+
+    #[test]
+    fn cube_corner_normals_keep_edges_sharp() {
+        let cube = TriangleMesh::cube();
+        let corners = cube.corner_normals(60f64.to_radians());
+        for (corner, face) in corners.iter().zip(cube.normals()) {
+            for n in corner {
+                assert!((n - face).norm() < 1e-12, "{n} != {face}");
+            }
+        }
+
+        // Без порога угол куба сглаживается по трём граням
+        let smoothed = cube.corner_normals(std::f64::consts::PI)[0][0];
+        let diagonal = Vector4::new(-1., -1., -1., 0.) / 3f64.sqrt();
+        assert!((smoothed - diagonal).norm() < 1e-9, "{smoothed}");
+    }
+
+    #[test]
+    fn obj_export_round_trip_keeps_face_normals() {
+        use crate::objects::model3d::ObjExportOptions;
+
+        let torus = TriangleMesh::torus(1., 0.3, 12);
+        let dir = TempDir::new("exported_torus");
+        let path = dir.join("exported_torus.obj");
+        let round_trip = |options: ObjExportOptions| {
+            fs::write(&path, torus.to_obj_string(&options)).unwrap();
+            TriangleMesh::from_obj(&path.to_string_lossy()).unwrap()
+        };
+
+        for normals in [false, true] {
+            for flip_winding in [false, true] {
+                let loaded = round_trip(ObjExportOptions {
+                    normals,
+                    flip_winding,
+                });
+                assert_eq!(loaded.triangles().len(), torus.triangles().len());
+
+                let sign = if flip_winding { -1. } else { 1. };
+                for (original, exported) in torus.normals().iter().zip(loaded.normals()) {
+                    assert!((original * sign - exported).norm() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn obj_export_of_transformed_mesh_keeps_normals_in_object_space() {
+        use crate::objects::model3d::{InteractiveModel, ObjExportOptions, Rotate, Scale};
+
+        let mut torus = TriangleMesh::torus(1., 0.3, 12);
+        torus.rotate((0.4, -0.7, 0.2));
+        torus.scale_xyz((2., 0.5, 1.3));
+
+        let dir = TempDir::new("exported_transformed_torus");
+        let path = dir.join("torus.obj");
+        let options = ObjExportOptions {
+            normals: true,
+            flip_winding: false,
+        };
+        fs::write(&path, torus.to_obj_string(&options)).unwrap();
+        let mut loaded = TriangleMesh::from_obj(&path.to_string_lossy()).unwrap();
+        loaded.set_model_matrix(torus.model_matrix());
+
+        for (original, exported) in torus.vertices().iter().zip(loaded.vertices()) {
+            assert!((original - exported).norm() < 1e-9);
+        }
+        for (original, exported) in torus.normals().iter().zip(loaded.normals()) {
+            assert!(
+                (original - exported).norm() < 1e-9,
+                "{original} != {exported}"
+            );
+        }
+    }
+
+    #[test]
+    fn fixed_winding_faces_outward() {
+        let mut cube = TriangleMesh::cube();
+        let triangles: Vec<Triangle> = cube
+            .triangles()
+            .iter()
+            .enumerate()
+            .map(|(i, &(a, b, c))| if i % 3 == 0 { (a, c, b) } else { (a, b, c) })
+            .collect();
+        cube.triangles = Arc::new(triangles);
+        assert!(cube.validate_manifold().is_err());
+
+        assert_eq!(cube.fix_winding(), 4);
+        assert_eq!(cube.validate_manifold(), Ok(0));
+        assert!((cube.signed_volume() - 1.).abs() < 1e-12);
+
+        // Грань z = 0.5, видимая с камеры на оси +Z, обходится против часовой стрелки
+        let camera = Scene::new(1.).camera;
+        let &(a, b, c) = &cube.triangles()[2];
+        let [a, b, c] = [a, b, c].map(|v| {
+            Point3::from_homogeneous(camera.camera_matrix * cube.vertices()[v].to_homogeneous())
+                .unwrap()
+        });
+        let signed_area = (b - a).xy().perp(&(c - a).xy());
+        assert!(signed_area > 0., "площадь {signed_area}");
+    }
+
+    #[test]
+    fn primitives_are_closed_manifolds() {
+        let sphere = TriangleMesh::unit_sphere(2);
+        let torus = TriangleMesh::torus(1., 0.4, 24);
+
+        for (mesh, genus) in [(&sphere, 0), (&TriangleMesh::cube(), 0), (&torus, 1)] {
+            assert_eq!(mesh.validate_manifold(), Ok(genus));
+            assert_eq!(mesh.normals().len(), mesh.triangles().len());
+            assert!(mesh.signed_volume() > 0.);
+        }
+
+        assert!((TriangleMesh::cube().volume() - 1.).abs() < 1e-12);
+        // Вписанный икосаэдр второго уровня меньше шара примерно на 3%
+        let sphere_volume = 4. / 3. * std::f64::consts::PI;
+        assert!((sphere.volume() - sphere_volume).abs() / sphere_volume < 0.05);
+        let torus_volume = 2. * std::f64::consts::PI.powi(2) * 0.4 * 0.4;
+        assert!((torus.volume() - torus_volume).abs() / torus_volume < 0.05);
+    }
+
+    #[test]
+    fn non_uniform_scale_keeps_normals_perpendicular() {
+        let mut mesh = octahedron();
+        mesh.rotate((0.3, 0.2, 0.1));
+        mesh.scale_xyz((3., 0.5, 1.5));
+
+        let vertices = mesh.vertices_world();
+        for (tri, normal) in mesh.triangles().iter().zip(mesh.normals()) {
+            let normal = normal.xyz();
+            let edges = [
+                vertices[tri.1] - vertices[tri.0],
+                vertices[tri.2] - vertices[tri.1],
+            ];
+            assert!((normal.norm() - 1.).abs() < 1e-9);
+            for edge in edges {
+                assert!(normal.dot(&edge.normalize()).abs() < 1e-9, "{}", normal);
+            }
+        }
     }
 }
@@ -0,0 +1,109 @@
+use crate::render::transparency::TransparencyPerformer;
+use crate::render::z_buffer::ZBufferPerformer;
+use crate::render::{RenderTimings, Renderer};
+use crate::scene::Scene;
+use image::RgbImage;
+
+/// Выбирает способ отрисовки по материалу объекта: непрозрачный объект рисуется
+/// ZBufferPerformer, объект с непрозрачностью меньше единицы - TransparencyPerformer
+#[derive(Default)]
+pub struct AutoPerformer {
+    solid: ZBufferPerformer,
+    transparent: TransparencyPerformer,
+    /// Последний кадр нарисован со смешиванием
+    translucent: bool,
+}
+
+impl AutoPerformer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            solid: ZBufferPerformer::new(width, height),
+            transparent: TransparencyPerformer::new(width, height),
+            translucent: false,
+        }
+    }
+}
+
+impl Renderer for AutoPerformer {
+    fn create_frame_mut(&mut self, image: &mut RgbImage, scene: &Scene) {
+        self.translucent = scene
+            .object
+            .as_ref()
+            .is_some_and(|object| object.borrow().material().opacity < 1.);
+
+        if self.translucent {
+            self.transparent.create_frame_mut(image, scene);
+        } else {
+            self.solid.create_frame_mut(image, scene);
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.solid.resize(width, height);
+        self.transparent.resize(width, height);
+    }
+
+    fn depth_buffer(&self) -> Option<&[f64]> {
+        if self.translucent {
+            self.transparent.depth_buffer()
+        } else {
+            Some(self.solid.depth_buffer())
+        }
+    }
+
+    fn timings(&self) -> Option<&RenderTimings> {
+        if self.translucent {
+            None
+        } else {
+            self.solid.timings()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BACKGROUND_COLOR;
+    use crate::objects::model3d::InteractiveModel;
+    use crate::objects::triangle_mesh::TriangleMesh;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn scene_with_opacity(opacity: f64) -> Scene {
+        let mut cube = TriangleMesh::cube();
+        cube.material.opacity = opacity;
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(cube));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+        scene
+    }
+
+    #[test]
+    fn translucent_object_is_blended_with_background() {
+        let render = |scene: &Scene| AutoPerformer::new(64, 64).create_frame(64, 64, scene);
+
+        let opaque = scene_with_opacity(1.);
+        assert_eq!(
+            render(&opaque),
+            ZBufferPerformer::new(64, 64).create_frame(64, 64, &opaque)
+        );
+
+        let glassy = scene_with_opacity(0.5);
+        let frame = render(&glassy);
+        assert_eq!(
+            frame,
+            TransparencyPerformer::new(64, 64).create_frame(64, 64, &glassy)
+        );
+
+        // Чем прозрачнее объект, тем ближе его пиксель к цвету фона
+        let fainter = render(&scene_with_opacity(0.25));
+        let distance = |image: &RgbImage| {
+            let pixel = image.get_pixel(32, 32);
+            (0..3)
+                .map(|c| pixel.0[c].abs_diff(BACKGROUND_COLOR.0[c]) as u32)
+                .sum::<u32>()
+        };
+        assert!(distance(&frame) > 0);
+        assert!(distance(&fainter) < distance(&frame));
+    }
+}
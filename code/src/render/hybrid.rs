@@ -0,0 +1,162 @@
+use crate::config::{WIREFRAME_COLOR, WIREFRAME_DEPTH_BIAS};
+use crate::objects::camera::Camera;
+use crate::objects::model3d::Model3D;
+use crate::render::z_buffer::ZBufferPerformer;
+use crate::render::{RenderTimings, Renderer};
+use crate::scene::Scene;
+use crate::utils::triangles::edge_key;
+use image::{Rgb, RgbImage};
+use nalgebra::Point3;
+use std::collections::BTreeSet;
+use std::ops::Deref;
+
+/// Поверхность с z-буфером и каркас поверх неё. Рёбра проверяются по буферу глубины
+/// только что отрисованной поверхности, поэтому невидимые линии не рисуются.
+#[derive(Default)]
+pub struct HybridPerformer {
+    solid: ZBufferPerformer,
+}
+
+impl HybridPerformer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            solid: ZBufferPerformer::new(width, height),
+        }
+    }
+}
+
+/// Рисует рёбра модели с теми же матрицами, что и поверхность, проверяя их
+/// по буферу глубины `depth`
+pub(super) fn draw_edges(
+    image: &mut RgbImage,
+    depth: &[f64],
+    model: &dyn Model3D,
+    camera: &Camera,
+    color: Rgb<u8>,
+) {
+    let (width, height) = image.dimensions();
+    let mvpv_matrix = ZBufferPerformer::calculate_viewport_matrix(width, height)
+        * camera.camera_matrix
+        * model.model_matrix();
+    let screen_vertices: Vec<Point3<f64>> = model
+        .vertices()
+        .iter()
+        .map(|v| {
+            Point3::from_homogeneous(mvpv_matrix * v.to_homogeneous())
+                .expect("Perspective division failed.")
+        })
+        .collect();
+
+    let edges: BTreeSet<[usize; 2]> = model
+        .triangles()
+        .iter()
+        .flat_map(|&(a, b, c)| [edge_key(a, b), edge_key(b, c), edge_key(c, a)])
+        .collect();
+
+    for [a, b] in edges {
+        draw_edge(
+            image,
+            depth,
+            &screen_vertices[a],
+            &screen_vertices[b],
+            color,
+        );
+    }
+}
+
+/// Рисует отрезок с проверкой глубины. Глубина в NDC линейна вдоль отрезка на экране.
+/// Рёбра, выходящие за ближнюю или дальнюю плоскость отсечения, пропускаются.
+fn draw_edge(
+    image: &mut RgbImage,
+    depth: &[f64],
+    start: &Point3<f64>,
+    end: &Point3<f64>,
+    color: Rgb<u8>,
+) {
+    let depth_range = -1.0..=1.0;
+    if !depth_range.contains(&start.z) || !depth_range.contains(&end.z) {
+        return;
+    }
+
+    let (width, height) = image.dimensions();
+    let steps = (end.x - start.x)
+        .abs()
+        .max((end.y - start.y).abs())
+        .ceil()
+        .max(1.) as usize;
+
+    for step in 0..=steps {
+        let p = start.coords.lerp(&end.coords, step as f64 / steps as f64);
+        let (x, y) = (p.x.floor(), p.y.floor());
+        if x < 0. || y < 0. || x >= width as f64 || y >= height as f64 {
+            continue;
+        }
+
+        let (x, y) = (x as u32, y as u32);
+        if p.z <= depth[(y * width + x) as usize] + WIREFRAME_DEPTH_BIAS {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+impl Renderer for HybridPerformer {
+    fn create_frame_mut(&mut self, image: &mut RgbImage, scene: &Scene) {
+        self.solid.create_frame_mut(image, scene);
+
+        // Каркас выделенного объекта уже нарисован цветом выделения
+        if let Some(object) = scene
+            .object
+            .as_ref()
+            .filter(|_| scene.selected_object().is_none())
+        {
+            let depth = self.solid.depth_buffer();
+            draw_edges(
+                image,
+                depth,
+                object.borrow().deref(),
+                &scene.camera,
+                WIREFRAME_COLOR,
+            );
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.solid.resize(width, height);
+    }
+
+    fn depth_buffer(&self) -> Option<&[f64]> {
+        Some(self.solid.depth_buffer())
+    }
+
+    fn timings(&self) -> Option<&RenderTimings> {
+        self.solid.timings()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BACKGROUND_COLOR;
+    use crate::objects::model3d::InteractiveModel;
+    use crate::objects::triangle_mesh::TriangleMesh;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn hybrid_frame_has_shaded_and_edge_pixels() {
+        let object: Rc<RefCell<dyn InteractiveModel>> =
+            Rc::new(RefCell::new(TriangleMesh::unit_sphere(1)));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+
+        let frame = HybridPerformer::new(64, 64).create_frame(64, 64, &scene);
+
+        let edges = frame.pixels().filter(|&&px| px == WIREFRAME_COLOR).count();
+        let shaded = frame
+            .pixels()
+            .filter(|&&px| px != WIREFRAME_COLOR && px != BACKGROUND_COLOR)
+            .count();
+        assert!(edges > 0, "нет пикселей каркаса");
+        assert!(shaded > 0, "нет закрашенных пикселей поверхности");
+    }
+}
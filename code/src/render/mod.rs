@@ -1,13 +1,23 @@
+pub mod auto;
+pub mod hybrid;
+pub mod post;
 pub mod transparency;
 pub mod wireframe_drawer;
+pub mod worker;
 pub mod z_buffer;
 
-use crate::config::{AMBIENT_INTENSITY, LIGHT_SCATTERING};
+use crate::config::{
+    AMBIENT_INTENSITY, SUPERMESH_INTERSECTION_COLOR, SUPERMESH_SOURCE_COLOR, SUPERMESH_TARGET_COLOR,
+};
 use crate::objects::light::LightSource;
-use crate::objects::model3d::Material;
+use crate::objects::model3d::{Material, Model3D};
+use crate::render::post::{FogSettings, OutlineSettings};
 use crate::scene::Scene;
+use crate::utils::math::all_finite;
+use crate::utils::morphing::SegmentOrigin;
 use image::{Rgb, RgbImage};
 use nalgebra::{Point3, Vector3};
+use std::time::Duration;
 
 fn compute_reflection(
     light_direction: &Vector3<f64>,
@@ -17,54 +27,397 @@ fn compute_reflection(
     (-1. * light_direction) + (beta * surface_normal)
 }
 
+/// Модель зеркального отражения
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SpecularModel {
+    /// Отражённый луч и направление на наблюдателя
+    #[default]
+    Phong,
+    /// Нормаль и биссектриса направлений на свет и наблюдателя
+    BlinnPhong,
+}
+
+impl SpecularModel {
+    /// Коэффициент зеркального блика (без учёта интенсивности света и материала)
+    fn specular_term(
+        self,
+        normal: &Vector3<f64>,
+        light_direction: &Vector3<f64>,
+        view_direction: &Vector3<f64>,
+        gloss: f64,
+    ) -> f64 {
+        let cos = match self {
+            SpecularModel::Phong => compute_reflection(light_direction, normal).dot(view_direction),
+            SpecularModel::BlinnPhong => (light_direction + view_direction)
+                .try_normalize(f64::EPSILON)
+                .map_or(0., |half| normal.dot(&half)),
+        };
+        cos.max(0.).powf(gloss)
+    }
+}
+
+/// Нормаль для освещения: при двустороннем освещении разворачивается к наблюдателю
+fn shading_normal(
+    normal: &Vector3<f64>,
+    surface_point: &Point3<f64>,
+    eye_pos: &Point3<f64>,
+    two_sided: bool,
+) -> Vector3<f64> {
+    if two_sided && normal.dot(&(eye_pos - surface_point)) < 0. {
+        -normal
+    } else {
+        *normal
+    }
+}
+
 fn calculate_color(
     material: &Material,
     normal: &Vector3<f64>,
     surface_point: &Point3<f64>,
+    base_color: Rgb<u8>,
     light_source: &LightSource,
     eye_pos: &Point3<f64>,
+    specular_model: SpecularModel,
 ) -> Rgb<u8> {
     // let normal = Vector3::new(0., 0., 1.);
     // let surface_point = Point3::new(0., 0., 0.);
+    debug_assert!(
+        all_finite(normal.iter()) && all_finite(surface_point.iter()),
+        "calculate_color: NaN в нормали {} или точке {}",
+        normal,
+        surface_point
+    );
     let mut light_direction = light_source.pos - surface_point;
     let dist = light_direction.norm();
 
     light_direction.normalize_mut();
     let view_direction = (eye_pos - surface_point).normalize();
 
-    let reflection_direction = compute_reflection(&light_direction, normal);
-
-    let light_intensity = light_source.intensity / (dist + LIGHT_SCATTERING as f64);
+    let light_intensity = light_source.intensity_at(dist);
 
+    // Фоновый свет не зависит от источника и даёт тусклую копию цвета поверхности
+    let ambient_intensity = AMBIENT_INTENSITY as f64;
     let diffuse_intensity = material.diffuse_reflectance_factor
         * light_intensity
-        * normal.dot(&light_direction).max(0.)
-        + AMBIENT_INTENSITY as f64;
+        * normal.dot(&light_direction).max(0.);
     let specular_intensity = material.specular_reflectance_factor
         * light_intensity
-        * reflection_direction
-            .dot(&view_direction)
-            .max(0.)
-            .powf(material.gloss);
+        * specular_model.specular_term(normal, &light_direction, &view_direction, material.gloss);
+    // Ободок (френелевский блик) там, где поверхность почти касательна к взгляду
+    let rim_intensity =
+        material.rim_strength * (1. - normal.dot(&view_direction).max(0.)).powf(material.rim_power);
 
-    let r = (material.color[0] as f64 * diffuse_intensity
-        + light_source.color[0] as f64 * specular_intensity)
+    let r = (base_color[0] as f64 * ambient_intensity
+        + base_color[0] as f64 * diffuse_intensity
+        + light_source.color[0] as f64 * specular_intensity
+        + material.rim_color[0] as f64 * rim_intensity)
         .clamp(0., 255.);
-    let g = (material.color[1] as f64 * diffuse_intensity
-        + light_source.color[1] as f64 * specular_intensity)
+    let g = (base_color[1] as f64 * ambient_intensity
+        + base_color[1] as f64 * diffuse_intensity
+        + light_source.color[1] as f64 * specular_intensity
+        + material.rim_color[1] as f64 * rim_intensity)
         .clamp(0., 255.);
-    let b = (material.color[2] as f64 * diffuse_intensity
-        + light_source.color[2] as f64 * specular_intensity)
+    let b = (base_color[2] as f64 * ambient_intensity
+        + base_color[2] as f64 * diffuse_intensity
+        + light_source.color[2] as f64 * specular_intensity
+        + material.rim_color[2] as f64 * rim_intensity)
         .clamp(0., 255.);
 
     Rgb([r.round() as u8, g.round() as u8, b.round() as u8])
 }
 
+/// Настройки отрисовки сцены
+#[derive(Clone, Default)]
+pub struct RenderOptions {
+    /// Подсвечивать рёбра, принадлежащие не ровно двум треугольникам
+    pub show_non_manifold_edges: bool,
+    /// Затенять вершины по доле перекрытого окружающего света
+    pub ambient_occlusion: bool,
+    /// Освещать грани с обеих сторон (для тонких и промежуточных сеток морфинга)
+    pub two_sided: bool,
+    pub specular_model: SpecularModel,
+    /// Туман по глубине
+    pub fog_enabled: bool,
+    pub fog: FogSettings,
+    /// Контур по разрывам глубины
+    pub outline_enabled: bool,
+    pub outline: OutlineSettings,
+    /// Сдвиг треугольников вдоль их нормалей (разнесённый вид), 0 - без сдвига
+    pub explode_factor: f64,
+    /// Отмечать вершины в каркасном режиме
+    pub vertex_markers: bool,
+    /// Раскрашивать треугольники суперсетки морфинга по происхождению рёбер
+    pub show_supermesh_origin: bool,
+    /// Замерять время этапов отрисовки (включается открытой отладочной панелью)
+    pub collect_timings: bool,
+}
+
+/// Время этапов отрисовки последнего кадра
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderTimings {
+    /// Перевод вершин в пространство экрана
+    pub transform: Duration,
+    /// Расчёт освещения в вершинах
+    pub shading: Duration,
+    /// Очистка буферов и растеризация треугольников
+    pub rasterization: Duration,
+    /// Контур, туман и отладочные наложения
+    pub post_processing: Duration,
+    /// Полное время отрисовки кадра
+    pub total: Duration,
+}
+
+impl RenderTimings {
+    /// Суммарное время замеренных этапов
+    pub fn stages(&self) -> Duration {
+        self.transform + self.shading + self.rasterization + self.post_processing
+    }
+}
+
+/// Отладочный цвет треугольника суперсетки, если включена раскраска по происхождению
+pub fn supermesh_origin_color(
+    model: &dyn Model3D,
+    triangle: usize,
+    options: &RenderOptions,
+) -> Option<Rgb<u8>> {
+    if !options.show_supermesh_origin {
+        return None;
+    }
+
+    Some(match model.triangle_origins()?.get(triangle)? {
+        SegmentOrigin::Source => SUPERMESH_SOURCE_COLOR,
+        SegmentOrigin::Target => SUPERMESH_TARGET_COLOR,
+        SegmentOrigin::Intersection => SUPERMESH_INTERSECTION_COLOR,
+    })
+}
+
+/// Рендерер окна просмотра
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RendererKind {
+    /// Поверхность с z-буфером; полупрозрачный объект смешивается с фоном
+    #[default]
+    Solid,
+    /// Поверхность и каркас с удалением невидимых линий
+    Hybrid,
+    /// Объекты с непрозрачностью меньше единицы смешиваются с тем, что за ними
+    Transparent,
+    /// Только рёбра, без удаления невидимых линий
+    Wireframe,
+}
+
 pub trait Renderer {
     fn create_frame(&mut self, width: u32, height: u32, scene: &Scene) -> RgbImage {
         let mut image = RgbImage::new(width, height);
+        self.resize(width, height);
         self.create_frame_mut(&mut image, scene);
         image
     }
+    /// Рисует кадр в изображение, размер которого задан последним вызовом `resize`
     fn create_frame_mut(&mut self, image: &mut RgbImage, scene: &Scene);
+
+    /// Подгоняет внутренние буферы под размер кадра. Вызывается при изменении размера
+    /// области просмотра, а не на каждом кадре
+    fn resize(&mut self, _width: u32, _height: u32) {}
+
+    /// Буфер глубины последнего кадра (построчно), если рендерер его ведёт
+    fn depth_buffer(&self) -> Option<&[f64]> {
+        None
+    }
+
+    /// Время этапов последнего кадра, если оно замерялось
+    fn timings(&self) -> Option<&RenderTimings> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{GLOSS_MAX, GLOSS_MIN};
+    use crate::objects::light::Attenuation;
+    use imageproc::definitions::HasWhite;
+
+    fn brightness(color: Rgb<u8>) -> u32 {
+        color.0.iter().map(|&c| c as u32).sum()
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "calculate_color: NaN")]
+    fn degenerate_triangle_fails_before_shading() {
+        use crate::utils::triangles::barycentric;
+
+        // Вершины на одной прямой: барицентрические координаты не определены
+        let [a, b, c] = [0., 1., 2.].map(|x| Point3::new(x, 0., 0.));
+        let bary = barycentric(&Point3::new(0.5, 0., 0.), &a, &b, &c);
+        let surface_point = Point3::from(bary.x * a.coords + bary.y * b.coords + bary.z * c.coords);
+        let light_source = LightSource {
+            pos: Point3::new(0., 0., 3.),
+            intensity: 15.,
+            color: Rgb::white(),
+            attenuation: Attenuation::default(),
+        };
+
+        calculate_color(
+            &Material::default(),
+            &Vector3::z(),
+            &surface_point,
+            Material::default().color,
+            &light_source,
+            &Point3::new(0., 0., 3.),
+            SpecularModel::default(),
+        );
+    }
+
+    #[test]
+    fn two_sided_lights_back_face() {
+        let light_source = LightSource {
+            pos: Point3::new(0., 0., 3.),
+            intensity: 15.,
+            color: Rgb::white(),
+            attenuation: Attenuation::default(),
+        };
+        let eye_pos = Point3::new(0., 0., 3.);
+        let surface_point = Point3::origin();
+        let back_normal = Vector3::new(0., 0., -1.);
+
+        let [one_sided, two_sided] = [false, true].map(|flag| {
+            let normal = shading_normal(&back_normal, &surface_point, &eye_pos, flag);
+            calculate_color(
+                &Material::default(),
+                &normal,
+                &surface_point,
+                Material::default().color,
+                &light_source,
+                &eye_pos,
+                SpecularModel::Phong,
+            )
+        });
+
+        assert!(brightness(two_sided) > brightness(one_sided));
+    }
+
+    #[test]
+    fn unlit_face_shows_ambient_fraction_of_color() {
+        let light_source = LightSource {
+            pos: Point3::new(0., 0., -3.),
+            intensity: 15.,
+            color: Rgb::white(),
+            attenuation: Attenuation::default(),
+        };
+        let eye_pos = Point3::new(0., 0., 3.);
+        let surface_point = Point3::origin();
+        let normal = Vector3::new(0., 0., 1.);
+        let material = Material {
+            color: Rgb([250, 128, 10]),
+            specular_reflectance_factor: 1.,
+            ..Material::default()
+        };
+
+        let color = calculate_color(
+            &material,
+            &normal,
+            &surface_point,
+            material.color,
+            &light_source,
+            &eye_pos,
+            SpecularModel::Phong,
+        );
+
+        let expected = material
+            .color
+            .0
+            .map(|c| (c as f64 * AMBIENT_INTENSITY as f64).round() as u8);
+        assert_eq!(color.0, expected);
+    }
+
+    #[test]
+    fn rim_brightens_grazing_angle() {
+        let light_source = LightSource {
+            pos: Point3::new(0., 3., 0.),
+            intensity: 15.,
+            color: Rgb::white(),
+            attenuation: Attenuation::default(),
+        };
+        let eye_pos = Point3::new(0., 0., 3.);
+        let surface_point = Point3::origin();
+        let head_on = Vector3::new(0., 0., 1.);
+        let grazing = Vector3::new(0., 1., 0.05).normalize();
+
+        let shade = |material: &Material, normal: &Vector3<f64>| {
+            brightness(calculate_color(
+                material,
+                normal,
+                &surface_point,
+                material.color,
+                &light_source,
+                &eye_pos,
+                SpecularModel::Phong,
+            ))
+        };
+
+        // Без ободка и отражений грани освещены только фоновым светом
+        let matte = Material {
+            diffuse_reflectance_factor: 0.,
+            specular_reflectance_factor: 0.,
+            ..Material::default()
+        };
+        assert_eq!(shade(&matte, &grazing), shade(&matte, &head_on));
+
+        let rim = Material {
+            rim_strength: 0.8,
+            ..matte.clone()
+        };
+        assert!(shade(&rim, &grazing) > shade(&rim, &head_on));
+        assert_eq!(shade(&rim, &head_on), shade(&matte, &head_on));
+    }
+
+    #[test]
+    fn blinn_phong_highlight_is_wider_at_grazing_angle() {
+        let normal = Vector3::new(0., 0., 1.);
+        let light_direction = Vector3::new(1., 0., 0.1).normalize();
+        let view_direction = Vector3::new(0., 0., 1.);
+
+        let [phong, blinn_phong] = [SpecularModel::Phong, SpecularModel::BlinnPhong]
+            .map(|model| model.specular_term(&normal, &light_direction, &view_direction, 8.));
+
+        assert!(
+            blinn_phong > phong,
+            "phong: {}, blinn-phong: {}",
+            phong,
+            blinn_phong
+        );
+        assert!((0.0..=1.0).contains(&blinn_phong));
+    }
+
+    #[test]
+    fn higher_gloss_narrows_highlight() {
+        let normal = Vector3::new(0., 0., 1.);
+        let view_direction = Vector3::new(0., 0., 1.);
+        // Доля направлений света в плоскости XZ, при которых блик заметен
+        let footprint = |gloss: f64| {
+            (0..=180)
+                .map(|step| (step as f64).to_radians())
+                .map(|angle| Vector3::new(angle.cos(), 0., angle.sin()))
+                .filter(|light_direction| {
+                    SpecularModel::Phong.specular_term(
+                        &normal,
+                        light_direction,
+                        &view_direction,
+                        gloss,
+                    ) > 0.5
+                })
+                .count()
+        };
+
+        let [wide, medium, narrow] = [GLOSS_MIN, 16., GLOSS_MAX].map(footprint);
+        assert!(
+            wide > medium && medium > narrow,
+            "{} {} {}",
+            wide,
+            medium,
+            narrow
+        );
+        assert!(narrow > 0);
+    }
 }
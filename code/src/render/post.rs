@@ -0,0 +1,203 @@
+// Постобработка готового кадра с использованием буфера глубины
+use crate::config::BACKGROUND_COLOR;
+use crate::objects::camera::Camera;
+use image::{Rgb, RgbImage};
+
+/// Параметры тумана
+#[derive(Clone)]
+pub struct FogSettings {
+    /// Расстояние от камеры, с которого начинается туман
+    pub start: f64,
+    /// Расстояние, на котором пиксель полностью окрашивается в цвет тумана
+    pub end: f64,
+    pub color: Rgb<u8>,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        Self {
+            start: 3.,
+            end: 8.,
+            color: BACKGROUND_COLOR,
+        }
+    }
+}
+
+impl FogSettings {
+    /// Доля тумана в цвете пикселя на расстоянии `distance` от камеры
+    fn density(&self, distance: f64) -> f64 {
+        if self.end <= self.start {
+            return if distance >= self.start { 1. } else { 0. };
+        }
+        ((distance - self.start) / (self.end - self.start)).clamp(0., 1.)
+    }
+}
+
+/// Параметры контура
+#[derive(Clone)]
+pub struct OutlineSettings {
+    /// Относительный перепад глубины между соседними пикселями, считающийся границей
+    pub threshold: f64,
+    /// Толщина контура в пикселях
+    pub thickness: u32,
+    pub color: Rgb<u8>,
+}
+
+impl Default for OutlineSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 0.05,
+            thickness: 1,
+            color: Rgb([0, 0, 0]),
+        }
+    }
+}
+
+/// Переводит глубину из нормализованных координат устройства в расстояние
+/// вдоль оси взгляда камеры
+pub fn linearize_depth(ndc_depth: f64, near: f64, far: f64) -> f64 {
+    if ndc_depth.is_infinite() {
+        return f64::INFINITY;
+    }
+    2. * near * far / (far + near - ndc_depth * (far - near))
+}
+
+/// Смешивает цвет каждого пикселя с цветом тумана в зависимости от его глубины.
+/// Пиксели фона (бесконечная глубина) полностью окрашиваются в цвет тумана.
+pub fn apply_fog(image: &mut RgbImage, depth_buffer: &[f64], camera: &Camera, fog: &FogSettings) {
+    let (near, far) = (camera.near_plane(), camera.far_plane());
+
+    for (pixel, &depth) in image.pixels_mut().zip(depth_buffer) {
+        let t = fog.density(linearize_depth(depth, near, far));
+        for (channel, &fog_channel) in pixel.0.iter_mut().zip(&fog.color.0) {
+            let blended = *channel as f64 * (1. - t) + fog_channel as f64 * t;
+            *channel = blended.round() as u8;
+        }
+    }
+}
+
+/// Отмечает пиксели, за которыми по глубине следует резкий перепад
+fn depth_edges(width: u32, height: u32, distances: &[f64], threshold: f64) -> Vec<bool> {
+    let index = |x: u32, y: u32| (y * width + x) as usize;
+    let mut edges = vec![false; distances.len()];
+
+    for y in 0..height {
+        for x in 0..width {
+            let near = distances[index(x, y)];
+            if near.is_infinite() {
+                continue; // Фон не может быть границей объекта
+            }
+
+            let neighbors = [
+                (x > 0).then(|| index(x - 1, y)),
+                (x + 1 < width).then(|| index(x + 1, y)),
+                (y > 0).then(|| index(x, y - 1)),
+                (y + 1 < height).then(|| index(x, y + 1)),
+            ];
+            edges[index(x, y)] = neighbors
+                .into_iter()
+                .flatten()
+                .any(|n| (distances[n] - near) / near > threshold);
+        }
+    }
+
+    edges
+}
+
+/// Рисует контур объекта по разрывам глубины между соседними пикселями.
+/// Граница проходит по ближнему к камере пикселю, затем утолщается до `thickness`.
+pub fn apply_outline(
+    image: &mut RgbImage,
+    depth_buffer: &[f64],
+    camera: &Camera,
+    outline: &OutlineSettings,
+) {
+    let (width, height) = image.dimensions();
+    let (near, far) = (camera.near_plane(), camera.far_plane());
+    let distances: Vec<f64> = depth_buffer
+        .iter()
+        .map(|&depth| linearize_depth(depth, near, far))
+        .collect();
+
+    let edges = depth_edges(width, height, &distances, outline.threshold);
+    let radius = outline.thickness.saturating_sub(1) as i64;
+
+    for (i, _) in edges.iter().enumerate().filter(|(_, edge)| **edge) {
+        let (x, y) = ((i as u32 % width) as i64, (i as u32 / width) as i64);
+        for py in (y - radius).max(0)..=(y + radius).min(height as i64 - 1) {
+            for px in (x - radius).max(0)..=(x + radius).min(width as i64 - 1) {
+                image.put_pixel(px as u32, py as u32, outline.color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Point3, Vector3};
+
+    fn camera() -> Camera {
+        Camera::new(
+            Point3::new(0., 0., 3.),
+            Point3::origin(),
+            Vector3::y(),
+            1.,
+            1.,
+            0.1,
+            100.,
+        )
+    }
+
+    #[test]
+    fn linearize_depth_maps_clip_planes() {
+        assert!((linearize_depth(-1., 0.1, 100.) - 0.1).abs() < 1e-9);
+        assert!((linearize_depth(1., 0.1, 100.) - 100.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn outline_marks_silhouette_only() {
+        // Квадрат 3x3 в центре изображения 7x7
+        let size = 7;
+        let depth: Vec<f64> = (0..size * size)
+            .map(|i| {
+                let (x, y) = (i % size, i / size);
+                if (2..=4).contains(&x) && (2..=4).contains(&y) {
+                    0.9
+                } else {
+                    f64::INFINITY
+                }
+            })
+            .collect();
+
+        let white = Rgb([255, 255, 255]);
+        let mut image = RgbImage::from_pixel(size, size, white);
+        let outline = OutlineSettings::default();
+        apply_outline(&mut image, &depth, &camera(), &outline);
+
+        for (x, y) in [(2, 2), (3, 2), (4, 3), (2, 4)] {
+            assert_eq!(*image.get_pixel(x, y), outline.color);
+        }
+        assert_eq!(*image.get_pixel(3, 3), white); // Внутренняя часть
+        assert_eq!(*image.get_pixel(0, 0), white); // Фон
+        assert_eq!(*image.get_pixel(1, 3), white);
+    }
+
+    #[test]
+    fn far_pixels_are_foggier() {
+        let mut image = RgbImage::from_pixel(3, 1, Rgb([255, 255, 255]));
+        let fog = FogSettings {
+            start: 1.,
+            end: 10.,
+            color: Rgb([0, 0, 0]),
+        };
+        // Ближний пиксель, дальний пиксель и фон
+        apply_fog(&mut image, &[0.95, 0.99, f64::INFINITY], &camera(), &fog);
+
+        let near = image.get_pixel(0, 0)[0];
+        let far = image.get_pixel(1, 0)[0];
+        let background = image.get_pixel(2, 0)[0];
+        assert!(far < near, "near: {}, far: {}", near, far);
+        assert_eq!(background, 0);
+    }
+}
@@ -1,92 +1,95 @@
-use crate::config::BACKGROUND_COLOR;
+use crate::config::SELECTION_COLOR;
 use crate::objects::camera::Camera;
 use crate::objects::light::LightSource;
-use crate::objects::model3d::{InteractiveModel, Model3D};
-use crate::render::Renderer;
-use crate::render::calculate_color;
+use crate::objects::model3d::Model3D;
+use crate::render::hybrid::draw_edges;
+use crate::render::z_buffer::{ZBufferPerformer, pixel_span};
+use crate::render::{RenderOptions, Renderer, calculate_color, supermesh_origin_color};
 use crate::scene::Scene;
 use crate::utils::triangles::barycentric;
 use image::{Rgb, RgbImage};
-use nalgebra::{Matrix4, Point3};
+use nalgebra::{Point3, Vector3};
 use std::ops::Deref;
 
-pub struct TransparencyPerformer {}
+/// Непрозрачные объекты рисуются с z-буфером, полупрозрачные (opacity < 1) смешиваются
+/// поверх них от дальних треугольников к ближним с проверкой глубины.
+#[derive(Default)]
+pub struct TransparencyPerformer {
+    solid: ZBufferPerformer,
+}
+
+/// Полупрозрачный треугольник с плоской заливкой в пространстве экрана
+struct TranslucentTriangle {
+    vertices: [Point3<f64>; 3],
+    color: Rgb<u8>,
+    alpha: f64,
+    /// Средняя глубина вершин в NDC, для сортировки
+    depth: f64,
+}
 
 impl TransparencyPerformer {
-    fn draw_triangle(
-        &mut self,
-        image: &mut RgbImage,
-        tri: &[Point3<f64>; 3],
-        color: Rgb<u8>,
-        alpha: f64,
-    ) {
-        let [p1, p2, p3] = *tri;
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            solid: ZBufferPerformer::new(width, height),
+        }
+    }
 
-        // Find the bounding box of the triangle to optimize rasterization.
-        let min_x = p1.x.min(p2.x).min(p3.x).round() as u32;
-        let max_x = p1.x.max(p2.x).max(p3.x).round() as u32;
-        let min_y = p1.y.min(p2.y).min(p3.y).round() as u32;
-        let max_y = p1.y.max(p2.y).max(p3.y).round() as u32;
+    fn draw_triangle(&self, image: &mut RgbImage, tri: &TranslucentTriangle) {
+        let [p1, p2, p3] = tri.vertices;
+        let flat = tri.vertices.map(|p| Point3::new(p.x, p.y, 0.));
+        let depth = self.solid.depth_buffer();
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // Find the pixels whose centers lie in the bounding box of the triangle.
+        let (min_x, max_x) = pixel_span(p1.x.min(p2.x).min(p3.x), p1.x.max(p2.x).max(p3.x));
+        let (min_y, max_y) = pixel_span(p1.y.min(p2.y).min(p3.y), p1.y.max(p2.y).max(p3.y));
 
         // Clamp bounding box to image boundaries.
-        let max_x = max_x.min(image.width() - 1);
-        let max_y = max_y.min(image.height() - 1);
+        let max_x = max_x.min(width - 1);
+        let max_y = max_y.min(height - 1);
 
         for y in min_y..=max_y {
             for x in min_x..=max_x {
-                let bary = barycentric(&Point3::new(x as f64, y as f64, 0.), &p1, &p2, &p3);
+                // Sample coverage at the pixel center.
+                let center = Point3::new(x as f64 + 0.5, y as f64 + 0.5, 0.);
+                let bary = barycentric(&center, &flat[0], &flat[1], &flat[2]);
 
                 // Check if the pixel is inside the triangle.
-                if bary.x >= 0.0 && bary.y >= 0.0 && bary.z >= 0.0 {
-                    let old_pixel = image.get_pixel(x, y);
-                    let final_r = (color[0] as f64 * alpha) + (old_pixel[0] as f64 * (1.0 - alpha));
-                    let final_g = (color[1] as f64 * alpha) + (old_pixel[1] as f64 * (1.0 - alpha));
-                    let final_b = (color[2] as f64 * alpha) + (old_pixel[2] as f64 * (1.0 - alpha));
-
-                    image.put_pixel(
-                        x,
-                        y,
-                        Rgb([
-                            final_r.round() as u8,
-                            final_g.round() as u8,
-                            final_b.round() as u8,
-                        ]),
-                    );
+                if bary.x < 0.0 || bary.y < 0.0 || bary.z < 0.0 {
+                    continue;
+                }
+
+                // Непрозрачная поверхность ближе - фрагмент скрыт
+                let z = p1.z * bary.x + p2.z * bary.y + p3.z * bary.z;
+                if z >= depth[(y * width + x) as usize] {
+                    continue;
                 }
+
+                let old_pixel = image.get_pixel(x, y);
+                let blended = [0, 1, 2].map(|c| {
+                    let value =
+                        tri.color[c] as f64 * tri.alpha + old_pixel[c] as f64 * (1.0 - tri.alpha);
+                    value.round() as u8
+                });
+                image.put_pixel(x, y, Rgb(blended));
             }
         }
     }
 
-    fn draw_object(
-        &mut self,
-        image: &mut RgbImage,
+    /// Переводит треугольники полупрозрачной модели в пространство экрана и освещает их
+    fn translucent_triangles(
+        (width, height): (u32, u32),
         model: &dyn Model3D,
         camera: &Camera,
         light_source: &LightSource,
-    ) {
-        // TODO: organize this transformations
-        let (width, height) = image.dimensions();
-        let mvp_matrix = camera.camera_matrix * model.model_matrix();
-        let viewport_matrix = Matrix4::new(
-            width as f64 / 2.,
-            0.,
-            0.,
-            width as f64 / 2.,
-            0.,
-            -(height as f64 / 2.),
-            0.,
-            height as f64 / 2.,
-            0.,
-            0.,
-            1.,
-            0.,
-            0.,
-            0.,
-            0.,
-            1.,
-        );
-
-        let mvpv_matrix = viewport_matrix * mvp_matrix;
+        options: &RenderOptions,
+    ) -> Vec<TranslucentTriangle> {
+        let mvpv_matrix = ZBufferPerformer::calculate_viewport_matrix(width, height)
+            * camera.camera_matrix
+            * model.model_matrix();
         let camera_dim_v: Vec<Point3<f64>> = model
             .vertices()
             .iter()
@@ -96,49 +99,175 @@ impl TransparencyPerformer {
             })
             .collect();
 
-        for (i, tri) in model.triangles().iter().enumerate() {
-            let surface_point = &model.vertices_world()[tri.0];
-            let normal = if model.normals()[i]
-                .dot(&(light_source.pos - surface_point).to_homogeneous())
-                > 0.0
-            {
-                model.normals()[i]
-            } else {
-                model.normals()[i] * -1.
-            };
-
-            let color = calculate_color(
-                model.material(),
-                &normal.xyz(),
-                surface_point,
-                light_source,
-                &camera.pos,
-            );
+        model
+            .triangles()
+            .iter()
+            .enumerate()
+            .map(|(i, tri)| {
+                let surface_point = &model.vertices_world()[tri.0];
+                let normal = if model.normals()[i]
+                    .dot(&(light_source.pos - surface_point).to_homogeneous())
+                    > 0.0
+                {
+                    model.normals()[i]
+                } else {
+                    model.normals()[i] * -1.
+                };
 
-            self.draw_triangle(
-                image,
-                &[
+                // Плоская заливка: текстура выбирается в центре треугольника
+                let base_color = supermesh_origin_color(model, i, options)
+                    .or_else(|| model.sample_texture(i, &Vector3::repeat(1. / 3.)))
+                    .unwrap_or_else(|| model.material().base_color_at(&model.vertices()[tri.0]));
+
+                let color = calculate_color(
+                    model.material(),
+                    &normal.xyz(),
+                    surface_point,
+                    base_color,
+                    light_source,
+                    &camera.pos,
+                    options.specular_model,
+                );
+
+                let vertices = [
                     camera_dim_v[tri.0],
                     camera_dim_v[tri.1],
                     camera_dim_v[tri.2],
-                ],
-                Rgb([color[0], color[1], color[2]]),
-                model.material().opacity,
-            )
+                ];
+                TranslucentTriangle {
+                    vertices,
+                    color,
+                    alpha: model.material().opacity,
+                    depth: vertices.iter().map(|v| v.z).sum::<f64>() / 3.,
+                }
+            })
+            .collect()
+    }
+
+    fn draw_objects(
+        &mut self,
+        image: &mut RgbImage,
+        objects: &[&dyn Model3D],
+        camera: &Camera,
+        light_source: &LightSource,
+        options: &RenderOptions,
+    ) {
+        self.solid.begin_frame(image);
+
+        let (opaque, translucent): (Vec<&dyn Model3D>, Vec<&dyn Model3D>) = objects
+            .iter()
+            .copied()
+            .partition(|model| model.material().opacity >= 1.);
+
+        for model in opaque {
+            self.solid
+                .draw_opaque(image, model, camera, light_source, options);
+        }
+
+        let dimensions = image.dimensions();
+        let mut triangles: Vec<TranslucentTriangle> = translucent
+            .into_iter()
+            .flat_map(|model| {
+                Self::translucent_triangles(dimensions, model, camera, light_source, options)
+            })
+            .collect();
+        // Большая глубина в NDC - дальше от камеры; дальние смешиваются первыми
+        triangles.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+
+        for tri in &triangles {
+            self.draw_triangle(image, tri);
         }
     }
 }
 
 impl Renderer for TransparencyPerformer {
     fn create_frame_mut(&mut self, image: &mut RgbImage, scene: &Scene) {
-        image.fill(70);
-        if let Some(object) = scene.object.as_ref() {
-            self.draw_object(
+        let object = scene.object.as_ref().map(|object| object.borrow());
+        let objects: Vec<&dyn Model3D> = object
+            .iter()
+            .map(|object| &**object as &dyn Model3D)
+            .collect();
+        self.draw_objects(
+            image,
+            &objects,
+            &scene.camera,
+            &scene.light_source,
+            &scene.render_options,
+        );
+
+        // Выделение рисуется по глубине непрозрачных объектов, чтобы каркас
+        // полупрозрачного объекта оставался виден сквозь него самого
+        if let Some(object) = scene.selected_object() {
+            let depth = self.solid.depth_buffer();
+            draw_edges(
                 image,
+                depth,
                 object.borrow().deref(),
                 &scene.camera,
-                &scene.light_source,
+                SELECTION_COLOR,
             );
         }
     }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.solid.resize(width, height);
+    }
+
+    fn depth_buffer(&self) -> Option<&[f64]> {
+        Some(self.solid.depth_buffer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BACKGROUND_COLOR;
+    use crate::objects::triangle_mesh::TriangleMesh;
+
+    fn quad(z: f64) -> TriangleMesh {
+        let vertices = [(-10., -10.), (10., -10.), (10., 10.), (-10., 10.)]
+            .map(|(x, y)| Point3::new(x, y, z))
+            .to_vec();
+        let mut quad = TriangleMesh::from_parts(vertices, vec![(0, 1, 2), (0, 2, 3)], None);
+        quad.material.color = Rgb([40, 200, 40]);
+        quad.material.opacity = 0.5;
+        quad
+    }
+
+    #[test]
+    fn translucent_objects_blend_over_opaque_ones() {
+        let scene = Scene::new(1.);
+        let mut cube = TriangleMesh::cube();
+        cube.material.opacity = 1.;
+        let (behind, in_front) = (quad(-2.), quad(1.));
+        let (cube, behind, in_front): (&dyn Model3D, &dyn Model3D, &dyn Model3D) =
+            (&cube, &behind, &in_front);
+
+        let render = |objects: &[&dyn Model3D]| {
+            let mut renderer = TransparencyPerformer::new(64, 64);
+            let mut image = RgbImage::new(64, 64);
+            renderer.draw_objects(
+                &mut image,
+                objects,
+                &scene.camera,
+                &scene.light_source,
+                &scene.render_options,
+            );
+            image
+        };
+        let (center, corner) = ((32, 32), (1, 1));
+
+        let opaque = render(&[cube]);
+        assert_eq!(opaque[corner], BACKGROUND_COLOR);
+
+        // Полупрозрачный квадрат за кубом скрыт им и смешивается только с фоном
+        let with_behind = render(&[behind, cube]);
+        assert_eq!(with_behind[center], opaque[center]);
+        assert_ne!(with_behind[corner], BACKGROUND_COLOR);
+
+        // Квадрат перед кубом окрашивает его
+        let with_front = render(&[cube, in_front]);
+        assert_ne!(with_front[center], opaque[center]);
+        assert_ne!(with_front[corner], BACKGROUND_COLOR);
+    }
 }
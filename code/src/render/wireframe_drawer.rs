@@ -1,55 +1,68 @@
+use crate::config::BACKGROUND_COLOR;
 use crate::objects::camera::Camera;
 use crate::objects::model3d::Model3D;
-use crate::render::Renderer;
+use crate::render::z_buffer::ZBufferPerformer;
+use crate::render::{RenderOptions, Renderer};
 use crate::scene::Scene;
 use image::RgbImage;
 use imageproc::drawing::{draw_filled_rect_mut, draw_hollow_polygon_mut};
 use imageproc::point::Point;
 use imageproc::rect::Rect;
-use nalgebra::{Matrix4, Point3};
+use nalgebra::Point3;
+use std::ops::Deref;
 
-#[allow(dead_code)]
+/// Сторона маркера вершины - такая доля меньшей стороны кадра
+const MARKER_SIZE_DIVISOR: u32 = 250;
+/// Наименьшая сторона маркера вершины в пикселях
+const MIN_MARKER_SIZE: u32 = 3;
+
+/// Каркас без удаления невидимых линий; вершины отмечаются квадратами,
+/// если включён RenderOptions::vertex_markers
+#[derive(Default)]
 pub struct WireframePerformer;
+
 impl WireframePerformer {
-    fn draw_object(image: &mut RgbImage, camera: &Camera, model: &dyn Model3D) {
+    /// Сторона маркера вершины: растёт с разрешением, чтобы маркер был одинаково
+    /// заметен в маленьком и большом кадре
+    pub fn marker_size(width: u32, height: u32) -> u32 {
+        (width.min(height) / MARKER_SIZE_DIVISOR).max(MIN_MARKER_SIZE)
+    }
+
+    fn draw_object(
+        image: &mut RgbImage,
+        camera: &Camera,
+        model: &dyn Model3D,
+        options: &RenderOptions,
+    ) {
         let (width, height) = image.dimensions();
-        let viewport_matrix = Matrix4::new(
-            width as f64 / 2.,
-            0.,
-            0.,
-            width as f64 / 2.,
-            0.,
-            -(height as f64 / 2.),
-            0.,
-            height as f64 / 2.,
-            0.,
-            0.,
-            1.,
-            0.,
-            0.,
-            0.,
-            0.,
-            1.,
-        );
 
         // Calculate the MVPV matrix once
-        let mvp_matrix = camera.camera_matrix;
-        let mvpv_matrix = viewport_matrix * mvp_matrix;
+        let mvpv_matrix =
+            ZBufferPerformer::calculate_viewport_matrix(width, height) * camera.camera_matrix;
 
         // Transform the world-space vertices once
-        let camera_dim_v: Vec<Point3<f64>> = model
+        // Вершины в плоскости камеры (деление на w невозможно) и за плоскостями
+        // отсечения считаются невидимыми
+        let camera_dim_v: Vec<Option<Point3<f64>>> = model
             .vertices_world()
             .iter()
             .map(|v| {
                 Point3::from_homogeneous(mvpv_matrix * v.to_homogeneous())
-                    .expect("Perspective division failed.")
+                    .filter(|p| (-1.0..=1.0).contains(&p.z))
             })
             .collect();
 
+        // Треугольник с невидимой вершиной пропускается целиком: за камерой проекция
+        // переворачивается, и его рёбра пересекли бы весь кадр. Рёбра, частично
+        // выходящие за кадр, отсекает imageproc
         for tri in model.triangles() {
-            let v0 = camera_dim_v[tri.0];
-            let v1 = camera_dim_v[tri.1];
-            let v2 = camera_dim_v[tri.2];
+            let (Some(v0), Some(v1), Some(v2)) = (
+                camera_dim_v[tri.0],
+                camera_dim_v[tri.1],
+                camera_dim_v[tri.2],
+            ) else {
+                continue;
+            };
 
             draw_hollow_polygon_mut(
                 image,
@@ -62,21 +75,130 @@ impl WireframePerformer {
             );
         }
 
-        for v in &camera_dim_v {
+        if !options.vertex_markers {
+            return;
+        }
+
+        // Маркеры рисуются только для вершин внутри кадра, поэтому прямоугольник
+        // выходит за край не больше чем на половину своей стороны
+        let size = Self::marker_size(width, height);
+        let half = (size / 2) as i32;
+        let on_screen = |v: &Point3<f64>| {
+            (0. ..width as f64).contains(&v.x) && (0. ..height as f64).contains(&v.y)
+        };
+        for v in camera_dim_v.iter().flatten().filter(|v| on_screen(v)) {
             draw_filled_rect_mut(
                 image,
-                Rect::at(v.x.round() as i32 - 2, v.y.round() as i32 - 2).of_size(4, 4),
+                Rect::at(v.x as i32 - half, v.y as i32 - half).of_size(size, size),
                 model.material().color,
             );
         }
     }
 }
-//
-// impl Renderer for WireframePerformer {
-//     fn create_frame_mut(&mut self, image: &mut RgbImage, scene: &Scene) {
-//         image.fill(255);
-//         for model in &scene.objects {
-//             WireframePerformer::draw_object(image, &scene.camera, &**model);
-//         }
-//     }
-// }
+
+impl Renderer for WireframePerformer {
+    fn create_frame_mut(&mut self, image: &mut RgbImage, scene: &Scene) {
+        image.pixels_mut().for_each(|px| *px = BACKGROUND_COLOR);
+        for object in scene.objects() {
+            Self::draw_object(
+                image,
+                &scene.camera,
+                object.borrow().deref(),
+                &scene.render_options,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::model3d::InteractiveModel;
+    use crate::objects::triangle_mesh::TriangleMesh;
+    use nalgebra::Vector2;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const SIZE: u32 = 1000;
+
+    /// Расстояние от точки до отрезка `ab` на экране
+    fn distance_to_segment(p: Vector2<f64>, a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+        let t = ((p - a).dot(&(b - a)) / (b - a).norm_squared()).clamp(0., 1.);
+        (p - (a + (b - a) * t)).norm()
+    }
+
+    #[test]
+    fn disabled_markers_leave_only_edge_pixels() {
+        let vertices = vec![
+            Point3::new(-0.8, -0.6, 0.),
+            Point3::new(0.8, -0.6, 0.),
+            Point3::new(0., 0.7, 0.),
+        ];
+        let triangle = TriangleMesh::from_parts(vertices, vec![(0, 1, 2)], None);
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(triangle));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object.clone());
+
+        let mvpv_matrix =
+            ZBufferPerformer::calculate_viewport_matrix(SIZE, SIZE) * scene.camera.camera_matrix;
+        let corners: Vec<Vector2<f64>> = object
+            .borrow()
+            .vertices_world()
+            .iter()
+            .map(|v| {
+                Point3::from_homogeneous(mvpv_matrix * v.to_homogeneous())
+                    .unwrap()
+                    .xy()
+                    .coords
+            })
+            .collect();
+        let off_edge_pixels = |frame: &RgbImage| {
+            frame
+                .enumerate_pixels()
+                .filter(|&(_, _, &px)| px != BACKGROUND_COLOR)
+                .filter(|&(x, y, _)| {
+                    let p = Vector2::new(x as f64 + 0.5, y as f64 + 0.5);
+                    (0..3)
+                        .map(|i| distance_to_segment(p, corners[i], corners[(i + 1) % 3]))
+                        .fold(f64::INFINITY, f64::min)
+                        > 1.5
+                })
+                .count()
+        };
+
+        let plain = WireframePerformer.create_frame(SIZE, SIZE, &scene);
+        assert_eq!(off_edge_pixels(&plain), 0);
+
+        scene.render_options.vertex_markers = true;
+        let marked = WireframePerformer.create_frame(SIZE, SIZE, &scene);
+        assert!(off_edge_pixels(&marked) > 0, "маркеры вершин не нарисованы");
+    }
+
+    #[test]
+    fn model_partly_outside_frame_is_drawn_without_panic() {
+        let vertices = vec![
+            Point3::new(-0.5, -0.5, 0.),
+            Point3::new(0.5, -0.5, 0.),
+            // Далеко за правым краем кадра
+            Point3::new(50., 0.5, 0.),
+            // В плоскости камеры: перспективное деление невозможно
+            Point3::new(0., 0., 3.),
+            // Позади камеры
+            Point3::new(0.2, 0.2, 5.),
+        ];
+        let triangles = vec![(0, 1, 2), (0, 1, 3), (1, 2, 4)];
+        let mesh = TriangleMesh::from_parts(vertices, triangles, None);
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(mesh));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+        scene.render_options.vertex_markers = true;
+
+        let frame = WireframePerformer.create_frame(SIZE, SIZE, &scene);
+
+        let drawn = frame.pixels().filter(|&&px| px != BACKGROUND_COLOR).count();
+        assert!(drawn > 0, "видимый треугольник не нарисован");
+        // Сверху кадр пуст: туда попали бы только рёбра треугольников за камерой
+        let mut top_rows = frame.rows().take(SIZE as usize / 4).flatten();
+        assert!(top_rows.all(|&px| px == BACKGROUND_COLOR));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::render::{RenderTimings, Renderer};
+use crate::scene::SceneSnapshot;
+use image::RgbImage;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Запрос на отрисовку сцены в задний буфер
+pub struct FrameRequest {
+    pub scene: SceneSnapshot,
+    /// Буфер, в который рисуется кадр; его размер задаёт размер кадра
+    pub buffer: RgbImage,
+}
+
+/// Отрисованный кадр
+pub struct RenderedFrame {
+    pub image: RgbImage,
+    /// Буфер глубины кадра, если рендерер его ведёт
+    pub depth: Vec<f64>,
+    /// Время этапов отрисовки, если оно замерялось
+    pub timings: Option<RenderTimings>,
+    /// Время отрисовки кадра рендерером, без ожидания в очереди и синхронизации UI
+    pub render_time: Duration,
+}
+
+pub type SceneRenderWorker = FrameWorker<FrameRequest, RenderedFrame>;
+
+/// Запускает поток, отрисовывающий сцены переданным рендерером
+pub fn spawn_scene_renderer(mut renderer: impl Renderer + Send + 'static) -> SceneRenderWorker {
+    let mut size = None;
+    FrameWorker::spawn(move |request: FrameRequest| {
+        let scene = request.scene.into_scene();
+        let mut image = request.buffer;
+        // Буфер другого размера приходит после изменения размера области просмотра
+        if size != Some(image.dimensions()) {
+            let (width, height) = image.dimensions();
+            renderer.resize(width, height);
+            size = Some((width, height));
+        }
+        let render_start = Instant::now();
+        renderer.create_frame_mut(&mut image, &scene);
+        let render_time = render_start.elapsed();
+        let depth = renderer
+            .depth_buffer()
+            .map(<[f64]>::to_vec)
+            .unwrap_or_default();
+        let timings = renderer.timings().copied();
+        RenderedFrame {
+            image,
+            depth,
+            timings,
+            render_time,
+        }
+    })
+}
+
+/// Фоновый поток отрисовки кадров.
+///
+/// Одновременно отрисовывается не больше одного запроса: пока поток занят, новые
+/// запросы отклоняются, и вызывающая сторона повторяет их позже уже с актуальным
+/// состоянием сцены. Готовые кадры забираются без блокировки.
+pub struct FrameWorker<Request, Frame> {
+    requests: Option<Sender<Request>>,
+    frames: Receiver<Frame>,
+    handle: Option<JoinHandle<()>>,
+    busy: bool,
+}
+
+impl<Request, Frame> FrameWorker<Request, Frame>
+where
+    Request: Send + 'static,
+    Frame: Send + 'static,
+{
+    pub fn spawn(mut render: impl FnMut(Request) -> Frame + Send + 'static) -> Self {
+        let (request_sender, request_receiver) = mpsc::channel::<Request>();
+        let (frame_sender, frame_receiver) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            for request in request_receiver {
+                if frame_sender.send(render(request)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            requests: Some(request_sender),
+            frames: frame_receiver,
+            handle: Some(handle),
+            busy: false,
+        }
+    }
+
+    /// Поток занят отрисовкой предыдущего запроса
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    /// Отправляет запрос на отрисовку. Возвращает false, если поток занят или завершён.
+    pub fn submit(&mut self, request: Request) -> bool {
+        if self.busy {
+            return false;
+        }
+        let sent = self
+            .requests
+            .as_ref()
+            .is_some_and(|requests| requests.send(request).is_ok());
+        self.busy = sent;
+        sent
+    }
+
+    /// Самый свежий из готовых кадров, если он есть
+    pub fn poll(&mut self) -> Option<Frame> {
+        let frame = self.frames.try_iter().last();
+        if frame.is_some() {
+            self.busy = false;
+        }
+        frame
+    }
+}
+
+impl<Request, Frame> Drop for FrameWorker<Request, Frame> {
+    fn drop(&mut self) {
+        // Закрытие канала запросов завершает цикл потока
+        self.requests = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::z_buffer::ZBufferPerformer;
+    use crate::scene::Scene;
+
+    fn wait_frame<R, F>(worker: &mut FrameWorker<R, F>) -> F
+    where
+        R: Send + 'static,
+        F: Send + 'static,
+    {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(frame) = worker.poll() {
+                return frame;
+            }
+            assert!(Instant::now() < deadline, "кадр не получен");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn frames_are_handed_back_one_request_at_a_time() {
+        // Отрисовка ждёт разрешения, чтобы проверить поведение занятого потока
+        let (gate_sender, gate_receiver) = mpsc::channel::<()>();
+        let mut worker = FrameWorker::spawn(move |request: u32| {
+            gate_receiver.recv().unwrap();
+            request * 10
+        });
+
+        assert!(worker.poll().is_none());
+        assert!(worker.submit(1));
+        assert!(worker.is_busy());
+        assert!(
+            !worker.submit(2),
+            "занятый поток не принимает новые запросы"
+        );
+
+        gate_sender.send(()).unwrap();
+        assert_eq!(wait_frame(&mut worker), 10);
+        assert!(!worker.is_busy());
+
+        assert!(worker.submit(3));
+        gate_sender.send(()).unwrap();
+        assert_eq!(wait_frame(&mut worker), 30);
+        assert!(worker.poll().is_none());
+    }
+
+    #[test]
+    fn render_time_fits_into_frame_time() {
+        let mut worker = spawn_scene_renderer(ZBufferPerformer::new(32, 32));
+        let request = FrameRequest {
+            scene: Scene::new(1.).snapshot(),
+            buffer: RgbImage::new(32, 32),
+        };
+
+        let submitted = Instant::now();
+        assert!(worker.submit(request));
+        let frame = wait_frame(&mut worker);
+        let frame_time = submitted.elapsed();
+
+        assert!(frame.render_time > Duration::ZERO);
+        assert!(frame.render_time <= frame_time);
+    }
+}
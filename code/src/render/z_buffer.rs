@@ -1,18 +1,82 @@
-use crate::config::BACKGROUND_COLOR;
+use crate::config::{BACKGROUND_COLOR, NON_MANIFOLD_EDGE_COLOR, SELECTION_COLOR};
 use crate::objects::camera::Camera;
 use crate::objects::light::LightSource;
 use crate::objects::model3d::Model3D;
-use crate::render::{Renderer, calculate_color};
+use crate::render::hybrid::draw_edges;
+use crate::render::post::{apply_fog, apply_outline};
+use crate::render::{
+    RenderOptions, RenderTimings, Renderer, calculate_color, shading_normal, supermesh_origin_color,
+};
 use crate::scene::Scene;
+use crate::utils::math::for_each_transformed;
+use crate::utils::triangles::non_manifold_edges;
 use image::{Rgb, RgbImage};
-use nalgebra::{Matrix4, Point3};
+use imageproc::drawing::draw_line_segment_mut;
+use nalgebra::{Matrix4, Point3, Vector3};
 use std::ops::Deref;
+use std::time::{Duration, Instant};
 
-#[derive(Default)]
 pub struct ZBufferPerformer {
     width: u32,
     height: u32,
+    /// Глубина в нормализованных координатах устройства: [-1, 1] от ближней
+    /// до дальней плоскости отсечения, f64::INFINITY - пиксель фона
     z_buffer: Vec<f64>,
+    /// Время этапов последнего кадра, если включён RenderOptions::collect_timings
+    timings: Option<RenderTimings>,
+    /// Рисовать треугольники от ближних к дальним, чтобы перекрытые не освещались
+    depth_sorting: bool,
+    /// Число фрагментов последнего кадра, прошедших тест глубины и закрашенных
+    shaded_fragments: u64,
+    scratch: FrameScratch,
+}
+
+impl Default for ZBufferPerformer {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+/// Вершина в пространстве изображения
+#[derive(Clone, Copy, Debug)]
+struct ScreenVertex {
+    pos: Point3<f64>,
+    /// Величина 1/w однородной координаты после проекции; атрибуты, делённые на w,
+    /// линейны в пространстве экрана
+    inv_w: f64,
+}
+
+/// Буферы отрисовки объекта, сохраняемые между кадрами: после первых кадров
+/// их ёмкости хватает, и память на каждый кадр не выделяется
+#[derive(Default)]
+struct FrameScratch {
+    screen_vertices: Vec<ScreenVertex>,
+    /// Порядок отрисовки треугольников
+    order: Vec<usize>,
+    /// Глубина ближайшей вершины каждого треугольника
+    nearest_depth: Vec<f64>,
+}
+
+/// Секундомер этапов отрисовки; выключенный не обращается к часам
+struct Stopwatch {
+    last: Option<Instant>,
+}
+
+impl Stopwatch {
+    fn new(enabled: bool) -> Self {
+        Self {
+            last: enabled.then(Instant::now),
+        }
+    }
+
+    /// Добавляет к этапу время, прошедшее с предыдущей отметки
+    fn lap(&mut self, stage: &mut Duration) {
+        if let Some(last) = self.last.as_mut() {
+            let now = Instant::now();
+            *stage += now - *last;
+            *last = now;
+        }
+    }
 }
 
 impl ZBufferPerformer {
@@ -21,15 +85,74 @@ impl ZBufferPerformer {
             width,
             height,
             z_buffer: vec![f64::INFINITY; (width * height) as usize],
+            timings: None,
+            depth_sorting: true,
+            shaded_fragments: 0,
+            scratch: FrameScratch::default(),
         }
     }
 
-    fn reset(&mut self, width: u32, height: u32) {
-        self.width = width;
-        self.height = height;
-        self.z_buffer
-            .resize((width * height) as usize, f64::INFINITY);
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Буфер глубины последнего кадра размером `width * height`, построчно.
+    /// Меньшее значение - ближе к камере, f64::INFINITY - пиксель фона
+    pub fn depth_buffer(&self) -> &[f64] {
+        &self.z_buffer
+    }
+
+    /// Включает сортировку треугольников от ближних к дальним (включена по умолчанию)
+    pub fn set_depth_sorting(&mut self, enabled: bool) {
+        self.depth_sorting = enabled;
+    }
+
+    /// Число закрашенных фрагментов последнего кадра; при перерисовке одного пикселя
+    /// несколькими треугольниками учитывается каждый
+    pub fn shaded_fragments(&self) -> u64 {
+        self.shaded_fragments
+    }
+
+    /// Начинает кадр: заливает изображение цветом фона и очищает буфер глубины
+    pub(super) fn begin_frame(&mut self, image: &mut RgbImage) {
+        // Без Renderer::resize буфер глубины меньше кадра, и растеризация вышла бы за
+        // его границы. Подгоняем размер здесь, чтобы такой вызов не портил память кадра
+        let (width, height) = image.dimensions();
+        if (width, height) != (self.width, self.height) {
+            Renderer::resize(self, width, height);
+        }
+        image.pixels_mut().for_each(|px| *px = BACKGROUND_COLOR);
+        self.reset();
+    }
+
+    /// Рисует модель в текущий кадр, не очищая буфер глубины
+    pub(super) fn draw_opaque(
+        &mut self,
+        image: &mut RgbImage,
+        model: &dyn Model3D,
+        camera: &Camera,
+        light_source: &LightSource,
+        options: &RenderOptions,
+    ) {
+        self.draw_object(
+            image,
+            model,
+            camera,
+            light_source,
+            options,
+            &mut Stopwatch::new(false),
+            &mut RenderTimings::default(),
+        );
+    }
+
+    /// Очищает буфер глубины, не меняя его размер
+    fn reset(&mut self) {
         self.z_buffer.fill(f64::INFINITY);
+        self.shaded_fragments = 0;
     }
 
     /// Устанавливает значение глубины в указанных координатах.
@@ -47,7 +170,7 @@ impl ZBufferPerformer {
     /// Вычисляет матрицу преобразования вьюпорта для заданных размеров изображения.
     ///
     /// Матрица преобразует нормализованные координаты устройства (NDC) в пространство экрана.
-    fn calculate_viewport_matrix(width: u32, height: u32) -> Matrix4<f64> {
+    pub(crate) fn calculate_viewport_matrix(width: u32, height: u32) -> Matrix4<f64> {
         Matrix4::new(
             width as f64 / 2.,
             0.,
@@ -68,112 +191,306 @@ impl ZBufferPerformer {
         )
     }
 
-    /// Преобразует вершины модели в пространство изображения.
+    /// Преобразует вершины модели в пространство изображения, заменяя содержимое `out`.
     ///
     /// Применяет последовательность преобразований: модель -> вид -> проекция -> вьюпорт.
     fn transform_vertices_to_screen(
         vertices: &[Point3<f64>],
         mvpv_matrix: &Matrix4<f64>,
-    ) -> Vec<Point3<f64>> {
-        vertices
-            .iter()
-            .map(|v| {
-                Point3::from_homogeneous(mvpv_matrix * v.to_homogeneous())
-                    .expect("Perspective division failed.")
+        out: &mut Vec<ScreenVertex>,
+    ) {
+        out.clear();
+        for_each_transformed(mvpv_matrix, vertices, |clip| {
+            out.push(ScreenVertex {
+                pos: Point3::from_homogeneous(clip).expect("Perspective division failed."),
+                inv_w: 1. / clip.w,
             })
-            .collect()
+        });
     }
 
+    /// Растеризует треугольник с интерполяцией цветов вершин. Если задана текстурированная
+    /// модель и индекс грани, интерполированный цвет освещения умножается на тексель.
+    ///
+    /// Пиксели на границе треугольника отбираются по правилу верхнего левого ребра:
+    /// пиксель на общем ребре соседних треугольников закрашивается ровно одним из них.
+    ///
+    /// Тест глубины выполняется до закрашивания: цвета вершин вычисляются `shade` только
+    /// при первом видимом фрагменте, полностью перекрытый треугольник не освещается.
+    ///
+    /// Глубина в NDC линейна в пространстве экрана и интерполируется по экранным
+    /// барицентрическим координатам, а цвета и текстурные координаты - перспективно-корректно,
+    /// с весами, делёнными на w вершин.
     fn draw_triangle(
         &mut self,
         image: &mut RgbImage,
-        tri: &[Point3<f64>; 3],
-        tri_colors: &[Rgb<u8>; 3],
+        tri: &[ScreenVertex; 3],
+        mut shade: impl FnMut() -> [Rgb<u8>; 3],
+        texture: Option<(&dyn Model3D, usize)>,
     ) {
-        let [p1, p2, p3] = *tri;
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
 
-        // Находим ограничивающий прямоугольник, ограничивая размерами изображения.
-        let min_x = p1.x.min(p2.x).min(p3.x).round() as u32;
-        let max_x = (p1.x.max(p2.x).max(p3.x).round() as u32).min(self.width - 1);
-        let min_y = p1.y.min(p2.y).min(p3.y).round() as u32;
-        let max_y = (p1.y.max(p2.y).max(p3.y).round() as u32).min(self.height - 1);
+        let [p1, p2, p3] = tri.map(|v| v.pos);
+        let inv_w = tri.map(|v| v.inv_w);
 
-        // Предварительно вычисляем общие компоненты, чтобы избежать избыточных вычислений в цикле.
-        let denom = (p2.x - p1.x) * (p3.y - p1.y) - (p2.y - p1.y) * (p3.x - p1.x);
+        // Находим пиксели, центры которых попадают в ограничивающий прямоугольник,
+        // ограничивая размерами изображения.
+        let (min_x, max_x) = pixel_span(p1.x.min(p2.x).min(p3.x), p1.x.max(p2.x).max(p3.x));
+        let (min_y, max_y) = pixel_span(p1.y.min(p2.y).min(p3.y), p1.y.max(p2.y).max(p3.y));
+        let max_x = max_x.min(self.width - 1);
+        let max_y = max_y.min(self.height - 1);
+
+        // Удвоенная ориентированная площадь; вырожденный треугольник не закрашивает пикселей
+        let denom = edge_function(&p1, &p2, p3.x, p3.y);
+        if denom == 0. || !denom.is_finite() {
+            return;
+        }
+
+        // Рёбра, противолежащие вершинам, обходятся так, чтобы внутренность треугольника
+        // была с положительной стороны при любом порядке вершин
+        let edges =
+            [(p2, p3), (p3, p1), (p1, p2)].map(|(a, b)| if denom > 0. { (a, b) } else { (b, a) });
+        let area = denom.abs();
+        let mut tri_colors = None;
 
         for y in min_y..=max_y {
             for x in min_x..=max_x {
-                // Вычисляем барицентрические координаты.
-                let u =
-                    ((p3.x - p2.x) * (y as f64 - p2.y) - (p3.y - p2.y) * (x as f64 - p2.x)) / denom;
-                let v =
-                    ((p1.x - p3.x) * (y as f64 - p3.y) - (p1.y - p3.y) * (x as f64 - p3.x)) / denom;
-
-                let bary = Point3::new(u, v, 1.0 - u - v);
+                // Покрытие проверяется в центре пикселя
+                let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                let weights = edges.map(|(a, b)| edge_function(&a, &b, px, py));
 
                 // Проверяем, находится ли пиксель внутри треугольника.
-                if bary.x > -f64::EPSILON && bary.y > -f64::EPSILON && bary.z > -f64::EPSILON {
+                let inside = edges
+                    .iter()
+                    .zip(weights)
+                    .all(|(&(a, b), w)| w > 0. || (w == 0. && is_top_left(&a, &b)));
+                if inside {
+                    // Барицентрические координаты
+                    let bary = Point3::from(weights.map(|w| w / area));
                     let z = p1.z * bary.x + p2.z * bary.y + p3.z * bary.z;
 
                     // Выполняем проверку по Z-буферу.
                     if z < self.get_depth(x, y) {
                         self.set_depth(x, y, z);
+                        self.shaded_fragments += 1;
+
+                        let tri_colors = *tri_colors.get_or_insert_with(&mut shade);
+                        let bary = perspective_correct(&bary.coords, &inv_w);
+
+                        let texel =
+                            texture.and_then(|(model, face)| model.sample_texture(face, &bary));
 
                         // Интерполируем цвета корректно для каждого канала.
-                        let r = (bary.x * tri_colors[0].0[0] as f64
-                            + bary.y * tri_colors[1].0[0] as f64
-                            + bary.z * tri_colors[2].0[0] as f64)
-                            .clamp(0.0, 255.0) as u8;
-                        let g = (bary.x * tri_colors[0].0[1] as f64
-                            + bary.y * tri_colors[1].0[1] as f64
-                            + bary.z * tri_colors[2].0[1] as f64)
-                            .clamp(0.0, 255.0) as u8;
-                        let b = (bary.x * tri_colors[0].0[2] as f64
-                            + bary.y * tri_colors[1].0[2] as f64
-                            + bary.z * tri_colors[2].0[2] as f64)
-                            .clamp(0.0, 255.0) as u8;
-
-                        image.put_pixel(x, y, Rgb([r, g, b]));
+                        let color = [0, 1, 2].map(|c| {
+                            let lit = bary.x * tri_colors[0].0[c] as f64
+                                + bary.y * tri_colors[1].0[c] as f64
+                                + bary.z * tri_colors[2].0[c] as f64;
+                            let albedo = texel.map_or(1., |texel| texel.0[c] as f64 / 255.);
+                            (lit * albedo).clamp(0.0, 255.0) as u8
+                        });
+
+                        image.put_pixel(x, y, Rgb(color));
                     }
                 }
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn draw_object(
         &mut self,
         image: &mut RgbImage,
         model: &dyn Model3D,
         camera: &Camera,
         light_source: &LightSource,
+        options: &RenderOptions,
+        stopwatch: &mut Stopwatch,
+        timings: &mut RenderTimings,
     ) {
         let (width, height) = image.dimensions();
         let mvp_matrix = camera.camera_matrix * model.model_matrix();
         let viewport_matrix = Self::calculate_viewport_matrix(width, height);
         let mvpv_matrix = viewport_matrix * mvp_matrix;
 
-        let screen_vertices: Vec<Point3<f64>> =
-            Self::transform_vertices_to_screen(model.vertices(), &mvpv_matrix);
-
-        for (i, tri) in model.triangles().iter().enumerate() {
-            let tri_colors = [tri.0, tri.1, tri.2].map(|v_idx| {
-                calculate_color(
-                    model.material(),
-                    &model.normals()[i].xyz(),
-                    &model.vertices_world()[v_idx],
-                    light_source,
-                    &camera.pos,
-                )
+        let mut scratch = std::mem::take(&mut self.scratch);
+        Self::transform_vertices_to_screen(
+            model.vertices(),
+            &mvpv_matrix,
+            &mut scratch.screen_vertices,
+        );
+        let triangles = model.triangles();
+
+        // Разнесённый вид: вершины каждого треугольника сдвигаются вдоль его нормали,
+        // поэтому у треугольников больше нет общих вершин
+        let exploded: Option<Vec<ScreenVertex>> = (options.explode_factor > 0.).then(|| {
+            let vp_matrix = viewport_matrix * camera.camera_matrix;
+            let world: Vec<Point3<f64>> = triangles
+                .iter()
+                .zip(model.normals())
+                .flat_map(|(tri, normal)| {
+                    let offset = normal.xyz() * options.explode_factor;
+                    [tri.0, tri.1, tri.2].map(|v_idx| model.vertices_world()[v_idx] + offset)
+                })
+                .collect();
+            let mut exploded = Vec::with_capacity(world.len());
+            Self::transform_vertices_to_screen(&world, &vp_matrix, &mut exploded);
+            exploded
+        });
+        let corners = |i: usize| -> [ScreenVertex; 3] {
+            match &exploded {
+                Some(exploded) => [exploded[3 * i], exploded[3 * i + 1], exploded[3 * i + 2]],
+                None => {
+                    let tri = triangles[i];
+                    let screen = &scratch.screen_vertices;
+                    [screen[tri.0], screen[tri.1], screen[tri.2]]
+                }
+            }
+        };
+        stopwatch.lap(&mut timings.transform);
+
+        let occlusion = if options.ambient_occlusion {
+            model.ambient_occlusion()
+        } else {
+            None
+        };
+
+        stopwatch.lap(&mut timings.shading);
+
+        // Ближние треугольники рисуются первыми, и фрагменты дальних отсекаются тестом
+        // глубины до освещения. Треугольники одной глубины рисуются в исходном порядке;
+        // сортировка без выделения памяти, поэтому порядок задаётся индексом явно
+        scratch.order.clear();
+        scratch.order.extend(0..triangles.len());
+        if self.depth_sorting {
+            scratch.nearest_depth.clear();
+            scratch.nearest_depth.extend((0..triangles.len()).map(|i| {
+                let z = corners(i).map(|v| v.pos.z);
+                z[0].min(z[1]).min(z[2])
+            }));
+            let nearest_depth = &scratch.nearest_depth;
+            scratch.order.sort_unstable_by(|&a, &b| {
+                nearest_depth[a]
+                    .total_cmp(&nearest_depth[b])
+                    .then(a.cmp(&b))
             });
+        }
+        stopwatch.lap(&mut timings.rasterization);
+
+        let material = model.material();
+        let textured = model.has_texture();
+        let vertex_normals = model.vertex_normals();
+
+        for &i in &scratch.order {
+            let tri = &triangles[i];
+            let origin_color = supermesh_origin_color(model, i, options);
+            let texture = (textured && origin_color.is_none()).then_some((model, i));
 
-            self.draw_triangle(
+            let shade = || {
+                stopwatch.lap(&mut timings.rasterization);
+                let tri_colors = [tri.0, tri.1, tri.2].map(|v_idx| {
+                    // Для текстурированных граней освещается белый цвет, а текстура
+                    // накладывается попиксельно при растеризации
+                    let base_color = match (origin_color, texture) {
+                        (Some(color), _) => color,
+                        (None, Some(_)) => Rgb([255, 255, 255]),
+                        (None, None) => material.base_color_at(&model.vertices()[v_idx]),
+                    };
+                    let surface_point = &model.vertices_world()[v_idx];
+                    let normal = vertex_normals.map_or(model.normals()[i], |n| n[v_idx]);
+                    let normal = shading_normal(
+                        &normal.xyz(),
+                        surface_point,
+                        &camera.pos,
+                        options.two_sided,
+                    );
+                    let color = calculate_color(
+                        material,
+                        &normal,
+                        surface_point,
+                        base_color,
+                        light_source,
+                        &camera.pos,
+                        options.specular_model,
+                    );
+
+                    match occlusion {
+                        Some(ao) => Rgb(color.0.map(|c| (c as f64 * ao[v_idx]).round() as u8)),
+                        None => color,
+                    }
+                });
+                stopwatch.lap(&mut timings.shading);
+                tri_colors
+            };
+
+            self.draw_triangle(image, &corners(i), shade, texture);
+            stopwatch.lap(&mut timings.rasterization);
+        }
+
+        self.scratch = scratch;
+    }
+}
+
+/// Диапазон пикселей, центры которых лежат на отрезке [min, max] экранной оси.
+/// Пустой диапазон, если max < 0 или отрезок не содержит ни одного центра.
+pub(crate) fn pixel_span(min: f64, max: f64) -> (u32, u32) {
+    let first = (min - 0.5).ceil().max(0.);
+    let last = (max - 0.5).floor();
+    if last < first {
+        return (1, 0);
+    }
+    (first as u32, last as u32)
+}
+
+/// Переводит экранные барицентрические координаты в барицентрические координаты
+/// на треугольнике в пространстве сцены
+fn perspective_correct(bary: &Vector3<f64>, inv_w: &[f64; 3]) -> Vector3<f64> {
+    let weighted = bary.component_mul(&Vector3::from(*inv_w));
+    let sum = weighted.sum();
+    if sum.abs() < f64::EPSILON {
+        return *bary;
+    }
+    weighted / sum
+}
+
+/// Функция ребра a→b в точке (x, y): удвоенная ориентированная площадь треугольника.
+/// Вычисляется от упорядоченных концов ребра, поэтому для общего ребра соседних
+/// треугольников значения совпадают точно, с точностью до знака.
+fn edge_function(a: &Point3<f64>, b: &Point3<f64>, x: f64, y: f64) -> f64 {
+    let (from, to, sign) = if (a.y, a.x) <= (b.y, b.x) {
+        (a, b, 1.)
+    } else {
+        (b, a, -1.)
+    };
+    sign * ((to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x))
+}
+
+/// Ребро a→b (внутренность треугольника с положительной стороны) является верхним
+/// горизонтальным или левым; пиксели на таких рёбрах принадлежат треугольнику.
+/// Для того же ребра, обойдённого в обратном направлении, условие ложно.
+fn is_top_left(a: &Point3<f64>, b: &Point3<f64>) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    dy < 0. || (dy == 0. && dx > 0.)
+}
+
+impl ZBufferPerformer {
+    /// Рисует поверх изображения рёбра, нарушающие многообразие
+    fn draw_non_manifold_edges(image: &mut RgbImage, model: &dyn Model3D, camera: &Camera) {
+        let (width, height) = image.dimensions();
+        let mvpv_matrix = Self::calculate_viewport_matrix(width, height)
+            * camera.camera_matrix
+            * model.model_matrix();
+        let mut screen_vertices = Vec::new();
+        Self::transform_vertices_to_screen(model.vertices(), &mvpv_matrix, &mut screen_vertices);
+
+        for (a, b) in non_manifold_edges(model.triangles()) {
+            let start = screen_vertices[a].pos;
+            let end = screen_vertices[b].pos;
+            draw_line_segment_mut(
                 image,
-                &[
-                    screen_vertices[tri.0],
-                    screen_vertices[tri.1],
-                    screen_vertices[tri.2],
-                ],
-                &tri_colors,
+                (start.x as f32, start.y as f32),
+                (end.x as f32, end.y as f32),
+                NON_MANIFOLD_EDGE_COLOR,
             );
         }
     }
@@ -181,17 +498,361 @@ impl ZBufferPerformer {
 
 impl Renderer for ZBufferPerformer {
     fn create_frame_mut(&mut self, image: &mut RgbImage, scene: &Scene) {
-        let (width, height) = image.dimensions();
-        image.pixels_mut().for_each(|px| *px = BACKGROUND_COLOR);
+        let collect_timings = scene.render_options.collect_timings;
+        let frame_start = Instant::now();
+        let mut stopwatch = Stopwatch::new(collect_timings);
+        let mut timings = RenderTimings::default();
+
+        self.begin_frame(image);
+        stopwatch.lap(&mut timings.rasterization);
+
+        // Свёрнутая до нуля область просмотра: рисовать некуда
+        if self.width == 0 || self.height == 0 {
+            self.timings = None;
+            return;
+        }
 
         if let Some(object) = scene.object.as_ref() {
-            self.reset(width, height);
             self.draw_object(
                 image,
                 object.borrow().deref(),
                 &scene.camera,
                 &scene.light_source,
+                &scene.render_options,
+                &mut stopwatch,
+                &mut timings,
+            );
+        }
+
+        if scene.render_options.outline_enabled {
+            apply_outline(
+                image,
+                &self.z_buffer,
+                &scene.camera,
+                &scene.render_options.outline,
             );
         }
+
+        if scene.render_options.fog_enabled {
+            apply_fog(
+                image,
+                &self.z_buffer,
+                &scene.camera,
+                &scene.render_options.fog,
+            );
+        }
+
+        let overlay_object = scene
+            .object
+            .as_ref()
+            .filter(|_| scene.render_options.show_non_manifold_edges);
+        if let Some(object) = overlay_object {
+            Self::draw_non_manifold_edges(image, object.borrow().deref(), &scene.camera);
+        }
+
+        if let Some(object) = scene.selected_object() {
+            let model = object.borrow();
+            draw_edges(
+                image,
+                &self.z_buffer,
+                model.deref(),
+                &scene.camera,
+                SELECTION_COLOR,
+            );
+        }
+        stopwatch.lap(&mut timings.post_processing);
+
+        self.timings = collect_timings.then(|| RenderTimings {
+            total: frame_start.elapsed(),
+            ..timings
+        });
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.z_buffer
+            .resize((width * height) as usize, f64::INFINITY);
+    }
+
+    fn depth_buffer(&self) -> Option<&[f64]> {
+        Some(ZBufferPerformer::depth_buffer(self))
+    }
+
+    fn timings(&self) -> Option<&RenderTimings> {
+        self.timings.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::model3d::InteractiveModel;
+    use crate::objects::triangle_mesh::TriangleMesh;
+    use crate::utils::testing::TempDir;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn depth_buffer_stores_object_depth() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/models/cube.obj");
+        let object: Rc<RefCell<dyn InteractiveModel>> =
+            Rc::new(RefCell::new(TriangleMesh::from_obj(path).unwrap()));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+
+        let mut renderer = ZBufferPerformer::new(64, 64);
+        renderer.create_frame(64, 64, &scene);
+
+        let depth = renderer.depth_buffer();
+        assert_eq!(depth.len(), (renderer.width() * renderer.height()) as usize);
+
+        let center = depth[32 * 64 + 32];
+        let corner = depth[0];
+        assert!((-1.0..=1.0).contains(&center), "center depth: {}", center);
+        assert!(center < corner);
+        assert_eq!(corner, f64::INFINITY);
+    }
+
+    #[test]
+    fn zero_size_image_is_left_empty() {
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(TriangleMesh::cube()));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+        scene.render_options.outline_enabled = true;
+        scene.render_options.fog_enabled = true;
+
+        let mut renderer = ZBufferPerformer::new(64, 64);
+        for (width, height) in [(0, 0), (0, 64), (64, 0)] {
+            let frame = renderer.create_frame(width, height, &scene);
+            assert_eq!(frame.dimensions(), (width, height));
+            assert!(renderer.depth_buffer().is_empty());
+        }
+    }
+
+    #[test]
+    fn resize_changes_buffer_length_and_clearing_keeps_it() {
+        let mut renderer = ZBufferPerformer::new(32, 16);
+        renderer.resize(64, 48);
+        assert_eq!(renderer.depth_buffer().len(), 64 * 48);
+
+        let buffer = renderer.depth_buffer().as_ptr();
+        let mut image = RgbImage::new(64, 48);
+        for _ in 0..2 {
+            renderer.create_frame_mut(&mut image, &Scene::new(64. / 48.));
+            assert_eq!(renderer.depth_buffer().len(), 64 * 48);
+            assert_eq!(renderer.depth_buffer().as_ptr(), buffer);
+            assert!(renderer.depth_buffer().iter().all(|&z| z == f64::INFINITY));
+        }
+    }
+
+    #[test]
+    fn frame_of_other_size_resizes_depth_buffer() {
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(TriangleMesh::cube()));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+
+        let mut renderer = ZBufferPerformer::new(16, 16);
+        let mut image = RgbImage::new(64, 64);
+        renderer.create_frame_mut(&mut image, &scene);
+        assert_eq!((renderer.width(), renderer.height()), (64, 64));
+        assert_eq!(renderer.depth_buffer().len(), 64 * 64);
+        assert!(renderer.depth_buffer().iter().any(|z| z.is_finite()));
+    }
+
+    #[test]
+    fn stage_timings_add_up_to_frame_time() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/models/cube.obj");
+        let object: Rc<RefCell<dyn InteractiveModel>> =
+            Rc::new(RefCell::new(TriangleMesh::from_obj(path).unwrap()));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+
+        let mut renderer = ZBufferPerformer::new(256, 256);
+        renderer.create_frame(256, 256, &scene);
+        assert!(
+            renderer.timings().is_none(),
+            "замеры выключены по умолчанию"
+        );
+
+        scene.render_options.collect_timings = true;
+        renderer.create_frame(256, 256, &scene);
+        let timings = *renderer.timings().unwrap();
+
+        let stages = timings.stages();
+        assert!(stages <= timings.total, "{:?}", timings);
+        assert!(timings.total - stages < timings.total / 10, "{:?}", timings);
+        assert!(timings.rasterization > Duration::ZERO);
+    }
+
+    #[test]
+    fn front_to_back_order_skips_occluded_fragments() {
+        // Дальний квадрат перечислен первым
+        let quad = |z: f64| {
+            [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)].map(|(x, y)| Point3::new(x, y, z))
+        };
+        let vertices = [quad(-0.5), quad(0.5)].concat();
+        let triangles = vec![(0, 1, 2), (0, 2, 3), (4, 5, 6), (4, 6, 7)];
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(
+            TriangleMesh::from_parts(vertices, triangles, None),
+        ));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+
+        let mut renderer = ZBufferPerformer::new(64, 64);
+        renderer.set_depth_sorting(false);
+        let unsorted = renderer.create_frame(64, 64, &scene);
+        let unsorted_fragments = renderer.shaded_fragments();
+
+        renderer.set_depth_sorting(true);
+        let sorted = renderer.create_frame(64, 64, &scene);
+        let sorted_fragments = renderer.shaded_fragments();
+
+        assert_eq!(sorted, unsorted);
+        assert!(
+            sorted_fragments < unsorted_fragments,
+            "{} >= {}",
+            sorted_fragments,
+            unsorted_fragments
+        );
+    }
+
+    fn coverage(tri: [(f64, f64); 3]) -> RgbImage {
+        let mut renderer = ZBufferPerformer::new(32, 32);
+        let mut image = RgbImage::from_pixel(32, 32, BACKGROUND_COLOR);
+        let tri = tri.map(|(x, y)| ScreenVertex {
+            pos: Point3::new(x, y, 0.),
+            inv_w: 1.,
+        });
+        renderer.draw_triangle(&mut image, &tri, || [Rgb([255, 0, 0]); 3], None);
+        image
+    }
+
+    #[test]
+    fn colors_are_interpolated_perspective_correctly() {
+        // Вершина B в четыре раза дальше от камеры, чем A и C
+        let vertex = |x, y, w: f64| ScreenVertex {
+            pos: Point3::new(x, y, 0.),
+            inv_w: 1. / w,
+        };
+        let tri = [vertex(0., 0., 1.), vertex(64., 0., 4.), vertex(0., 64., 1.)];
+        let colors = [Rgb([0, 0, 0]), Rgb([255, 255, 255]), Rgb([0, 0, 0])];
+
+        let mut renderer = ZBufferPerformer::new(64, 64);
+        let mut image = RgbImage::from_pixel(64, 64, BACKGROUND_COLOR);
+        renderer.draw_triangle(&mut image, &tri, || colors, None);
+
+        // Середина ребра AB на экране ближе к A в пространстве сцены
+        let screen_t = 31.5 / 64.;
+        let scene_t = screen_t / 4. / (1. - screen_t + screen_t / 4.);
+        let actual = image.get_pixel(31, 0).0[0] as f64;
+        assert!(
+            (actual - 255. * scene_t).abs() <= 1.,
+            "{} != {}",
+            actual,
+            255. * scene_t
+        );
+        assert!((actual - 255. * screen_t).abs() > 50.);
+    }
+
+    #[test]
+    fn shared_edge_pixels_are_drawn_exactly_once() {
+        // Квадрат из двух треугольников; диагональ проходит точно через центры пикселей
+        let (a, b, c, d) = ((4., 4.), (28., 4.), (28., 28.), (4., 28.));
+        let halves = [[a, b, c], [a, c, d]].map(coverage);
+
+        for y in 0..32 {
+            for x in 0..32 {
+                let writes = halves
+                    .iter()
+                    .filter(|image| *image.get_pixel(x, y) != BACKGROUND_COLOR)
+                    .count();
+                let interior = (5..28).contains(&x) && (5..28).contains(&y);
+                if interior {
+                    assert_eq!(writes, 1, "пиксель ({}, {})", x, y);
+                } else {
+                    assert!(writes <= 1, "пиксель ({}, {})", x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn half_image_triangles_cover_expected_columns() {
+        let left = coverage([(16., -32.), (16., 64.), (-64., 16.)]);
+        let right = coverage([(16., -32.), (96., 16.), (16., 64.)]);
+
+        for y in 0..32 {
+            for x in 0..32 {
+                let in_left = *left.get_pixel(x, y) != BACKGROUND_COLOR;
+                let in_right = *right.get_pixel(x, y) != BACKGROUND_COLOR;
+                assert_eq!(in_left, x < 16, "пиксель ({}, {})", x, y);
+                assert_eq!(in_right, x >= 16, "пиксель ({}, {})", x, y);
+            }
+        }
+
+        // Треугольник, симметричный относительно центра изображения, закрашивает
+        // зеркально симметричные столбцы
+        let centered = coverage([(16., 4.), (4., 28.), (28., 28.)]);
+        for y in 0..32 {
+            for x in 0..16 {
+                assert_eq!(
+                    centered.get_pixel(x, y),
+                    centered.get_pixel(31 - x, y),
+                    "пиксель ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn textured_quad_samples_center_texel() {
+        let obj = "v -1 -1 0\nv 1 -1 0\nv 1 1 0\nv -1 1 0\n\
+                   vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nvn 0 0 1\n\
+                   f 1/1/1 2/2/1 3/3/1\nf 1/1/1 3/3/1 4/4/1\n";
+        let dir = TempDir::new("textured_quad");
+        let path = dir.join("textured_quad.obj");
+        std::fs::write(&path, obj).unwrap();
+
+        let mut mesh = TriangleMesh::from_obj(&path.to_string_lossy()).unwrap();
+        let mut texture = RgbImage::from_pixel(3, 3, Rgb([0, 0, 255]));
+        texture.put_pixel(1, 1, Rgb([255, 0, 0]));
+        mesh.material.texture = Some(Arc::new(texture));
+
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(mesh));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+
+        let frame = ZBufferPerformer::new(64, 64).create_frame(64, 64, &scene);
+        assert_eq!(*frame.get_pixel(32, 32), Rgb([255, 0, 0]));
+        assert_eq!(frame.get_pixel(22, 22).0[0], 0);
+    }
+
+    #[test]
+    fn exploded_triangles_move_outward() {
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(TriangleMesh::cube()));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object);
+        let render = |scene: &Scene| ZBufferPerformer::new(64, 64).create_frame(64, 64, scene);
+        // Ширина занятой объектом области по горизонтали
+        let extent = |frame: &RgbImage| {
+            let columns: Vec<u32> = frame
+                .enumerate_pixels()
+                .filter(|&(_, _, &px)| px != BACKGROUND_COLOR)
+                .map(|(x, _, _)| x)
+                .collect();
+            columns.iter().max().unwrap() - columns.iter().min().unwrap()
+        };
+
+        let plain = render(&scene);
+        scene.render_options.explode_factor = 0.;
+        assert_eq!(render(&scene), plain);
+
+        scene.render_options.explode_factor = 0.3;
+        let exploded = render(&scene);
+        assert!(extent(&exploded) > extent(&plain));
     }
 }
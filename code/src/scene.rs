@@ -1,11 +1,212 @@
+use crate::config::{FAR_PLANE, FOV_DEGREES, NEAR_PLANE};
 use crate::objects::camera::Camera;
-use crate::objects::light::LightSource;
-use crate::objects::model3d::InteractiveModel;
+use crate::objects::light::{Attenuation, LightSource};
+use crate::objects::model3d::{InteractiveModel, Model3D, ModelSnapshot};
+use crate::render::RenderOptions;
+use crate::render::z_buffer::ZBufferPerformer;
+use crate::utils::math::union_bounds;
+use crate::utils::triangles::ray_triangle_intersect;
+use image::Rgb;
+use imageproc::definitions::HasWhite;
+use nalgebra::{Point3, Vector3, Vector4};
 use std::cell::RefCell;
+use std::ops::Deref;
 use std::rc::Rc;
 
 pub struct Scene {
     pub camera: Camera,
     pub light_source: LightSource,
     pub object: Option<Rc<RefCell<dyn InteractiveModel>>>,
+    /// Индекс выделенного объекта (см. Scene::pick), его каркас подсвечивается
+    pub selected: Option<usize>,
+    pub render_options: RenderOptions,
+}
+
+/// Луч в мировых координатах
+#[derive(Clone, Debug)]
+pub struct Ray {
+    pub origin: Point3<f64>,
+    /// Единичное направление
+    pub direction: Vector3<f64>,
+}
+
+impl Ray {
+    /// Луч из камеры через точку изображения `width` x `height`, заданную в пикселях.
+    /// Строится обращением матрицы вида, проекции и окна просмотра: начало лежит
+    /// на ближней плоскости отсечения, направление - к дальней.
+    pub fn through_pixel(camera: &Camera, width: u32, height: u32, x: f64, y: f64) -> Option<Self> {
+        let inverse = (ZBufferPerformer::calculate_viewport_matrix(width, height)
+            * camera.camera_matrix)
+            .try_inverse()?;
+        let unproject = |z: f64| Point3::from_homogeneous(inverse * Vector4::new(x, y, z, 1.));
+        let near = unproject(-1.)?;
+        let far = unproject(1.)?;
+
+        Some(Self {
+            origin: near,
+            direction: (far - near).try_normalize(f64::EPSILON)?,
+        })
+    }
+}
+
+/// Расстояние вдоль луча до ближайшего пересечения с треугольниками модели
+pub fn intersect_model(ray: &Ray, model: &dyn Model3D) -> Option<f64> {
+    let vertices = model.vertices_world();
+    model
+        .triangles()
+        .iter()
+        .filter_map(|&(a, b, c)| {
+            ray_triangle_intersect(
+                &ray.origin,
+                &ray.direction,
+                &vertices[a],
+                &vertices[b],
+                &vertices[c],
+            )
+        })
+        .min_by(f64::total_cmp)
+}
+
+impl Scene {
+    /// Создаёт пустую сцену с камерой и источником света по умолчанию
+    pub fn new(aspect_ratio: f64) -> Self {
+        let camera = Camera::new(
+            Point3::new(0., 0., 3.),
+            Point3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            FOV_DEGREES.to_radians(),
+            aspect_ratio,
+            NEAR_PLANE,
+            FAR_PLANE,
+        );
+        let light_source = LightSource {
+            pos: Point3::new(0., 0., 3.),
+            intensity: 15.,
+            color: Rgb::white(),
+            attenuation: Attenuation::default(),
+        };
+
+        Self {
+            camera,
+            light_source,
+            object: None,
+            selected: None,
+            render_options: RenderOptions::default(),
+        }
+    }
+
+    /// Объекты сцены в порядке их индексов
+    pub fn objects(&self) -> impl Iterator<Item = &Rc<RefCell<dyn InteractiveModel>>> {
+        self.object.iter()
+    }
+
+    /// Индекс объекта, ближайшего к началу луча среди пересечённых им
+    pub fn pick(&self, ray: &Ray) -> Option<usize> {
+        self.objects()
+            .enumerate()
+            .filter_map(|(i, object)| Some((i, intersect_model(ray, object.borrow().deref())?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Общий ограничивающий параллелепипед всех объектов сцены
+    pub fn bounding_box(&self) -> Option<(Point3<f64>, Point3<f64>)> {
+        self.objects()
+            .filter_map(|object| object.borrow().bounding_box())
+            .reduce(union_bounds)
+    }
+
+    /// Выделенный объект, если индекс выделения указывает на объект сцены
+    pub fn selected_object(&self) -> Option<&Rc<RefCell<dyn InteractiveModel>>> {
+        self.objects().nth(self.selected?)
+    }
+
+    /// Сцена с теми же объектами, светом и настройками, но другой камерой.
+    /// Объекты общие: изменения объектов видны в обеих сценах
+    pub fn with_camera(&self, camera: Camera) -> Scene {
+        Scene {
+            camera,
+            light_source: self.light_source.clone(),
+            object: self.object.clone(),
+            selected: self.selected,
+            render_options: self.render_options.clone(),
+        }
+    }
+
+    /// Независимая копия сцены, которую можно передать в поток отрисовки
+    pub fn snapshot(&self) -> SceneSnapshot {
+        SceneSnapshot {
+            camera: self.camera.clone(),
+            light_source: self.light_source.clone(),
+            object: self
+                .object
+                .as_ref()
+                .map(|object| object.borrow().snapshot()),
+            selected: self.selected,
+            render_options: self.render_options.clone(),
+        }
+    }
+}
+
+/// Копия сцены для отрисовки в другом потоке
+pub struct SceneSnapshot {
+    camera: Camera,
+    light_source: LightSource,
+    object: Option<ModelSnapshot>,
+    selected: Option<usize>,
+    render_options: RenderOptions,
+}
+
+impl SceneSnapshot {
+    /// Восстанавливает сцену в текущем потоке
+    pub fn into_scene(self) -> Scene {
+        Scene {
+            camera: self.camera,
+            light_source: self.light_source,
+            object: self.object.map(|snapshot| snapshot()),
+            selected: self.selected,
+            render_options: self.render_options,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::triangle_mesh::TriangleMesh;
+
+    #[test]
+    fn ray_through_triangle_picks_its_object() {
+        let vertices = vec![
+            Point3::new(-1., -1., -2.),
+            Point3::new(1., -1., -2.),
+            Point3::new(0., 1., -2.),
+        ];
+        let triangle = TriangleMesh::from_parts(vertices, vec![(0, 1, 2)], None);
+        let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(triangle));
+        let mut scene = Scene::new(1.);
+        scene.object = Some(object.clone());
+
+        let hit = Ray {
+            origin: Point3::new(0., 0., 3.),
+            direction: -Vector3::z(),
+        };
+        assert_eq!(scene.pick(&hit), Some(0));
+        let distance = intersect_model(&hit, object.borrow().deref()).unwrap();
+        assert!((distance - 5.).abs() < 1e-9, "расстояние {distance}");
+
+        let miss = Ray {
+            origin: Point3::new(2., 0., 3.),
+            direction: -Vector3::z(),
+        };
+        assert_eq!(scene.pick(&miss), None);
+    }
+
+    #[test]
+    fn ray_through_image_center_looks_at_target() {
+        let scene = Scene::new(1.);
+        let ray = Ray::through_pixel(&scene.camera, 64, 64, 32., 32.).unwrap();
+        assert!((ray.direction + Vector3::z()).norm() < 1e-9);
+        assert!(ray.origin.x.abs() < 1e-9 && ray.origin.y.abs() < 1e-9);
+    }
 }
@@ -1,14 +1,15 @@
 use nalgebra::{Point3, Vector3};
 use std::collections::BTreeMap;
+use std::fmt;
 
 pub type Vertex = Point3<f64>;
 
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct Face {
     edge: usize,
 }
 
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct HalfEdge {
     origin: usize,       // Начальная вершина
     twin: usize,         // Парное полуребро
@@ -23,6 +24,17 @@ pub struct DCEL {
     pub faces: Vec<Face>,
 }
 
+// Вместо всех вершин и полурёбер выводятся их количества
+impl fmt::Debug for DCEL {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DCEL")
+            .field("vertices", &self.vertices.len())
+            .field("half_edges", &self.half_edges.len())
+            .field("faces", &self.faces.len())
+            .finish()
+    }
+}
+
 impl DCEL {
     pub fn new(
         vertices: Vec<Vertex>,
@@ -142,7 +154,11 @@ impl DCEL {
         half_edges: &[HalfEdge],
         vertices: &[Vertex],
     ) {
-        let normal = origin_point.coords.normalize(); // Всегда лучше нормализовать
+        // Вершина в начале координат не задаёт нормаль - берём любую ось
+        let normal = origin_point
+            .coords
+            .try_normalize(f64::EPSILON)
+            .unwrap_or_else(Vector3::z);
 
         // 1. Создаем НАДЕЖНЫЙ ортонормированный базис в касательной плоскости.
         // Выбираем глобальную ось, которая не коллинеарна нормали.
@@ -165,8 +181,13 @@ impl DCEL {
             let p_a = vertices[half_edges[half_edges[a_idx].twin].origin];
             let p_b = vertices[half_edges[half_edges[b_idx].twin].origin];
 
-            let a_dir = (p_a - origin_point).normalize();
-            let b_dir = (p_b - origin_point).normalize();
+            // Совпадающие вершины дают нулевое направление вместо NaN
+            let a_dir = (p_a - origin_point)
+                .try_normalize(f64::EPSILON)
+                .unwrap_or_else(Vector3::zeros);
+            let b_dir = (p_b - origin_point)
+                .try_normalize(f64::EPSILON)
+                .unwrap_or_else(Vector3::zeros);
 
             let angle_a = tangent_angle(&u, &v, &a_dir);
             let angle_b = tangent_angle(&u, &v, &b_dir);
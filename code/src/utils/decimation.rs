@@ -0,0 +1,314 @@
+use crate::objects::model3d::Triangle;
+use crate::utils::triangles::{edge_key, oriented_face_normal};
+use nalgebra::{Matrix4, Point3, Vector4};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, BinaryHeap};
+
+/// Минимальное количество треугольников замкнутой сетки (тетраэдр)
+const MIN_CLOSED_TRIANGLES: usize = 4;
+
+/// Кандидат на стягивание ребра в точку `target`
+struct EdgeCollapse {
+    cost: f64,
+    edge: [usize; 2],
+    versions: [usize; 2],
+    target: Point3<f64>,
+}
+
+impl PartialEq for EdgeCollapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for EdgeCollapse {}
+
+impl PartialOrd for EdgeCollapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EdgeCollapse {
+    // Обратный порядок: BinaryHeap извлекает кандидата с наименьшей ошибкой
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.edge.cmp(&self.edge))
+    }
+}
+
+/// Квадрика плоскости треугольника
+fn face_quadric(vertices: &[Point3<f64>], tri: &Triangle) -> Matrix4<f64> {
+    let p0 = vertices[tri.0];
+    let normal = (vertices[tri.1] - p0)
+        .cross(&(vertices[tri.2] - p0))
+        .try_normalize(f64::EPSILON);
+
+    match normal {
+        Some(n) => {
+            let plane = Vector4::new(n.x, n.y, n.z, -n.dot(&p0.coords));
+            plane * plane.transpose()
+        }
+        None => Matrix4::zeros(),
+    }
+}
+
+fn quadric_error(quadric: &Matrix4<f64>, p: &Point3<f64>) -> f64 {
+    let h = p.to_homogeneous();
+    h.dot(&(quadric * h))
+}
+
+/// Подбирает положение вершины после стягивания ребра `a`-`b`,
+/// минимизирующее квадратичную ошибку
+fn best_collapse_target(
+    quadric: &Matrix4<f64>,
+    a: &Point3<f64>,
+    b: &Point3<f64>,
+) -> (Point3<f64>, f64) {
+    let midpoint = nalgebra::center(a, b);
+    let mut candidates = vec![*a, *b, midpoint];
+
+    // Оптимальная точка - решение системы dQ/dp = 0. Принимаем её только
+    // если она не уходит далеко от ребра (почти вырожденная система)
+    let system = quadric.fixed_view::<3, 3>(0, 0).into_owned();
+    let rhs = -quadric.fixed_view::<3, 1>(0, 3).into_owned();
+    if let Some(inverse) = system.try_inverse() {
+        let optimal = Point3::from(inverse * rhs);
+        if (optimal - midpoint).norm() <= (b - a).norm() {
+            candidates.push(optimal);
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|p| (p, quadric_error(quadric, &p)))
+        .min_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap()
+}
+
+fn collapse_candidate(
+    edge: [usize; 2],
+    positions: &[Point3<f64>],
+    quadrics: &[Matrix4<f64>],
+    versions: &[usize],
+) -> EdgeCollapse {
+    let [a, b] = edge;
+    let (target, cost) =
+        best_collapse_target(&(quadrics[a] + quadrics[b]), &positions[a], &positions[b]);
+
+    EdgeCollapse {
+        cost,
+        edge,
+        versions: [versions[a], versions[b]],
+        target,
+    }
+}
+
+fn vertex_neighbors(
+    v: usize,
+    vertex_faces: &[BTreeSet<usize>],
+    faces: &[Option<Triangle>],
+) -> BTreeSet<usize> {
+    vertex_faces[v]
+        .iter()
+        .filter_map(|&f| faces[f])
+        .flat_map(|tri| [tri.0, tri.1, tri.2])
+        .filter(|&w| w != v)
+        .collect()
+}
+
+/// Проверяет, что стягивание ребра сохранит многообразие и не вывернет грани
+fn can_collapse(
+    collapse: &EdgeCollapse,
+    positions: &[Point3<f64>],
+    vertex_faces: &[BTreeSet<usize>],
+    faces: &[Option<Triangle>],
+) -> bool {
+    let [u, v] = collapse.edge;
+
+    // 1. Ребро должно разделять ровно две грани
+    if vertex_faces[u].intersection(&vertex_faces[v]).count() != 2 {
+        return false;
+    }
+
+    // 2. Условие связности: общими соседями концов ребра могут быть
+    //    только две противолежащие вершины
+    let neighbors_u = vertex_neighbors(u, vertex_faces, faces);
+    let neighbors_v = vertex_neighbors(v, vertex_faces, faces);
+    if neighbors_u.intersection(&neighbors_v).count() != 2 {
+        return false;
+    }
+
+    // 3. Оставшиеся грани не должны менять ориентацию
+    for &f in vertex_faces[u].union(&vertex_faces[v]) {
+        let Some(tri) = faces[f] else {
+            continue;
+        };
+        let corners = [tri.0, tri.1, tri.2];
+        if corners.contains(&u) && corners.contains(&v) {
+            continue; // Грань будет удалена
+        }
+
+        let moved = corners.map(|w| {
+            if w == u || w == v {
+                collapse.target
+            } else {
+                positions[w]
+            }
+        });
+
+        let before =
+            (positions[tri.1] - positions[tri.0]).cross(&(positions[tri.2] - positions[tri.0]));
+        let after = (moved[1] - moved[0]).cross(&(moved[2] - moved[0]));
+        if before.dot(&after) <= 0. {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Упрощает замкнутую сетку стягиванием рёбер с квадратичной метрикой ошибки
+/// (Garland, Heckbert) до `target_triangles` треугольников.
+///
+/// `normals` - нормали граней; если их количество не совпадает с количеством
+/// треугольников, возвращается пустой список нормалей.
+pub fn decimate_mesh(
+    vertices: &[Point3<f64>],
+    triangles: &[Triangle],
+    normals: &[Vector4<f64>],
+    target_triangles: usize,
+) -> (Vec<Point3<f64>>, Vec<Triangle>, Vec<Vector4<f64>>) {
+    let target_triangles = target_triangles.max(MIN_CLOSED_TRIANGLES);
+    let has_normals = normals.len() == triangles.len();
+
+    let mut positions = vertices.to_vec();
+    let mut faces: Vec<Option<Triangle>> = triangles.iter().copied().map(Some).collect();
+    let mut face_normals: Vec<Vector4<f64>> = if has_normals {
+        normals.to_vec()
+    } else {
+        triangles
+            .iter()
+            .map(|tri| oriented_face_normal(&positions, tri, &Vector4::zeros()))
+            .collect()
+    };
+
+    // 1. Инцидентные грани и квадрики вершин
+    let mut vertex_faces = vec![BTreeSet::new(); positions.len()];
+    let mut quadrics: Vec<Matrix4<f64>> = vec![Matrix4::zeros(); positions.len()];
+    for (f, tri) in triangles.iter().enumerate() {
+        let quadric = face_quadric(&positions, tri);
+        for v in [tri.0, tri.1, tri.2] {
+            vertex_faces[v].insert(f);
+            quadrics[v] += quadric;
+        }
+    }
+
+    // 2. Очередь кандидатов на стягивание
+    let mut versions = vec![0; positions.len()];
+    let mut removed = vec![false; positions.len()];
+    let edges: BTreeSet<[usize; 2]> = triangles
+        .iter()
+        .flat_map(|tri| [(tri.0, tri.1), (tri.1, tri.2), (tri.2, tri.0)])
+        .map(|(a, b)| edge_key(a, b))
+        .collect();
+    let mut heap: BinaryHeap<EdgeCollapse> = edges
+        .into_iter()
+        .map(|edge| collapse_candidate(edge, &positions, &quadrics, &versions))
+        .collect();
+
+    // 3. Стягиваем рёбра с наименьшей ошибкой
+    let mut face_count = triangles.len();
+    while face_count > target_triangles {
+        let Some(collapse) = heap.pop() else {
+            break;
+        };
+        let [u, v] = collapse.edge;
+
+        // Кандидат устарел: одна из вершин удалена или перемещена
+        if removed[u]
+            || removed[v]
+            || versions[u] != collapse.versions[0]
+            || versions[v] != collapse.versions[1]
+        {
+            continue;
+        }
+
+        if !can_collapse(&collapse, &positions, &vertex_faces, &faces) {
+            continue;
+        }
+
+        // Удаляем две грани, содержащие ребро
+        let shared: Vec<usize> = vertex_faces[u]
+            .intersection(&vertex_faces[v])
+            .copied()
+            .collect();
+        for f in shared {
+            if let Some(tri) = faces[f].take() {
+                for w in [tri.0, tri.1, tri.2] {
+                    vertex_faces[w].remove(&f);
+                }
+                face_count -= 1;
+            }
+        }
+
+        // Переносим оставшиеся грани вершины v на вершину u
+        for f in std::mem::take(&mut vertex_faces[v]) {
+            if let Some(tri) = faces[f].as_mut() {
+                for w in [&mut tri.0, &mut tri.1, &mut tri.2] {
+                    if *w == v {
+                        *w = u;
+                    }
+                }
+            }
+            vertex_faces[u].insert(f);
+        }
+
+        positions[u] = collapse.target;
+        let quadric_v = quadrics[v];
+        quadrics[u] += quadric_v;
+        removed[v] = true;
+        versions[u] += 1;
+        versions[v] += 1;
+
+        for &f in &vertex_faces[u] {
+            if let Some(tri) = faces[f] {
+                face_normals[f] = oriented_face_normal(&positions, &tri, &face_normals[f]);
+            }
+        }
+
+        for w in vertex_neighbors(u, &vertex_faces, &faces) {
+            heap.push(collapse_candidate(
+                edge_key(u, w),
+                &positions,
+                &quadrics,
+                &versions,
+            ));
+        }
+    }
+
+    // 4. Удаляем неиспользуемые вершины и перенумеровываем оставшиеся
+    let mut remap = vec![usize::MAX; positions.len()];
+    let mut new_vertices = Vec::new();
+    for (i, p) in positions.iter().enumerate() {
+        if !removed[i] && !vertex_faces[i].is_empty() {
+            remap[i] = new_vertices.len();
+            new_vertices.push(*p);
+        }
+    }
+
+    let mut new_triangles = Vec::with_capacity(face_count);
+    let mut new_normals = Vec::with_capacity(if has_normals { face_count } else { 0 });
+    for (tri, normal) in faces.iter().zip(face_normals.iter()) {
+        if let Some(tri) = tri {
+            new_triangles.push((remap[tri.0], remap[tri.1], remap[tri.2]));
+            if has_normals {
+                new_normals.push(*normal);
+            }
+        }
+    }
+
+    (new_vertices, new_triangles, new_normals)
+}
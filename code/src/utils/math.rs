@@ -1,3 +1,7 @@
+use crate::config::{PARALLEL_TRANSFORM_THRESHOLD, TRANSFORM_BATCH_SIZE};
+use nalgebra::{Matrix4, Point3, Rotation3, SMatrix, Unit, Vector3, Vector4};
+use rayon::prelude::*;
+use std::f64::consts::PI;
 use std::ops::{Add, Mul};
 
 pub fn lerp<T>(a: T, b: T, t: f64) -> T
@@ -6,3 +10,230 @@ where
 {
     a * (1.0 - t) + b * t
 }
+
+/// Сферическая интерполяция направлений нормалей по дуге большого круга.
+///
+/// В отличие от `lerp`, промежуточная нормаль остаётся единичной и поворачивается
+/// с постоянной угловой скоростью. Противоположные нормали поворачиваются вокруг
+/// произвольной перпендикулярной оси; вырожденные (нулевые) — интерполируются линейно.
+pub fn slerp_normal(a: Vector4<f64>, b: Vector4<f64>, t: f64) -> Vector4<f64> {
+    let (Some(from), Some(to)) = (
+        Unit::try_new(a.xyz(), f64::EPSILON),
+        Unit::try_new(b.xyz(), f64::EPSILON),
+    ) else {
+        return lerp(a, b, t);
+    };
+
+    let direction = from.try_slerp(&to, t, 1e-9).unwrap_or_else(|| {
+        let helper = if from.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        let axis = Unit::new_normalize(from.cross(&helper));
+        Unit::new_unchecked(Rotation3::from_axis_angle(&axis, PI * t) * from.into_inner())
+    });
+
+    let w = lerp(a.w, b.w, t);
+    Vector4::new(direction.x, direction.y, direction.z, w)
+}
+
+/// Матрица преобразования нормалей: обратная транспонированная к линейной части
+/// матрицы модели. Перенос на нормали не влияет, вырожденная матрица используется как есть.
+pub fn normal_matrix(model_matrix: &Matrix4<f64>) -> Matrix4<f64> {
+    model_matrix
+        .fixed_view::<3, 3>(0, 0)
+        .clone_owned()
+        .try_inverse()
+        .map_or(*model_matrix, |inverse| {
+            inverse.transpose().to_homogeneous()
+        })
+}
+
+/// Записывает в каждый элемент `dst` результат `f` от соответствующего элемента `src`.
+/// Для буферов от `PARALLEL_TRANSFORM_THRESHOLD` элементов работа делится между потоками.
+pub fn transform_into<T: Send, U: Sync>(dst: &mut [T], src: &[U], f: impl Fn(&U) -> T + Sync) {
+    if src.len() >= PARALLEL_TRANSFORM_THRESHOLD {
+        dst.par_iter_mut()
+            .zip(src.par_iter())
+            .for_each(|(d, s)| *d = f(s));
+    } else {
+        dst.iter_mut().zip(src.iter()).for_each(|(d, s)| *d = f(s));
+    }
+}
+
+type TransformBatch = SMatrix<f64, 4, TRANSFORM_BATCH_SIZE>;
+
+/// Однородные координаты не более `TRANSFORM_BATCH_SIZE` точек после умножения на матрицу,
+/// столбец на точку. Столбцы после последней точки нулевые.
+fn transform_batch(matrix: &Matrix4<f64>, points: &[Point3<f64>]) -> TransformBatch {
+    let columns = TransformBatch::from_fn(|row, col| match points.get(col) {
+        Some(p) if row < 3 => p[row],
+        Some(_) => 1.,
+        None => 0.,
+    });
+    matrix * columns
+}
+
+/// Вызывает `f` для однородных координат каждой точки после преобразования. Точки
+/// умножаются на матрицу блоками по `TRANSFORM_BATCH_SIZE` одним произведением матриц,
+/// а не по одной.
+pub fn for_each_transformed(
+    matrix: &Matrix4<f64>,
+    points: &[Point3<f64>],
+    mut f: impl FnMut(Vector4<f64>),
+) {
+    for chunk in points.chunks(TRANSFORM_BATCH_SIZE) {
+        let batch = transform_batch(matrix, chunk);
+        for column in batch.column_iter().take(chunk.len()) {
+            f(column.into_owned());
+        }
+    }
+}
+
+/// Однородные координаты точек после преобразования
+pub fn transform_homogeneous(matrix: &Matrix4<f64>, points: &[Point3<f64>]) -> Vec<Vector4<f64>> {
+    let mut result = Vec::with_capacity(points.len());
+    for_each_transformed(matrix, points, |clip| result.push(clip));
+    result
+}
+
+/// Записывает в `out` точки `points`, преобразованные матрицей с делением на w.
+/// Блоки по `TRANSFORM_BATCH_SIZE` точек обрабатываются параллельно для буферов
+/// от `PARALLEL_TRANSFORM_THRESHOLD` элементов.
+pub fn transform_points(matrix: &Matrix4<f64>, points: &[Point3<f64>], out: &mut [Point3<f64>]) {
+    let transform_chunk = |(dst, src): (&mut [Point3<f64>], &[Point3<f64>])| {
+        let batch = transform_batch(matrix, src);
+        for (d, column) in dst.iter_mut().zip(batch.column_iter()) {
+            *d = Point3::from(column.xyz() / column[3]);
+        }
+    };
+
+    if points.len() >= PARALLEL_TRANSFORM_THRESHOLD {
+        out.par_chunks_mut(TRANSFORM_BATCH_SIZE)
+            .zip(points.par_chunks(TRANSFORM_BATCH_SIZE))
+            .for_each(transform_chunk);
+    } else {
+        out.chunks_mut(TRANSFORM_BATCH_SIZE)
+            .zip(points.chunks(TRANSFORM_BATCH_SIZE))
+            .for_each(transform_chunk);
+    }
+}
+
+/// Наименьший параллелепипед, содержащий оба параллелепипеда `(min, max)`
+pub fn union_bounds(
+    a: (Point3<f64>, Point3<f64>),
+    b: (Point3<f64>, Point3<f64>),
+) -> (Point3<f64>, Point3<f64>) {
+    (a.0.inf(&b.0), a.1.sup(&b.1))
+}
+
+/// Все координаты конечны (нет NaN и бесконечностей). Используется в отладочных
+/// проверках, чтобы NaN обнаруживался там, где он возник
+pub fn all_finite<'a>(coords: impl IntoIterator<Item = &'a f64>) -> bool {
+    coords.into_iter().all(|c| c.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Matrix3;
+
+    #[test]
+    fn slerp_midpoint_of_perpendicular_normals_is_at_45_degrees() {
+        let a = Vector4::new(1., 0., 0., 0.);
+        let b = Vector4::new(0., 1., 0., 0.);
+
+        let slerped = slerp_normal(a, b, 0.5).xyz();
+        let lerped = lerp(a, b, 0.5).xyz();
+
+        assert!((slerped.norm() - 1.).abs() < 1e-12);
+        assert!((slerped.angle(&a.xyz()) - PI / 4.).abs() < 1e-12);
+        assert!((lerped.norm() - 1.).abs() > 0.25);
+    }
+
+    #[test]
+    fn slerp_handles_opposite_normals() {
+        let a = Vector4::new(0., 0., 1., 0.);
+        let b = Vector4::new(0., 0., -1., 0.);
+
+        let midpoint = slerp_normal(a, b, 0.5).xyz();
+
+        assert!((midpoint.norm() - 1.).abs() < 1e-12);
+        assert!(midpoint.z.abs() < 1e-12);
+        assert!((slerp_normal(a, b, 1.).xyz() - b.xyz()).norm() < 1e-12);
+    }
+
+    #[test]
+    fn normal_matrix_keeps_normals_orthogonal_to_tangents() {
+        // Неравномерный масштаб со сдвигом и переносом
+        let model_matrix = Matrix4::new_translation(&Vector3::new(1., -2., 3.))
+            * Matrix3::new(2., 0.7, 0., 0., 0.5, 0., 0.3, 0., 3.).to_homogeneous();
+        let normal = Vector3::new(1., 1., 1.).normalize();
+        let tangents = [Vector3::new(1., -1., 0.), Vector3::new(0., 1., -1.)];
+
+        let transformed = (normal_matrix(&model_matrix) * normal.to_homogeneous()).normalize();
+        assert_eq!(transformed.w, 0.);
+        for tangent in tangents {
+            let tangent = model_matrix * tangent.to_homogeneous();
+            assert!(transformed.dot(&tangent).abs() < 1e-12);
+            // Простое умножение на матрицу модели перекашивает нормаль
+            assert!((model_matrix * normal.to_homogeneous()).dot(&tangent).abs() > 1e-3);
+        }
+    }
+
+    #[test]
+    fn parallel_transform_matches_serial() {
+        let model_matrix = Matrix4::new_translation(&Vector3::new(0.5, -1., 2.))
+            * Matrix4::new_rotation(Vector3::new(0.3, -0.7, 1.1))
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(1.5, 0.25, 3.));
+        let points: Vec<Point3<f64>> = (0..PARALLEL_TRANSFORM_THRESHOLD * 4)
+            .map(|i| {
+                let t = i as f64 * 0.37;
+                Point3::new(t.sin(), (t * 1.3).cos(), t.sqrt())
+            })
+            .collect();
+        let transform = |p: &Point3<f64>| model_matrix.transform_point(p);
+
+        let serial: Vec<Point3<f64>> = points.iter().map(transform).collect();
+        let mut parallel = vec![Point3::origin(); points.len()];
+        transform_into(&mut parallel, &points, transform);
+
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn batched_transform_matches_per_point() {
+        let projection = Matrix4::new_perspective(1.5, 1., 0.1, 100.)
+            * Matrix4::new_translation(&Vector3::new(0., 0., -5.))
+            * Matrix4::new_rotation(Vector3::new(0.2, 0.4, -0.1));
+        let points: Vec<Point3<f64>> = (0..TRANSFORM_BATCH_SIZE * 5 + 7)
+            .map(|i| {
+                let t = i as f64 * 0.11;
+                Point3::new(t.cos(), (t * 0.7).sin(), (t * 0.3).cos())
+            })
+            .collect();
+
+        let clip = transform_homogeneous(&projection, &points);
+        let mut projected = vec![Point3::origin(); points.len()];
+        transform_points(&projection, &points, &mut projected);
+
+        assert_eq!(clip.len(), points.len());
+        for ((p, c), q) in points.iter().zip(&clip).zip(&projected) {
+            let expected = projection * p.to_homogeneous();
+            assert!((c - expected).norm() < 1e-12);
+            let expected = Point3::from_homogeneous(expected).unwrap();
+            assert!((q - expected).norm() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn union_of_bounds_contains_both() {
+        let a = (Point3::new(-1., 0., 2.), Point3::new(1., 1., 3.));
+        let b = (Point3::new(0., -2., -1.), Point3::new(0.5, 4., 0.));
+        let (min, max) = union_bounds(a, b);
+        assert_eq!(min, Point3::new(-1., -2., -1.));
+        assert_eq!(max, Point3::new(1., 4., 3.));
+        assert_eq!(union_bounds(a, a), a);
+    }
+}
@@ -1,4 +1,10 @@
 pub mod dcel;
+pub mod decimation;
 pub mod math;
 pub mod morphing;
+pub mod occlusion;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod triangles;
+#[cfg(feature = "video")]
+pub mod video_export;
@@ -1,21 +1,131 @@
-use crate::config::RELAXATION_ROUNDS_LIMIT;
+use crate::config::{NORMAL_SMOOTHING_ANGLE_DEGREES, RELAXATION_ROUNDS_LIMIT};
 use crate::objects::model3d::{Model3D, Triangle};
 use crate::objects::triangle_mesh::TriangleMesh;
 use crate::utils::dcel::{DCEL, Vertex};
-use crate::utils::triangles::barycentric;
+use crate::utils::math::all_finite;
+use crate::utils::triangles::{barycentric, closest_point_on_triangle};
 use delaunator::{Point, triangulate};
 use itertools::izip;
-use nalgebra::{Matrix4, Point3, Vector3, Vector4};
-use std::collections::{HashMap, HashSet};
+use nalgebra::{Point3, Vector2, Vector3, Vector4};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
-
-const EPS: f64 = 1e-6;
-const VERTEX_MATCH_EPS: f64 = 1e-6;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 type Segment = [usize; 2];
 
-fn collect_neighbors(mesh: &TriangleMesh) -> Vec<HashSet<usize>> {
-    let mut neighbors = vec![HashSet::new(); mesh.vertices().len()];
+/// Суперсетка: вершины, треугольники и происхождение каждого треугольника
+pub type Supermesh = (Vec<Vertex>, Vec<Triangle>, Vec<SegmentOrigin>);
+
+/// Количество внутренних точек, среди которых выбирается центр проекции на сферу
+const INNER_POINT_CANDIDATES: usize = 32;
+
+/// Происхождение отрезка (и треугольника) суперсетки
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SegmentOrigin {
+    /// Часть ребра исходной сетки
+    Source,
+    /// Часть ребра целевой сетки
+    Target,
+    /// Отрезок, образованный точкой пересечения рёбер двух сеток
+    Intersection,
+}
+
+/// Ошибка, которой завершается построение морфинга после отмены
+pub const CANCELLED: &str = "cancelled";
+
+/// Флаг отмены построения морфинга, разделяемый между потоками
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает ошибку `CANCELLED`, если построение было отменено
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err(CANCELLED.to_string());
+        }
+        Ok(())
+    }
+}
+
+// Токены равны, если разделяют один флаг
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Итоги релаксации сетки на сфере
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ParametrizeReport {
+    /// Количество выполненных раундов релаксации
+    pub rounds: usize,
+    /// Наибольшее смещение вершины за последний раунд
+    pub final_residual: f64,
+    /// Ориентации граней восстановлены и смещения ниже порога до исчерпания раундов
+    pub converged: bool,
+}
+
+/// Геометрические допуски и условия сходимости алгоритма морфинга.
+///
+/// Все допуски относительные, поэтому результат не зависит от масштаба модели.
+/// Допуски, применяемые после параметризации, заданы для единичной сферы, допуски
+/// в мировых координатах - в долях от радиуса сетки.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MorphParams {
+    /// Расстояние, в пределах которого вершины считаются совпадающими
+    /// (в долях от радиуса объединяемых сеток)
+    pub vertex_match_eps: f64,
+    /// Минимальная длина направляющей линии пересечения двух больших кругов
+    /// (на единичной сфере)
+    pub arc_intersection_eps: f64,
+    /// Допуск принадлежности точки дуге большого круга (на единичной сфере)
+    pub on_arc_eps: f64,
+    /// Смещение вершин за раунд, ниже которого релаксация считается сошедшейся
+    /// (на единичной сфере)
+    pub relaxation_threshold: f64,
+    /// Максимальное количество раундов релаксации
+    pub relaxation_rounds_limit: usize,
+    /// Допуск отрицательных барицентрических координат при поиске треугольника
+    /// (безразмерный)
+    pub barycentric_slack: f64,
+    /// Минимальное расстояние до пересечения луча при поиске внутренней точки
+    /// (в долях от радиуса сетки)
+    pub inner_point_relative_eps: f64,
+    /// Наибольший угол между гранями (в радианах), по которому нормали
+    /// сглаживаются при переносе на суперсетку
+    pub smoothing_angle: f64,
+    /// Флаг отмены, проверяемый в длительных циклах алгоритма
+    pub cancellation: CancellationToken,
+}
+
+impl Default for MorphParams {
+    fn default() -> Self {
+        Self {
+            vertex_match_eps: 1e-6,
+            arc_intersection_eps: 1e-6,
+            on_arc_eps: 1e-10,
+            relaxation_threshold: 1e-2,
+            relaxation_rounds_limit: RELAXATION_ROUNDS_LIMIT,
+            barycentric_slack: 1e-12,
+            inner_point_relative_eps: 1e-9,
+            smoothing_angle: NORMAL_SMOOTHING_ANGLE_DEGREES.to_radians(),
+            cancellation: CancellationToken::default(),
+        }
+    }
+}
+
+fn collect_neighbors(mesh: &TriangleMesh) -> Vec<BTreeSet<usize>> {
+    let mut neighbors = vec![BTreeSet::new(); mesh.vertices().len()];
 
     for tri in mesh.triangles() {
         neighbors[tri.0].extend(&[tri.1, tri.2]);
@@ -39,9 +149,23 @@ fn get_orientations(vertices: &[Vertex], triangles: &[Triangle]) -> Vec<f64> {
         .collect()
 }
 
-fn relax_mesh(parametrized_mesh: &mut TriangleMesh, original_orientations: &[f64]) {
-    let epsilon_threshold = 1e-2;
+/// Новое положение вершины на единичной сфере - направление суммы соседей.
+/// Если соседи уравновешивают друг друга и сумма почти нулевая, направление
+/// не определено, и вершина остаётся на месте `current`
+fn relaxed_position(neighbors: &BTreeSet<usize>, vertices: &[Vertex], current: &Vertex) -> Vertex {
+    neighbors
+        .iter()
+        .map(|&neighbor_idx| vertices[neighbor_idx].coords)
+        .sum::<Vector3<f64>>()
+        .try_normalize(f64::EPSILON)
+        .map_or(*current, Vertex::from)
+}
 
+fn relax_mesh(
+    parametrized_mesh: &mut TriangleMesh,
+    original_orientations: &[f64],
+    params: &MorphParams,
+) -> Result<ParametrizeReport, String> {
     let neighbors = collect_neighbors(parametrized_mesh);
 
     // Релаксация сетки
@@ -52,8 +176,13 @@ fn relax_mesh(parametrized_mesh: &mut TriangleMesh, original_orientations: &[f64
     let mut orientations_established = original_orientations.iter().eq(orientations.iter());
     let mut epsilon_reached = true;
     let mut round_no: usize = 0;
+    let mut residual = 0.;
+
+    while (!(orientations_established && epsilon_reached))
+        && round_no < params.relaxation_rounds_limit
+    {
+        params.cancellation.check()?;
 
-    while (!(orientations_established && epsilon_reached)) && round_no < RELAXATION_ROUNDS_LIMIT {
         // 1. Сохраняем положение вершин перед релаксацией
         let prev_vertices = parametrized_mesh.vertices_world().clone();
 
@@ -61,19 +190,16 @@ fn relax_mesh(parametrized_mesh: &mut TriangleMesh, original_orientations: &[f64
         let vertices = parametrized_mesh.vertices_world_mut();
 
         for i in 0..vertices.len() {
-            let new_pos = neighbors[i]
-                .iter()
-                .map(|neighbor_idx| prev_vertices[*neighbor_idx].coords)
-                .sum::<Vector3<f64>>()
-                .normalize();
-            vertices[i] = Vertex::from(new_pos);
+            vertices[i] = relaxed_position(&neighbors[i], &prev_vertices, &prev_vertices[i]);
         }
 
         // Достигнут эпсилон-порог (вершины почти не сдвинулись)
-        epsilon_reached = prev_vertices
+        residual = prev_vertices
             .iter()
             .zip(vertices.iter())
-            .all(|(prev, curr)| (prev - curr).norm() < epsilon_threshold);
+            .map(|(prev, curr)| (prev - curr).norm())
+            .fold(0., f64::max);
+        epsilon_reached = residual < params.relaxation_threshold;
 
         // Центрирование сферы для избежания коллапса вершин
         let mean: Vector3<f64> =
@@ -89,26 +215,39 @@ fn relax_mesh(parametrized_mesh: &mut TriangleMesh, original_orientations: &[f64
 
         round_no += 1;
     }
+
+    Ok(ParametrizeReport {
+        rounds: round_no,
+        final_residual: residual,
+        converged: orientations_established && epsilon_reached,
+    })
 }
 
-fn find_inner_point(mesh: &TriangleMesh) -> Option<Vertex> {
+// Точка внутри замкнутой сетки, найденная лучом из середины треугольника `start`
+// в направлении внутренней нормали
+fn inner_point_from_triangle(
+    mesh: &TriangleMesh,
+    start: usize,
+    min_distance: f64,
+) -> Option<Vertex> {
     let vertices = mesh.vertices_world();
     let normals = mesh.normals();
     let triangles = mesh.triangles();
 
-    // 1. Испускаем луч из середины первого попавшегося полигона в направлении внутренней нормали
-    let tri = triangles[0];
-    let ray_direction = normals[0].xyz().scale(-1.0);
+    // 1. Испускаем луч из середины полигона в направлении внутренней нормали
+    let tri = triangles[start];
+    let ray_direction = normals[start].xyz().scale(-1.0);
     let ray_origin =
         (vertices[tri.0].coords + vertices[tri.1].coords + vertices[tri.2].coords) / 3.0;
 
     // 2. Находим все пересечения луча с другими полигонами
     let intersections = izip!(triangles, normals)
-        .skip(1)
-        .filter_map(|(tri, normal)| {
+        .enumerate()
+        .filter(|&(i, _)| i != start)
+        .filter_map(|(_, (tri, normal))| {
             let normal = normal.xyz();
             let t = (vertices[tri.0].coords - ray_origin).dot(&normal) / ray_direction.dot(&normal);
-            if t < f64::EPSILON || t.is_infinite() || t.is_nan() {
+            if t < min_distance || t.is_infinite() || t.is_nan() {
                 return None;
             }
 
@@ -138,7 +277,49 @@ fn find_inner_point(mesh: &TriangleMesh) -> Option<Vertex> {
     closest_intersection.map(|point| Vertex::from((ray_origin + point.coords) / 2.0))
 }
 
-pub fn parametrize_mesh(mesh: &mut TriangleMesh) {
+// Расстояние от точки до поверхности сетки
+fn distance_to_surface(mesh: &TriangleMesh, p: &Vertex) -> f64 {
+    let vertices = mesh.vertices_world();
+    mesh.triangles()
+        .iter()
+        .map(|tri| {
+            let closest =
+                closest_point_on_triangle(p, &vertices[tri.0], &vertices[tri.1], &vertices[tri.2]);
+            (p - closest).norm()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+// Находит центр проекции на сферу: среди внутренних точек, построенных лучами из
+// равномерно выбранных треугольников, берётся наиболее удалённая от поверхности.
+// Для изогнутых невыпуклых объектов такая точка "видит" большую часть поверхности,
+// что уменьшает число вывернутых после проекции треугольников.
+fn find_inner_point(mesh: &TriangleMesh, params: &MorphParams) -> Option<Vertex> {
+    // Минимальное расстояние до пересечения зависит от размера сетки
+    let mesh_radius = mesh
+        .vertices_world()
+        .iter()
+        .map(|v| v.coords.norm())
+        .fold(0., f64::max);
+    let min_distance = (params.inner_point_relative_eps * mesh_radius).max(f64::EPSILON);
+
+    let triangles_count = mesh.triangles().len();
+    let step = triangles_count.div_ceil(INNER_POINT_CANDIDATES).max(1);
+
+    (0..triangles_count)
+        .step_by(step)
+        .filter_map(|start| inner_point_from_triangle(mesh, start, min_distance))
+        .map(|point| (distance_to_surface(mesh, &point), point))
+        .max_by(|(depth_a, _), (depth_b, _)| depth_a.total_cmp(depth_b))
+        .map(|(_, point)| point)
+}
+
+pub fn parametrize_mesh(
+    mesh: &mut TriangleMesh,
+    params: &MorphParams,
+) -> Result<ParametrizeReport, String> {
+    params.cancellation.check()?;
+
     let vertices_world = mesh.vertices_world();
     let original_orientations: Vec<f64> = izip!(mesh.triangles(), mesh.normals())
         .map(|(tri, normal)| {
@@ -150,22 +331,23 @@ pub fn parametrize_mesh(mesh: &mut TriangleMesh) {
         })
         .collect();
 
-    let inner_point = find_inner_point(mesh).unwrap();
+    let inner_point = find_inner_point(mesh, params).unwrap();
     for v in mesh.vertices_world_mut() {
         *v -= inner_point.coords;
         v.coords.normalize_mut();
     }
 
-    relax_mesh(mesh, &original_orientations);
+    let report = relax_mesh(mesh, &original_orientations, params)?;
 
-    mesh.vertices = mesh.vertices_world().clone();
-    mesh.model_matrix = Matrix4::identity();
+    mesh.vertices = Arc::new(mesh.vertices_world().clone());
+    mesh.reset_transformations();
+
+    Ok(report)
 }
 
 /// Checks if a point `p` is on the arc between points `start` and `end`.
 /// All points are expected to be on the unit sphere.
-fn is_on_arc(p: &Point3<f64>, start: &Point3<f64>, end: &Point3<f64>) -> bool {
-    const ON_ARC_EPSILON: f64 = 1e-10;
+fn is_on_arc(p: &Point3<f64>, start: &Point3<f64>, end: &Point3<f64>, eps: f64) -> bool {
     let p_vec = p.coords.normalize();
     let start_vec = start.coords.normalize();
     let end_vec = end.coords.normalize();
@@ -176,7 +358,7 @@ fn is_on_arc(p: &Point3<f64>, start: &Point3<f64>, end: &Point3<f64>) -> bool {
     // Мы можем пропустить эту явную проверку, так как intersect_arcs уже находит копланарные точки.
     // Но для общей функции она важна.
     let normal = start_vec.cross(&end_vec);
-    if normal.dot(&p_vec).abs() > ON_ARC_EPSILON {
+    if normal.dot(&p_vec).abs() > eps {
         return false; // P не лежит на большом круге дуги
     }
 
@@ -190,7 +372,7 @@ fn is_on_arc(p: &Point3<f64>, start: &Point3<f64>, end: &Point3<f64>) -> bool {
     // clamp(-1.0, 1.0) защищает от ошибок f64, когда dot-продукт чуть-чуть > 1.0.
 
     // Проверяем, что сумма углов равна общему углу с некоторой точностью.
-    if (angle_sp + angle_pe - angle_se).abs() < ON_ARC_EPSILON {
+    if (angle_sp + angle_pe - angle_se).abs() < eps {
         return true;
     }
 
@@ -199,7 +381,11 @@ fn is_on_arc(p: &Point3<f64>, start: &Point3<f64>, end: &Point3<f64>) -> bool {
 
 /// Finds the intersection point of two great-circle arcs on a unit sphere.
 /// Returns `Some(Point3<f64>)` if a unique intersection is found, otherwise `None`.
-fn intersect_arcs(arc_1: [&Point3<f64>; 2], arc_2: [&Point3<f64>; 2]) -> Option<Point3<f64>> {
+fn intersect_arcs(
+    arc_1: [&Point3<f64>; 2],
+    arc_2: [&Point3<f64>; 2],
+    params: &MorphParams,
+) -> Option<Point3<f64>> {
     // 1. Calculate the normals of the great circles.
     // The normal is the cross product of the arc's endpoints.
     let normal_1 = arc_1[0].coords.cross(&arc_1[1].coords);
@@ -210,7 +396,7 @@ fn intersect_arcs(arc_1: [&Point3<f64>; 2], arc_2: [&Point3<f64>; 2]) -> Option<
     let intersection_line = normal_1.cross(&normal_2);
 
     // 3. Handle edge cases where great circles are parallel or the same.
-    if intersection_line.norm_squared() < EPS * EPS {
+    if intersection_line.norm_squared() < params.arc_intersection_eps.powi(2) {
         return None; // No unique intersection point.
     }
 
@@ -220,11 +406,12 @@ fn intersect_arcs(arc_1: [&Point3<f64>; 2], arc_2: [&Point3<f64>; 2]) -> Option<
     let p2 = -p1;
 
     // 5. Check if either of the two intersection points lies on both arcs.
-    if is_on_arc(&p1, arc_1[0], arc_1[1]) && is_on_arc(&p1, arc_2[0], arc_2[1]) {
+    let eps = params.on_arc_eps;
+    if is_on_arc(&p1, arc_1[0], arc_1[1], eps) && is_on_arc(&p1, arc_2[0], arc_2[1], eps) {
         return Some(p1);
     }
 
-    if is_on_arc(&p2, arc_1[0], arc_1[1]) && is_on_arc(&p2, arc_2[0], arc_2[1]) {
+    if is_on_arc(&p2, arc_1[0], arc_1[1], eps) && is_on_arc(&p2, arc_2[0], arc_2[1], eps) {
         return Some(p2);
     }
 
@@ -232,7 +419,133 @@ fn intersect_arcs(arc_1: [&Point3<f64>; 2], arc_2: [&Point3<f64>; 2]) -> Option<
     None
 }
 
-fn get_mesh_segments(mesh: &TriangleMesh) -> HashSet<Segment> {
+/// Ограничивающий параллелепипед дуги большого круга единичной сферы, расширенный на
+/// допуск `eps`, с которым `is_on_arc` относит точку к дуге.
+fn arc_bounds(start: &Point3<f64>, end: &Point3<f64>, eps: f64) -> [Vector3<f64>; 2] {
+    let a = start.coords.normalize();
+    let b = end.coords.normalize();
+
+    // Дуга лежит в трапеции с вершинами a, b, a / c, b / c,
+    // где c = cos(θ / 2) - расстояние от центра сферы до середины хорды
+    let cos_half = ((1. + a.dot(&b)) / 2.).max(0.).sqrt();
+    let bulge = 1. / cos_half.max(f64::EPSILON);
+
+    // Допуск копланарности в is_on_arc задан для ненормированной нормали |a x b| = sin θ
+    let sin = a.cross(&b).norm();
+    let margin = Vector3::repeat(eps + eps / sin.max(f64::EPSILON));
+
+    let corners = [a, b, a * bulge, b * bulge];
+    let min = corners
+        .iter()
+        .fold(Vector3::repeat(f64::INFINITY), |m, c| m.inf(c));
+    let max = corners
+        .iter()
+        .fold(Vector3::repeat(f64::NEG_INFINITY), |m, c| m.sup(c));
+
+    [min - margin, max + margin]
+}
+
+/// Широкая фаза поиска пересечений: равномерная сетка ячеек, покрывающая единичную сферу.
+/// Дуга регистрируется во всех ячейках, которые пересекает её ограничивающий параллелепипед,
+/// поэтому пересекающиеся дуги обязательно имеют общую ячейку.
+struct ArcGrid {
+    cells_per_axis: usize,
+    cells: BTreeMap<[usize; 3], Vec<usize>>,
+}
+
+impl ArcGrid {
+    fn new(arcs: &[Segment], vertices: &[Point3<f64>], eps: f64) -> Self {
+        // В среднем около одной дуги на ячейку поверхности сферы
+        let cells_per_axis = ((arcs.len() as f64).sqrt() / 2.).ceil().clamp(1., 64.) as usize;
+        let mut grid = ArcGrid {
+            cells_per_axis,
+            cells: BTreeMap::new(),
+        };
+
+        for (i, arc) in arcs.iter().enumerate() {
+            for cell in grid.cells_of(arc, vertices, eps) {
+                grid.cells.entry(cell).or_default().push(i);
+            }
+        }
+
+        grid
+    }
+
+    /// Индексы ячеек, которые пересекает ограничивающий параллелепипед дуги
+    fn cells_of(&self, arc: &Segment, vertices: &[Point3<f64>], eps: f64) -> Vec<[usize; 3]> {
+        let [min, max] = arc_bounds(&vertices[arc[0]], &vertices[arc[1]], eps);
+        let n = self.cells_per_axis;
+        let to_cell = |x: f64| (((x + 1.) / 2. * n as f64).floor().max(0.) as usize).min(n - 1);
+        let [lo, hi] = [min, max].map(|v| v.map(to_cell));
+
+        let mut cells = Vec::new();
+        for x in lo.x..=hi.x {
+            for y in lo.y..=hi.y {
+                for z in lo.z..=hi.z {
+                    cells.push([x, y, z]);
+                }
+            }
+        }
+        cells
+    }
+
+    /// Индексы зарегистрированных дуг, имеющих общую ячейку с данной
+    fn candidates(&self, arc: &Segment, vertices: &[Point3<f64>], eps: f64) -> BTreeSet<usize> {
+        self.cells_of(arc, vertices, eps)
+            .iter()
+            .filter_map(|cell| self.cells.get(cell))
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+/// Находит пересечения дуг двух сеток. Пары дуг отбираются широкой фазой по ячейкам
+/// сферы, проверяются параллельно и возвращаются упорядоченными по парам сегментов.
+fn find_arc_intersections(
+    segments_a: &[Segment],
+    segments_b: &[Segment],
+    vertices: &[Point3<f64>],
+    params: &MorphParams,
+) -> Vec<(Segment, Segment, Point3<f64>)> {
+    let eps = params.on_arc_eps;
+    let grid = ArcGrid::new(segments_b, vertices, eps);
+
+    let mut intersections: Vec<(Segment, Segment, Point3<f64>)> = segments_a
+        .par_iter()
+        .flat_map_iter(|&seg_a| {
+            // После отмены оставшиеся дуги не проверяются
+            let candidates = if params.cancellation.is_cancelled() {
+                BTreeSet::new()
+            } else {
+                grid.candidates(&seg_a, vertices, eps)
+            };
+            candidates.into_iter().filter_map(move |b_idx| {
+                let seg_b = segments_b[b_idx];
+
+                // Пропускаем, если сегменты имеют общие вершины
+                if seg_a[0] == seg_b[0]
+                    || seg_a[0] == seg_b[1]
+                    || seg_a[1] == seg_b[0]
+                    || seg_a[1] == seg_b[1]
+                {
+                    return None;
+                }
+
+                let arc_1 = [&vertices[seg_a[0]], &vertices[seg_a[1]]];
+                let arc_2 = [&vertices[seg_b[0]], &vertices[seg_b[1]]];
+
+                intersect_arcs(arc_1, arc_2, params).map(|point| (seg_a, seg_b, point))
+            })
+        })
+        .collect();
+
+    // Порядок добавления определяет индексы новых вершин, поэтому фиксируем его
+    intersections.sort_unstable_by_key(|&(seg_a, seg_b, _)| (seg_a, seg_b));
+    intersections
+}
+
+fn get_mesh_segments(mesh: &TriangleMesh) -> BTreeSet<Segment> {
     mesh.triangles()
         .iter()
         .flat_map(|&tri| {
@@ -250,9 +563,9 @@ fn get_mesh_segments(mesh: &TriangleMesh) -> HashSet<Segment> {
         .collect()
 }
 
-fn find_or_add_vertex(vertices: &mut Vec<Point3<f64>>, point: &Point3<f64>) -> usize {
+fn find_or_add_vertex(vertices: &mut Vec<Point3<f64>>, point: &Point3<f64>, eps: f64) -> usize {
     for (i, v) in vertices.iter().enumerate() {
-        if (v.coords - point.coords).norm() < VERTEX_MATCH_EPS {
+        if (v.coords - point.coords).norm() < eps {
             return i;
         }
     }
@@ -261,10 +574,31 @@ fn find_or_add_vertex(vertices: &mut Vec<Point3<f64>>, point: &Point3<f64>) -> u
     new_index
 }
 
-/// Создает карту уникальных вершин, объединяя совпадающие точки из двух сеток
+/// Вычисляет абсолютный допуск слияния вершин двух сеток.
+///
+/// Допуск задаётся относительно радиуса описанной вокруг начала координат сферы,
+/// поэтому для параметризованных сеток (единичная сфера) он совпадает с `vertex_match_eps`,
+/// а для сеток в мировых координатах масштабируется вместе с моделью.
+fn vertex_match_tolerance(
+    mesh_a: &TriangleMesh,
+    mesh_b: &TriangleMesh,
+    params: &MorphParams,
+) -> f64 {
+    let radius = mesh_a
+        .vertices_world()
+        .iter()
+        .chain(mesh_b.vertices_world())
+        .map(|v| v.coords.norm())
+        .fold(0., f64::max);
+
+    params.vertex_match_eps * radius
+}
+
+/// Создает карту уникальных вершин, объединяя совпадающие в пределах `merge_eps` точки из двух сеток
 fn create_unified_vertex_map(
     mesh_a: &TriangleMesh,
     mesh_b: &TriangleMesh,
+    merge_eps: f64,
 ) -> (Vec<Point3<f64>>, Vec<usize>, Vec<usize>) {
     let mut unified_vertices = Vec::new();
     let mut mapping_a = Vec::new();
@@ -272,13 +606,13 @@ fn create_unified_vertex_map(
 
     // Добавляем вершины из первой сетки
     for vertex in mesh_a.vertices_world() {
-        let idx = find_or_add_vertex(&mut unified_vertices, vertex);
+        let idx = find_or_add_vertex(&mut unified_vertices, vertex, merge_eps);
         mapping_a.push(idx);
     }
 
     // Добавляем вершины из второй сетки, проверяя на дубликаты
     for vertex in mesh_b.vertices_world() {
-        let idx = find_or_add_vertex(&mut unified_vertices, vertex);
+        let idx = find_or_add_vertex(&mut unified_vertices, vertex, merge_eps);
         mapping_b.push(idx);
     }
 
@@ -290,7 +624,8 @@ fn find_vertices_on_edges(
     vertex_mapping: &[usize],
     segments: &[Segment],
     all_vertices: &[Point3<f64>],
-    segment_map: &mut HashMap<Segment, HashSet<usize>>,
+    segment_map: &mut BTreeMap<Segment, BTreeSet<usize>>,
+    params: &MorphParams,
 ) {
     for &vertex_idx in vertex_mapping {
         let vertex = &all_vertices[vertex_idx];
@@ -300,7 +635,9 @@ fn find_vertices_on_edges(
             let end = &all_vertices[segment[1]];
 
             // Проверяем, лежит ли вершина на этой дуге
-            if is_on_arc(vertex, start, end) && vertex_idx != segment[0] && vertex_idx != segment[1]
+            if is_on_arc(vertex, start, end, params.on_arc_eps)
+                && vertex_idx != segment[0]
+                && vertex_idx != segment[1]
             {
                 segment_map.entry(segment).or_default().insert(vertex_idx);
             }
@@ -309,16 +646,16 @@ fn find_vertices_on_edges(
 }
 /// Основная функция для построения DCEL из пересечения двух сеток.
 /// Корректно обрабатывает совпадающие вершины и случаи, когда вершина лежит на ребре.
-pub fn create_dcel_map(mesh_a: &TriangleMesh, mesh_b: &TriangleMesh) -> Result<DCEL, String> {
+/// Вместе с DCEL возвращает происхождение каждого отрезка пересечённой сетки.
+pub fn create_dcel_map(
+    mesh_a: &TriangleMesh,
+    mesh_b: &TriangleMesh,
+    params: &MorphParams,
+) -> Result<(DCEL, BTreeMap<Segment, SegmentOrigin>), String> {
     // 1. Создаем унифицированную карту вершин, избегая дублирования
-    let (mut all_vertices, mapping_a, mapping_b) = create_unified_vertex_map(mesh_a, mesh_b);
-
-    println!(
-        "{} {} {}",
-        mesh_a.vertices.len(),
-        mesh_b.vertices.len(),
-        all_vertices.len()
-    );
+    let merge_eps = vertex_match_tolerance(mesh_a, mesh_b, params);
+    let (mut all_vertices, mapping_a, mapping_b) =
+        create_unified_vertex_map(mesh_a, mesh_b, merge_eps);
 
     // 2. Получаем сегменты из обеих сеток с правильными индексами
     let segments_a: Vec<Segment> = get_mesh_segments(mesh_a)
@@ -340,7 +677,7 @@ pub fn create_dcel_map(mesh_a: &TriangleMesh, mesh_b: &TriangleMesh) -> Result<D
         .collect();
 
     // 3. Ассоциативный массив для хранения всех вершин, которые лежат на каждом отрезке
-    let mut segment_map: HashMap<Segment, HashSet<usize>> = HashMap::new();
+    let mut segment_map: BTreeMap<Segment, BTreeSet<usize>> = BTreeMap::new();
 
     // Добавляем все отрезки в ассоциативный массив
     for &s in &segments_a {
@@ -352,38 +689,48 @@ pub fn create_dcel_map(mesh_a: &TriangleMesh, mesh_b: &TriangleMesh) -> Result<D
 
     // 4. Находим вершины, которые лежат на рёбрах другой сетки
     // Проверяем вершины сетки A на рёбрах сетки B
-    find_vertices_on_edges(&mapping_a, &segments_b, &all_vertices, &mut segment_map);
+    find_vertices_on_edges(
+        &mapping_a,
+        &segments_b,
+        &all_vertices,
+        &mut segment_map,
+        params,
+    );
 
     // Проверяем вершины сетки B на рёбрах сетки A
-    find_vertices_on_edges(&mapping_b, &segments_a, &all_vertices, &mut segment_map);
-
-    // 5. Находим точки пересечения между дугами
-    for &seg_a in &segments_a {
-        for &seg_b in &segments_b {
-            // Пропускаем, если сегменты имеют общие вершины
-            if seg_a[0] == seg_b[0]
-                || seg_a[0] == seg_b[1]
-                || seg_a[1] == seg_b[0]
-                || seg_a[1] == seg_b[1]
-            {
-                continue;
-            }
-
-            let arc_1 = [&all_vertices[seg_a[0]], &all_vertices[seg_a[1]]];
-            let arc_2 = [&all_vertices[seg_b[0]], &all_vertices[seg_b[1]]];
+    find_vertices_on_edges(
+        &mapping_b,
+        &segments_a,
+        &all_vertices,
+        &mut segment_map,
+        params,
+    );
 
-            if let Some(intersection_point) = intersect_arcs(arc_1, arc_2) {
-                let inter_idx = find_or_add_vertex(&mut all_vertices, &intersection_point);
-                segment_map.get_mut(&seg_a).unwrap().insert(inter_idx);
-                segment_map.get_mut(&seg_b).unwrap().insert(inter_idx);
-            }
-        }
+    // 5. Находим точки пересечения между дугами.
+    // Все вершины, добавленные начиная с этого индекса, являются точками пересечения
+    let first_intersection_vertex = all_vertices.len();
+
+    params.cancellation.check()?;
+    let intersections = find_arc_intersections(&segments_a, &segments_b, &all_vertices, params);
+    params.cancellation.check()?;
+    for (seg_a, seg_b, intersection_point) in intersections {
+        let inter_idx = find_or_add_vertex(&mut all_vertices, &intersection_point, merge_eps);
+        segment_map.get_mut(&seg_a).unwrap().insert(inter_idx);
+        segment_map.get_mut(&seg_b).unwrap().insert(inter_idx);
     }
 
-    // 6. Генерируем финальный список подотрезков
-    let mut all_segments: HashSet<Segment> = HashSet::new();
+    // 6. Генерируем финальный список подотрезков с их происхождением
+    let source_segments: BTreeSet<Segment> = segments_a.into_iter().collect();
+    let mut all_segments: BTreeMap<Segment, SegmentOrigin> = BTreeMap::new();
 
     for ([start_idx, end_idx], points_idx_set) in segment_map.into_iter() {
+        params.cancellation.check()?;
+        let parent_origin = if source_segments.contains(&[start_idx, end_idx]) {
+            SegmentOrigin::Source
+        } else {
+            SegmentOrigin::Target
+        };
+
         let mut points_indices: Vec<usize> = points_idx_set.into_iter().collect();
 
         // Сортируем точки вдоль дуги на основе их угла поворота от начальной точки
@@ -415,21 +762,51 @@ pub fn create_dcel_map(mesh_a: &TriangleMesh, mesh_b: &TriangleMesh) -> Result<D
                 seg.swap(0, 1);
             }
 
-            all_segments.insert(seg);
+            let origin = if seg[1] >= first_intersection_vertex {
+                SegmentOrigin::Intersection
+            } else {
+                parent_origin
+            };
+            all_segments.entry(seg).or_insert(origin);
         }
     }
 
-    DCEL::new(all_vertices, all_segments)
+    let dcel = DCEL::new(all_vertices, all_segments.keys().copied())?;
+    Ok((dcel, all_segments))
+}
+
+/// Классифицирует треугольники суперсетки по происхождению их рёбер.
+///
+/// Треугольник, ограниченный только рёбрами одной сетки, относится к этой сетке;
+/// если среди рёбер есть отрезки пересечения или рёбра обеих сеток - к пересечению.
+/// Диагонали, добавленные при триангуляции граней, не учитываются.
+pub fn classify_triangles(
+    triangles: &[Triangle],
+    segment_origins: &BTreeMap<Segment, SegmentOrigin>,
+) -> Vec<SegmentOrigin> {
+    triangles
+        .iter()
+        .map(|&(a, b, c)| {
+            let origins: BTreeSet<SegmentOrigin> = [[a, b], [b, c], [c, a]]
+                .into_iter()
+                .filter_map(|mut seg| {
+                    seg.sort_unstable();
+                    segment_origins.get(&seg).copied()
+                })
+                .collect();
+
+            match origins.len() {
+                1 => *origins.iter().next().unwrap(),
+                _ => SegmentOrigin::Intersection,
+            }
+        })
+        .collect()
 }
 
 /// Треангулирует плоскую грань многогранника с использованием триангуляции Делоне.
 fn triangulate_face(face_vertices: &Vec<&Vertex>) -> Result<Vec<usize>, Box<dyn Error>> {
     // Проверка минимального количества вершин
     if face_vertices.len() < 3 {
-        eprintln!(
-            "DEBUG: triangulate_face - недостаточно вершин: {} (требуется >= 3)",
-            face_vertices.len()
-        );
         return Err(format!(
             "Грань должна содержать минимум 3 вершины, получено: {}",
             face_vertices.len()
@@ -451,13 +828,6 @@ fn triangulate_face(face_vertices: &Vec<&Vertex>) -> Result<Vec<usize>, Box<dyn
 
     // Проверка валидности нормали
     if normal.norm() < f64::EPSILON {
-        eprintln!("DEBUG: triangulate_face - не удалось вычислить нормаль к грани");
-        eprintln!("  v1 = {}", v1);
-        if face_vertices.len() > 2 {
-            eprintln!("  v2 = {}", face_vertices[2] - face_vertices[0]);
-        }
-        eprintln!("  normal.norm() = {}", normal.norm());
-        eprintln!("  количество вершин: {}", face_vertices.len());
         return Err("Не удалось вычислить нормаль к грани: все вершины коллинеарны".into());
     }
 
@@ -468,8 +838,6 @@ fn triangulate_face(face_vertices: &Vec<&Vertex>) -> Result<Vec<usize>, Box<dyn
     let mut u_vec = face_vertices[1] - face_vertices[0];
 
     if u_vec.norm_squared() < f64::EPSILON {
-        eprintln!("DEBUG: triangulate_face - вырожденное первое ребро грани");
-        eprintln!("  u_vec.norm_squared() = {}", u_vec.norm_squared());
         return Err("Не удалось построить базис: первое ребро вырождено".into());
     }
 
@@ -480,10 +848,6 @@ fn triangulate_face(face_vertices: &Vec<&Vertex>) -> Result<Vec<usize>, Box<dyn
 
     // Проверяем валидность второго базисного вектора
     if v_vec.norm_squared() < f64::EPSILON {
-        eprintln!("DEBUG: triangulate_face - вырожденный второй базисный вектор");
-        eprintln!("  v_vec.norm_squared() = {}", v_vec.norm_squared());
-        eprintln!("  normal = {}", normal);
-        eprintln!("  u_vec = {}", u_vec);
         return Err("Не удалось построить ортонормированный базис грани".into());
     }
 
@@ -519,13 +883,8 @@ pub fn triangulate_dcel(dcel: &DCEL) -> Result<Vec<Triangle>, Box<dyn Error>> {
         let face_vertices_refs: Vec<&Vertex> =
             vertex_indices.iter().map(|&i| &dcel.vertices[i]).collect();
 
-        let local_triangles = triangulate_face(&face_vertices_refs).map_err(|e| {
-            eprintln!(
-                "DEBUG: triangulate_dcel - ошибка триангуляции грани {}: {}",
-                face_idx, e
-            );
-            format!("Ошибка триангуляции грани {}: {}", face_idx, e)
-        })?;
+        let local_triangles = triangulate_face(&face_vertices_refs)
+            .map_err(|e| format!("Ошибка триангуляции грани {}: {}", face_idx, e))?;
 
         let global_triangles: Vec<Triangle> = local_triangles
             .chunks(3)
@@ -544,80 +903,146 @@ pub fn triangulate_dcel(dcel: &DCEL) -> Result<Vec<Triangle>, Box<dyn Error>> {
     Ok(triangles)
 }
 
+/// Строит суперсетку: вершины, треугольники и происхождение каждого треугольника
 pub fn create_supermesh(
     parametrized_source_mesh: &TriangleMesh,
     parametrized_target_mesh: &TriangleMesh,
-) -> Result<(Vec<Vertex>, Vec<Triangle>), String> {
+    params: &MorphParams,
+) -> Result<Supermesh, String> {
     // 1. Пересечение исходной и целевой сеток
-    let dcel = create_dcel_map(parametrized_source_mesh, parametrized_target_mesh)?;
+    let (dcel, segment_origins) =
+        create_dcel_map(parametrized_source_mesh, parametrized_target_mesh, params)?;
 
     // 2. Триангуляция граней пересеченной сетки
     let triangles =
         triangulate_dcel(&dcel).map_err(|e| format!("Ошибка триангуляции DCEL: {}", e))?;
 
-    Ok((dcel.vertices, triangles))
+    // 3. Происхождение треугольников для отладочной раскраски
+    let triangle_origins = classify_triangles(&triangles, &segment_origins);
+
+    Ok((dcel.vertices, triangles, triangle_origins))
+}
+
+// Центрально проецирует точку сферы на плоскость треугольника сферической сетки.
+// Возвращает барицентрические координаты проекции или None, если треугольник
+// находится на противоположной стороне сферы.
+fn project_barycentric(p: &Vertex, v0: &Vertex, v1: &Vertex, v2: &Vertex) -> Option<Vector3<f64>> {
+    // 1. Находим нормаль к плоскости треугольника, направленную от центра сферы
+    let mut normal = (v1 - v0).cross(&(v2 - v1)).normalize();
+
+    // Разворачиваем нормаль, если она направленна в центр
+    if normal.dot(&v0.coords) < 0.0 {
+        normal = -normal;
+    }
+
+    // 2. Проецируем точку на плоскость треугольника
+    let t = normal.dot(&p.coords);
+
+    // Отбрасываем треугольники на противоположной стороне сферы
+    if t < 0. {
+        return None;
+    }
+
+    let projected_point = if t < f64::EPSILON {
+        *p
+    } else {
+        p * (normal.dot(&v0.coords) / t)
+    };
+
+    Some(barycentric(&projected_point, v0, v1, v2))
 }
 
 // Найти треугольник на сетке, которому принадлежит точка.
 // Возвращает индекс треугольника и барицентрические координаты точки в этом треугольнике.
+//
+// Точка рядом с ребром может попасть в допуск нескольких соседних треугольников.
+// Выбирается тот, внутри которого она лежит глубже всего (наибольшая минимальная
+// барицентрическая координата), а отрицательные в пределах допуска координаты
+// обнуляются. Тогда точка на общем ребре интерполируется только по его концам
+// и её положение не зависит от того, какой из соседей выбран.
 fn find_enclosing_triangle(
     p: &Vertex,
     mesh: &TriangleMesh,
+    params: &MorphParams,
 ) -> Result<(usize, Vector3<f64>), String> {
     let mesh_vertices = mesh.vertices_world();
+    let mut best: Option<(usize, Vector3<f64>)> = None;
 
     for (i, tri) in mesh.triangles().iter().enumerate() {
         let v0 = &mesh_vertices[tri.0];
         let v1 = &mesh_vertices[tri.1];
         let v2 = &mesh_vertices[tri.2];
 
-        // 1. Находим нормаль к плоскости треугольника, направленную от центра сферы
-        let mut normal = (v1 - v0).cross(&(v2 - v1)).normalize();
-
-        // Разворачиваем нормаль, если она направленна в центр
-        if normal.dot(&v0.coords) < 0.0 {
-            normal = -normal;
-        }
-
-        // 2. Проецируем точку на плоскость треугольника
-        let t = normal.dot(&p.coords);
-
-        // Отбрасываем треугольники на противоположной стороне сферы
-        if t < 0. {
+        // Определяем принадлежность точки треугольнику по барицентрическим координатам
+        let Some(bary) = project_barycentric(p, v0, v1, v2) else {
             continue;
-        }
-
-        let projected_point = if t < f64::EPSILON {
-            *p
-        } else {
-            p * (normal.dot(&v0.coords) / t)
         };
 
-        if t < f64::EPSILON {
-            println!("{}", projected_point);
+        if bary.iter().all(|&coord| coord > -params.barycentric_slack)
+            && best.is_none_or(|(_, best_bary)| bary.min() > best_bary.min())
+        {
+            best = Some((i, bary));
         }
+    }
 
-        // 3. Определяем принадлежность точки треугольнику по барицентрическим координатам
-        let bary = barycentric(&projected_point, v0, v1, v2);
-
-        if bary.iter().all(|&coord| coord > -1e-12) {
-            return Ok((i, bary));
-        }
+    // Из-за погрешностей точка может не попасть ни в один треугольник даже на
+    // замкнутой сетке: тогда берём ближайший по углу до центроида треугольник
+    let (tri_idx, bary) = match best {
+        Some(found) => found,
+        None => nearest_triangle_by_angle(p, mesh).ok_or_else(|| {
+            "Не найден треугольник, содержащий точку: сетка не содержит треугольников.".to_string()
+        })?,
+    };
+
+    let clamped = bary.map(|coord| coord.max(0.));
+    let sum = clamped.sum();
+    if sum < f64::EPSILON {
+        return Ok((tri_idx, Vector3::repeat(1. / 3.)));
     }
+    Ok((tri_idx, clamped / sum))
+}
+
+// Треугольник, центроид которого виден из центра сферы под наименьшим углом к точке,
+// и барицентрические координаты точки в нём (возможно, вне симплекса)
+fn nearest_triangle_by_angle(p: &Vertex, mesh: &TriangleMesh) -> Option<(usize, Vector3<f64>)> {
+    let mesh_vertices = mesh.vertices_world();
+    let direction = p.coords.try_normalize(f64::EPSILON)?;
+
+    let centroid_cos = |tri: &Triangle| {
+        let centroid =
+            mesh_vertices[tri.0].coords + mesh_vertices[tri.1].coords + mesh_vertices[tri.2].coords;
+        centroid
+            .try_normalize(f64::EPSILON)
+            .map_or(-1., |c| c.dot(&direction))
+    };
+    let (tri_idx, tri) = mesh
+        .triangles()
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| centroid_cos(a).total_cmp(&centroid_cos(b)))?;
 
-    Err("Не найден треугольник, содержащий точку. Возможно, сетка не замкнута или точка находится вне сетки.".to_string())
+    let [v0, v1, v2] = [tri.0, tri.1, tri.2].map(|v| &mesh_vertices[v]);
+    let bary = project_barycentric(p, v0, v1, v2).unwrap_or_else(|| barycentric(p, v0, v1, v2));
+    Some((tri_idx, bary))
 }
 
-// Расположить рассчитать реальные координаты точке на сетке объекта
+/// Положение точки на сетке: индекс треугольника и барицентрические координаты в нём
+pub type MeshLocation = (usize, Vector3<f64>);
+
+// Расположить рассчитать реальные координаты точке на сетке объекта.
+// Вместе с координатами возвращает треугольник параметризованной сетки,
+// в котором найдена каждая точка.
 pub fn relocate_vertices_on_mesh(
     parametrized_vertices: &[Vertex],
     parametrized_mesh: &TriangleMesh,
     real_vertices: &[Vertex],
-) -> Result<Vec<Vertex>, String> {
+    params: &MorphParams,
+) -> Result<(Vec<Vertex>, Vec<MeshLocation>), String> {
     let mut relocated_vertices = Vec::new();
+    let mut locations = Vec::new();
 
     for v in parametrized_vertices {
-        let (tri_idx, bary) = find_enclosing_triangle(v, parametrized_mesh)?;
+        let (tri_idx, bary) = find_enclosing_triangle(v, parametrized_mesh, params)?;
         let tri = parametrized_mesh.triangles()[tri_idx];
 
         relocated_vertices.push(Vertex::from(
@@ -625,30 +1050,371 @@ pub fn relocate_vertices_on_mesh(
                 + bary.y * real_vertices[tri.1].coords
                 + bary.z * real_vertices[tri.2].coords,
         ));
+        locations.push((tri_idx, bary));
     }
 
-    Ok(relocated_vertices)
+    debug_assert!(
+        relocated_vertices.iter().all(|v| all_finite(v.iter())),
+        "relocate_vertices_on_mesh: NaN в перенесённых вершинах"
+    );
+    Ok((relocated_vertices, locations))
 }
 
+/// Нормали вершин суперсетки на исходной сетке.
+///
+/// Для каждой вершины нормаль интерполируется по её барицентрическим координатам
+/// `locations` (см. [`relocate_vertices_on_mesh`]) между нормалями в углах объемлющего
+/// треугольника исходной сетки (см. [`TriangleMesh::corner_normals`]), так же как
+/// переносятся сами вершины. Соседние треугольники суперсетки делят нормаль в общей
+/// вершине, поэтому гладкая сетка остаётся гладкой, а не распадается на плоские грани.
 pub fn find_normals(
+    locations: &[MeshLocation],
+    corner_normals: &[[Vector4<f64>; 3]],
+) -> Vec<Vector4<f64>> {
+    locations
+        .iter()
+        .map(|&(tri_idx, bary)| {
+            let [n0, n1, n2] = corner_normals[tri_idx];
+            (n0 * bary.x + n1 * bary.y + n2 * bary.z)
+                .try_normalize(f64::EPSILON)
+                .unwrap_or(n0)
+        })
+        .collect()
+}
+
+/// Текстурные координаты углов треугольников суперсетки на исходной сетке.
+///
+/// Каждый треугольник суперсетки целиком лежит внутри одного треугольника исходной
+/// сетки, поэтому координаты всех его углов интерполируются по этому треугольнику,
+/// что сохраняет швы текстурной развёртки.
+pub fn find_uvs(
     parametrized_vertices: &[Vertex],
     triangles: &[Triangle],
     parametrized_mesh: &TriangleMesh,
-    normals: &[Vector4<f64>],
-) -> Result<Vec<Vector4<f64>>, String> {
-    let mut result_normals = Vec::new();
+    uvs: &[[Vector2<f64>; 3]],
+    params: &MorphParams,
+) -> Result<Vec<[Vector2<f64>; 3]>, String> {
+    let mesh_vertices = parametrized_mesh.vertices_world();
+    let mut result_uvs = Vec::with_capacity(triangles.len());
 
     for tri in triangles {
-        let center = Point3::from(
-            (parametrized_vertices[tri.0].coords
-                + parametrized_vertices[tri.1].coords
-                + parametrized_vertices[tri.2].coords)
-                / 3.,
+        let corners = [tri.0, tri.1, tri.2].map(|v| parametrized_vertices[v]);
+        let center = Point3::from((corners[0].coords + corners[1].coords + corners[2].coords) / 3.);
+
+        let (tri_idx, _) = find_enclosing_triangle(&center, parametrized_mesh, params)?;
+        let mesh_tri = parametrized_mesh.triangles()[tri_idx];
+        let [uv0, uv1, uv2] = uvs[tri_idx];
+
+        result_uvs.push(corners.map(|corner| {
+            let bary = project_barycentric(
+                &corner,
+                &mesh_vertices[mesh_tri.0],
+                &mesh_vertices[mesh_tri.1],
+                &mesh_vertices[mesh_tri.2],
+            )
+            .unwrap_or_else(|| Vector3::repeat(1. / 3.));
+            uv0 * bary.x + uv1 * bary.y + uv2 * bary.z
+        }));
+    }
+
+    Ok(result_uvs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::morph::Morph;
+    use nalgebra::Rotation3;
+
+    fn load_model(name: &str) -> TriangleMesh {
+        let path = format!("{}/models/{}", env!("CARGO_MANIFEST_DIR"), name);
+        TriangleMesh::from_obj(&path).unwrap()
+    }
+
+    #[test]
+    fn balanced_neighbors_keep_vertex_in_place() {
+        // Соседи попарно противоположны, их сумма - нулевой вектор
+        let vertices = vec![
+            Vertex::new(0., 0., 1.),
+            Vertex::new(1., 0., 0.),
+            Vertex::new(-1., 0., 0.),
+            Vertex::new(0., 1., 0.),
+            Vertex::new(0., -1., 0.),
+        ];
+        let neighbors = BTreeSet::from([1, 2, 3, 4]);
+
+        let relaxed = relaxed_position(&neighbors, &vertices, &vertices[0]);
+        assert!(relaxed.iter().all(|c| c.is_finite()));
+        assert_eq!(relaxed, vertices[0]);
+
+        let unbalanced = BTreeSet::from([1, 3]);
+        let relaxed = relaxed_position(&unbalanced, &vertices, &vertices[0]);
+        assert!((relaxed.coords.norm() - 1.).abs() < 1e-12);
+    }
+
+    fn radius(vertices: &[Vertex]) -> f64 {
+        vertices.iter().map(|v| v.coords.norm()).fold(0., f64::max)
+    }
+
+    #[test]
+    fn tiny_scale_mesh_morphs() {
+        let scale = 1e-4;
+        let mut source = load_model("apple2.obj");
+        let mut target = load_model("lemon.obj");
+        for mesh in [&mut source, &mut target] {
+            mesh.vertices_mut()
+                .iter_mut()
+                .for_each(|v| v.coords *= scale);
+            mesh.vertices_world = mesh.vertices.to_vec();
+        }
+        let source_radius = radius(source.vertices_world());
+
+        let morph = Morph::with_params(source, target, &MorphParams::default()).unwrap();
+
+        // При t = 0 вершины суперсетки лежат на поверхности исходной модели
+        let morph_radius = radius(morph.vertices());
+        assert!(morph_radius > 0.);
+        assert!(morph_radius <= source_radius * (1. + 1e-9));
+    }
+
+    #[test]
+    fn supermesh_is_deterministic() {
+        let params = MorphParams::default();
+        let mut source = load_model("apple2.obj");
+        let mut target = load_model("lemon.obj");
+        parametrize_mesh(&mut source, &params).unwrap();
+        parametrize_mesh(&mut target, &params).unwrap();
+
+        let (vertices_1, triangles_1, _) = create_supermesh(&source, &target, &params).unwrap();
+        let (vertices_2, triangles_2, _) = create_supermesh(&source, &target, &params).unwrap();
+
+        assert_eq!(vertices_1, vertices_2);
+        assert_eq!(triangles_1, triangles_2);
+    }
+
+    #[test]
+    fn broad_phase_finds_all_arc_intersections() {
+        let params = MorphParams::default();
+        let rotation = Rotation3::from_euler_angles(0.3, 0.5, 0.7);
+        let mesh_a = TriangleMesh::unit_sphere(1);
+        let mut mesh_b = TriangleMesh::unit_sphere(1);
+        mesh_b.vertices_world = mesh_b.vertices_world.iter().map(|v| rotation * v).collect();
+
+        // Вершины обеих сеток в общем массиве, индексы сетки B смещены
+        let offset = mesh_a.vertices_world().len();
+        let vertices: Vec<Vertex> = mesh_a
+            .vertices_world()
+            .iter()
+            .chain(mesh_b.vertices_world())
+            .copied()
+            .collect();
+        let segments_a: Vec<Segment> = get_mesh_segments(&mesh_a).into_iter().collect();
+        let segments_b: Vec<Segment> = get_mesh_segments(&mesh_b)
+            .into_iter()
+            .map(|[start, end]| [start + offset, end + offset])
+            .collect();
+
+        let mut brute_force = Vec::new();
+        for &seg_a in &segments_a {
+            for &seg_b in &segments_b {
+                let arc_1 = [&vertices[seg_a[0]], &vertices[seg_a[1]]];
+                let arc_2 = [&vertices[seg_b[0]], &vertices[seg_b[1]]];
+                if let Some(point) = intersect_arcs(arc_1, arc_2, &params) {
+                    brute_force.push((seg_a, seg_b, point));
+                }
+            }
+        }
+
+        assert!(!brute_force.is_empty());
+        assert_eq!(
+            find_arc_intersections(&segments_a, &segments_b, &vertices, &params),
+            brute_force
+        );
+    }
+
+    /// Вытянутая изогнутая сфера ("банан"): центр масс лежит вне тела
+    fn banana() -> TriangleMesh {
+        let sphere = TriangleMesh::unit_sphere(2);
+        let vertices = sphere
+            .vertices()
+            .iter()
+            .map(|v| {
+                let x = v.x * 2.5;
+                Vertex::new(x, v.y * 0.5 + 0.4 * x * x, v.z * 0.5)
+            })
+            .collect();
+        TriangleMesh::from_parts(vertices, sphere.triangles().to_vec(), None)
+    }
+
+    #[test]
+    fn relaxation_converges_after_several_rounds() {
+        // Проекция банана из внутренней точки выворачивает часть граней,
+        // и релаксация идёт, пока они не развернутся и смещения не станут малы
+        let params = MorphParams::default();
+        let report = parametrize_mesh(&mut banana(), &params).unwrap();
+
+        assert!(report.converged);
+        assert!(
+            (2..params.relaxation_rounds_limit).contains(&report.rounds),
+            "раундов: {}",
+            report.rounds
         );
+        assert!(report.final_residual > 0.);
+        assert!(report.final_residual < params.relaxation_threshold);
+    }
+
+    #[test]
+    fn curved_mesh_parametrizes_without_inversions() {
+        let mut banana = banana();
+        assert!(banana.validate_manifold().is_ok());
+
+        parametrize_mesh(&mut banana, &MorphParams::default()).unwrap();
 
-        let (tri_idx, _) = find_enclosing_triangle(&center, parametrized_mesh)?;
-        result_normals.push(normals[tri_idx]);
+        let on_sphere = banana.vertices_world();
+        let inverted = banana
+            .triangles()
+            .iter()
+            .filter(|tri| {
+                let [v0, v1, v2] = [tri.0, tri.1, tri.2].map(|i| on_sphere[i].coords);
+                v0.dot(&v1.cross(&v2)) <= 0.
+            })
+            .count();
+        assert_eq!(inverted, 0);
     }
 
-    Ok(result_normals)
+    #[test]
+    fn cancelled_parametrization_returns_early() {
+        let params = MorphParams::default();
+        params.cancellation.cancel();
+
+        let mut mesh = load_model("apple2.obj");
+        let original = mesh.vertices_world().clone();
+
+        assert_eq!(
+            parametrize_mesh(&mut mesh, &params),
+            Err(CANCELLED.to_string())
+        );
+        assert_eq!(mesh.vertices_world(), &original);
+    }
+
+    fn unify(
+        points_a: Vec<Vertex>,
+        points_b: Vec<Vertex>,
+    ) -> (Vec<Vertex>, Vec<usize>, Vec<usize>) {
+        let mut mesh_a = TriangleMesh::default();
+        mesh_a.vertices_world = points_a;
+        let mut mesh_b = TriangleMesh::default();
+        mesh_b.vertices_world = points_b;
+
+        let merge_eps = vertex_match_tolerance(&mesh_a, &mesh_b, &MorphParams::default());
+        create_unified_vertex_map(&mesh_a, &mesh_b, merge_eps)
+    }
+
+    #[test]
+    fn close_vertices_are_not_merged() {
+        let x = Vertex::new(1., 0., 0.);
+        let y = Vertex::new(0., 1., 0.);
+        let near_x = Vertex::from(Vector3::new(1., 1e-4, 0.).normalize());
+
+        let (unified, mapping_a, mapping_b) = unify(vec![x, y], vec![y, near_x]);
+
+        assert_eq!(unified.len(), 3);
+        assert_eq!(mapping_b[0], mapping_a[1]);
+        assert_ne!(mapping_b[1], mapping_a[0]);
+    }
+
+    #[test]
+    fn merge_tolerance_scales_with_mesh() {
+        let scale = 1e-3;
+        let x = Vertex::new(scale, 0., 0.);
+        let y = Vertex::new(0., scale, 0.);
+        let near_x = Vertex::from(Vector3::new(1., 1e-4, 0.).normalize() * scale);
+
+        let (unified, mapping_a, mapping_b) = unify(vec![x, y], vec![y, near_x]);
+
+        assert_eq!(unified.len(), 3);
+        assert_eq!(mapping_b[0], mapping_a[1]);
+        assert_ne!(mapping_b[1], mapping_a[0]);
+    }
+
+    // Тетраэдр, вписанный в единичную сферу; `sign = -1` даёт двойственный
+    // тетраэдр, рёбра которого пересекают рёбра исходного в центрах граней куба
+    fn spherical_tetrahedron(sign: f64) -> TriangleMesh {
+        let vertices = [[1., 1., 1.], [1., -1., -1.], [-1., 1., -1.], [-1., -1., 1.]]
+            .into_iter()
+            .map(|[x, y, z]| Vertex::from(Vector3::new(x, y, z).normalize() * sign))
+            .collect();
+        let triangles = vec![(0, 1, 2), (0, 3, 1), (0, 2, 3), (1, 3, 2)];
+        TriangleMesh::from_parts(vertices, triangles, None)
+    }
+
+    #[test]
+    fn overlapping_tetrahedra_produce_intersection_segments() {
+        let (_, origins) = create_dcel_map(
+            &spherical_tetrahedron(1.),
+            &spherical_tetrahedron(-1.),
+            &MorphParams::default(),
+        )
+        .unwrap();
+
+        assert!(
+            origins
+                .values()
+                .any(|&origin| origin == SegmentOrigin::Intersection)
+        );
+    }
+
+    #[test]
+    fn point_on_shared_edge_relocates_independent_of_triangle_order() {
+        let params = MorphParams::default();
+        let forward = spherical_tetrahedron(1.);
+        let mut reversed_triangles = forward.triangles().clone();
+        reversed_triangles.reverse();
+        let reversed =
+            TriangleMesh::from_parts(forward.vertices_world().clone(), reversed_triangles, None);
+
+        // Середина ребра (0, 1), общего для треугольников (0, 1, 2) и (0, 3, 1)
+        let sphere = forward.vertices_world();
+        let on_edge = Vertex::from((sphere[0].coords + sphere[1].coords).normalize());
+        let real_vertices = [[0., 0., 2.], [3., 0., 0.], [0., 5., 0.], [-1., -1., -1.]]
+            .map(|[x, y, z]| Vertex::new(x, y, z));
+        let expected = (real_vertices[0].coords + real_vertices[1].coords) / 2.;
+
+        for mesh in [&forward, &reversed] {
+            let (relocated, _) =
+                relocate_vertices_on_mesh(&[on_edge], mesh, &real_vertices, &params).unwrap();
+            assert!(
+                (relocated[0].coords - expected).norm() < 1e-9,
+                "{}",
+                relocated[0]
+            );
+        }
+    }
+
+    #[test]
+    fn point_outside_all_triangles_falls_back_to_nearest() {
+        let params = MorphParams::default();
+        let tetrahedron = spherical_tetrahedron(1.);
+        let sphere = tetrahedron.vertices_world();
+        let patch = TriangleMesh::from_parts(sphere.clone(), vec![(0, 1, 2)], None);
+
+        // Точка чуть за ребром (0, 1) и немного вне сферы
+        let midpoint = (sphere[0].coords + sphere[1].coords).normalize();
+        let outside = midpoint + (sphere[3].coords - midpoint) * 1e-4;
+        let point = Vertex::from(outside.normalize() * 1.001);
+        let real_vertices = [[0., 0., 2.], [3., 0., 0.], [0., 5., 0.], [-1., -1., -1.]]
+            .map(|[x, y, z]| Vertex::new(x, y, z));
+        let expected = (real_vertices[0].coords + real_vertices[1].coords) / 2.;
+
+        let (relocated, locations) =
+            relocate_vertices_on_mesh(&[point], &patch, &real_vertices, &params).unwrap();
+
+        assert_eq!(locations[0].0, 0);
+        assert!(locations[0].1.iter().all(|&coord| coord >= 0.));
+        assert!(
+            (relocated[0].coords - expected).norm() < 1e-2,
+            "{}",
+            relocated[0]
+        );
+    }
 }
@@ -0,0 +1,305 @@
+use crate::objects::model3d::Triangle;
+use crate::utils::triangles::ray_triangle_intersect;
+use nalgebra::{Point3, Vector3, Vector4};
+use rayon::prelude::*;
+use std::f64::consts::PI;
+
+/// Количество лучей, испускаемых из каждой вершины
+const AO_SAMPLES: usize = 32;
+/// Дальность лучей относительно радиуса сетки
+const AO_RADIUS_FACTOR: f64 = 0.5;
+/// Смещение начала луча вдоль нормали относительно радиуса сетки
+const AO_RAY_OFFSET_FACTOR: f64 = 1e-6;
+
+/// Направления лучей в полусфере вокруг оси Z (спираль Фибоначчи)
+fn hemisphere_directions(count: usize) -> Vec<Vector3<f64>> {
+    let golden_angle = PI * (3. - 5f64.sqrt());
+
+    (0..count)
+        .map(|i| {
+            let z = 1. - (i as f64 + 0.5) / count as f64;
+            let r = (1. - z * z).sqrt();
+            let phi = golden_angle * i as f64;
+            Vector3::new(r * phi.cos(), r * phi.sin(), z)
+        })
+        .collect()
+}
+
+/// Равномерная сетка ячеек над ограничивающим параллелепипедом сетки.
+/// Треугольник регистрируется во всех ячейках, которые пересекает его ограничивающий
+/// параллелепипед, а луч проверяет только треугольники ячеек на своём пути
+/// (обход ячеек по алгоритму Амантидеса - Ву).
+struct TriangleGrid {
+    min: Vector3<f64>,
+    cell_size: Vector3<f64>,
+    cells_per_axis: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl TriangleGrid {
+    fn new(vertices: &[Point3<f64>], triangles: &[Triangle], margin: f64) -> Self {
+        let (min, max) = vertices
+            .iter()
+            .fold((vertices[0].coords, vertices[0].coords), |(min, max), v| {
+                (min.inf(&v.coords), max.sup(&v.coords))
+            });
+        let min = min.add_scalar(-margin);
+        let max = max.add_scalar(margin);
+
+        // Поверхность занимает порядка n² ячеек: в среднем несколько треугольников на ячейку
+        let cells_per_axis = ((triangles.len() as f64).sqrt() / 2.).ceil().clamp(1., 64.) as usize;
+        let cell_size = ((max - min) / cells_per_axis as f64).map(|size| size.max(f64::EPSILON));
+        let mut grid = TriangleGrid {
+            min,
+            cell_size,
+            cells_per_axis,
+            cells: vec![Vec::new(); cells_per_axis.pow(3)],
+        };
+
+        for (i, tri) in triangles.iter().enumerate() {
+            let corners = [tri.0, tri.1, tri.2].map(|v| vertices[v].coords);
+            let lo = grid.cell_of(&corners[0].inf(&corners[1]).inf(&corners[2]));
+            let hi = grid.cell_of(&corners[0].sup(&corners[1]).sup(&corners[2]));
+            for x in lo[0]..=hi[0] {
+                for y in lo[1]..=hi[1] {
+                    for z in lo[2]..=hi[2] {
+                        let index = grid.index([x, y, z]);
+                        grid.cells[index].push(i);
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Ячейка, содержащая точку; точки вне сетки относятся к ближайшей ячейке
+    fn cell_of(&self, p: &Vector3<f64>) -> [usize; 3] {
+        let n = self.cells_per_axis;
+        [0, 1, 2].map(|axis| {
+            let cell = ((p[axis] - self.min[axis]) / self.cell_size[axis]).floor();
+            (cell.max(0.) as usize).min(n - 1)
+        })
+    }
+
+    fn index(&self, [x, y, z]: [usize; 3]) -> usize {
+        let n = self.cells_per_axis;
+        (x * n + y) * n + z
+    }
+
+    /// Пересекает ли луч с единичным направлением какой-либо треугольник
+    /// на расстоянии меньше `max_distance`
+    fn any_hit(
+        &self,
+        origin: &Point3<f64>,
+        direction: &Vector3<f64>,
+        max_distance: f64,
+        vertices: &[Point3<f64>],
+        triangles: &[Triangle],
+    ) -> bool {
+        let n = self.cells_per_axis as i64;
+        let mut cell = self.cell_of(&origin.coords).map(|c| c as i64);
+
+        // Шаг по ячейкам и расстояния вдоль луча до следующей границы по каждой оси
+        let mut step = [0; 3];
+        let mut t_max = [f64::INFINITY; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+        for axis in 0..3 {
+            let d = direction[axis];
+            if d == 0. {
+                continue;
+            }
+            step[axis] = if d > 0. { 1 } else { -1 };
+            let boundary_cell = cell[axis] + i64::from(d > 0.);
+            let boundary = self.min[axis] + boundary_cell as f64 * self.cell_size[axis];
+            t_max[axis] = (boundary - origin[axis]) / d;
+            t_delta[axis] = self.cell_size[axis] / d.abs();
+        }
+
+        loop {
+            let index = self.index(cell.map(|c| c as usize));
+            let hit = self.cells[index].iter().any(|&i| {
+                let tri = triangles[i];
+                let [a, b, c] = [tri.0, tri.1, tri.2].map(|v| &vertices[v]);
+                ray_triangle_intersect(origin, direction, a, b, c).is_some_and(|t| t < max_distance)
+            });
+            if hit {
+                return true;
+            }
+
+            let axis = (0..3)
+                .min_by(|&a, &b| t_max[a].total_cmp(&t_max[b]))
+                .unwrap();
+            if t_max[axis] > max_distance {
+                return false;
+            }
+            cell[axis] += step[axis];
+            if !(0..n).contains(&cell[axis]) {
+                return false;
+            }
+            t_max[axis] += t_delta[axis];
+        }
+    }
+}
+
+/// Вычисляет затенение окружающим светом для каждой вершины сетки.
+///
+/// Из вершины испускаются лучи в полусферу вокруг нормали, результат - взвешенная
+/// по косинусу доля лучей, не пересекающих сетку: 1 - вершина полностью открыта,
+/// 0 - полностью закрыта. `normals` - внешние нормали граней.
+/// Лучи проверяются только с треугольниками ячеек пространственной сетки на их пути,
+/// вершины обрабатываются параллельно.
+pub fn vertex_ambient_occlusion(
+    vertices: &[Point3<f64>],
+    triangles: &[Triangle],
+    normals: &[Vector4<f64>],
+) -> Vec<f64> {
+    if vertices.is_empty() {
+        return Vec::new();
+    }
+
+    // 1. Нормали вершин как сумма нормалей инцидентных граней
+    let mut vertex_normals: Vec<Vector3<f64>> = vec![Vector3::zeros(); vertices.len()];
+    for (tri, normal) in triangles.iter().zip(normals) {
+        for v in [tri.0, tri.1, tri.2] {
+            vertex_normals[v] += normal.xyz();
+        }
+    }
+
+    // 2. Дальность лучей зависит от размера сетки
+    let (min, max) = vertices
+        .iter()
+        .fold((vertices[0].coords, vertices[0].coords), |(min, max), v| {
+            (min.inf(&v.coords), max.sup(&v.coords))
+        });
+    let mesh_radius = (max - min).norm() / 2.;
+    let max_distance = AO_RADIUS_FACTOR * mesh_radius;
+    let ray_offset = AO_RAY_OFFSET_FACTOR * mesh_radius;
+
+    // 3. Пространственная сетка; начала лучей, смещённые вдоль нормалей, остаются внутри неё
+    let grid = TriangleGrid::new(vertices, triangles, 2. * ray_offset);
+    let directions = hemisphere_directions(AO_SAMPLES);
+
+    vertices
+        .par_iter()
+        .zip(&vertex_normals)
+        .map(|(p, normal)| {
+            let Some(n) = normal.try_normalize(f64::EPSILON) else {
+                return 1.;
+            };
+
+            // Ортонормированный базис полусферы
+            let helper = if n.x.abs() < 0.9 {
+                Vector3::x()
+            } else {
+                Vector3::y()
+            };
+            let u = n.cross(&helper).normalize();
+            let w = n.cross(&u);
+
+            let origin = p + n * ray_offset;
+            let mut total_weight = 0.;
+            let mut open_weight = 0.;
+            for d in &directions {
+                let direction = u * d.x + w * d.y + n * d.z;
+                total_weight += d.z;
+
+                if !grid.any_hit(&origin, &direction, max_distance, vertices, triangles) {
+                    open_weight += d.z;
+                }
+            }
+
+            open_weight / total_weight
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Карта высот с глубокой ямой в точке (-1, 0) и выпуклостью в точке (1, 0)
+    fn pit_and_bump() -> (Vec<Point3<f64>>, Vec<Triangle>, Vec<Vector4<f64>>) {
+        let height = |x: f64, y: f64| {
+            -2. * (-((x + 1.).powi(2) + y * y) / 0.1).exp()
+                + 0.5 * (-((x - 1.).powi(2) + y * y) / 0.1).exp()
+        };
+
+        let n = 17;
+        let step = 4. / (n - 1) as f64;
+        let mut vertices = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                let (x, y) = (-2. + i as f64 * step, -2. + j as f64 * step);
+                vertices.push(Point3::new(x, y, height(x, y)));
+            }
+        }
+
+        let mut triangles = Vec::new();
+        for j in 0..n - 1 {
+            for i in 0..n - 1 {
+                let idx = j * n + i;
+                triangles.push((idx, idx + 1, idx + n + 1));
+                triangles.push((idx, idx + n + 1, idx + n));
+            }
+        }
+
+        let normals = triangles
+            .iter()
+            .map(|tri: &Triangle| {
+                let normal = (vertices[tri.1] - vertices[tri.0])
+                    .cross(&(vertices[tri.2] - vertices[tri.0]))
+                    .normalize();
+                let normal = if normal.z < 0. { -normal } else { normal };
+                normal.to_homogeneous()
+            })
+            .collect();
+
+        (vertices, triangles, normals)
+    }
+
+    fn vertex_at(vertices: &[Point3<f64>], x: f64, y: f64) -> usize {
+        vertices
+            .iter()
+            .position(|v| (v.x - x).abs() < 1e-9 && (v.y - y).abs() < 1e-9)
+            .unwrap()
+    }
+
+    #[test]
+    fn grid_traversal_finds_same_hits_as_brute_force() {
+        let (vertices, triangles, _) = pit_and_bump();
+        let grid = TriangleGrid::new(&vertices, &triangles, 1e-6);
+        let brute_force = |origin: &Point3<f64>, direction: &Vector3<f64>, max_distance: f64| {
+            triangles.iter().any(|tri| {
+                let [a, b, c] = [tri.0, tri.1, tri.2].map(|v| &vertices[v]);
+                ray_triangle_intersect(origin, direction, a, b, c).is_some_and(|t| t < max_distance)
+            })
+        };
+
+        let mut hits = 0;
+        for p in vertices.iter().step_by(7) {
+            let origin = p + Vector3::z() * 1e-3;
+            for d in hemisphere_directions(16) {
+                for direction in [d, Vector3::new(d.x, d.y, -d.z)] {
+                    let expected = brute_force(&origin, &direction, 1.5);
+                    let found = grid.any_hit(&origin, &direction, 1.5, &vertices, &triangles);
+                    assert_eq!(found, expected, "луч из {origin} в направлении {direction}");
+                    hits += usize::from(found);
+                }
+            }
+        }
+        assert!(hits > 0);
+    }
+
+    #[test]
+    fn concavity_is_darker_than_bump() {
+        let (vertices, triangles, normals) = pit_and_bump();
+        let ao = vertex_ambient_occlusion(&vertices, &triangles, &normals);
+
+        let pit = ao[vertex_at(&vertices, -1., 0.)];
+        let bump = ao[vertex_at(&vertices, 1., 0.)];
+
+        assert!(ao.iter().all(|&value| (0.0..=1.0).contains(&value)));
+        assert!(pit < bump, "pit: {}, bump: {}", pit, bump);
+    }
+}
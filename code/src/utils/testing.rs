@@ -0,0 +1,29 @@
+// Вспомогательные средства модульных тестов
+use std::fs;
+use std::path::PathBuf;
+
+/// Временная папка теста, уникальная для процесса и удаляемая по завершении.
+/// Параллельные запуски тестов не мешают друг другу и не оставляют файлов.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// Путь к файлу внутри временной папки
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
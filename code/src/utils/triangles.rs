@@ -1,4 +1,6 @@
-use nalgebra::{Point3, Vector3};
+use crate::objects::model3d::Triangle;
+use nalgebra::{Point3, Vector3, Vector4};
+use std::collections::BTreeMap;
 
 pub fn barycentric(
     p: &Point3<f64>,
@@ -37,6 +39,149 @@ pub fn triangle_area(v1: &Point3<f64>, v2: &Point3<f64>, v3: &Point3<f64>) -> f6
     0.5 * cross_product.norm()
 }
 
+/// Ближайшая к `p` точка треугольника `abc` (включая рёбра и вершины)
+pub fn closest_point_on_triangle(
+    p: &Point3<f64>,
+    a: &Point3<f64>,
+    b: &Point3<f64>,
+    c: &Point3<f64>,
+) -> Point3<f64> {
+    let ab = b - a;
+    let ac = c - a;
+
+    // Области вершин и рёбер определяются по знакам проекций на стороны
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0. && d2 <= 0. {
+        return *a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0. && d4 <= d3 {
+        return *b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0. && d1 >= 0. && d3 <= 0. {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0. && d5 <= d6 {
+        return *c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0. && d2 >= 0. && d6 <= 0. {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    // Проекция попадает внутрь треугольника
+    let denom = 1. / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+/// Каноническое (упорядоченное) представление ребра
+pub fn edge_key(a: usize, b: usize) -> [usize; 2] {
+    if a < b { [a, b] } else { [b, a] }
+}
+
+/// Вычисляет нормаль треугольника, ориентированную так же, как `reference`.
+/// Для вырожденного треугольника возвращает `reference`.
+pub fn oriented_face_normal(
+    vertices: &[Point3<f64>],
+    tri: &Triangle,
+    reference: &Vector4<f64>,
+) -> Vector4<f64> {
+    let normal = (vertices[tri.1] - vertices[tri.0])
+        .cross(&(vertices[tri.2] - vertices[tri.0]))
+        .try_normalize(f64::EPSILON)
+        .map(|n| n.to_homogeneous());
+
+    match normal {
+        Some(n) if n.dot(reference) < 0. => -n,
+        Some(n) => n,
+        None => *reference,
+    }
+}
+
+/// Расстояние вдоль луча до пересечения с треугольником `v0 v1 v2`
+/// (алгоритм Мёллера — Трумбора). Для единичного `dir` это расстояние от `orig`.
+pub fn ray_triangle_intersect(
+    orig: &Point3<f64>,
+    dir: &Vector3<f64>,
+    v0: &Point3<f64>,
+    v1: &Point3<f64>,
+    v2: &Point3<f64>,
+) -> Option<f64> {
+    ray_triangle_intersect_barycentric(orig, dir, v0, v1, v2).map(|(t, _)| t)
+}
+
+/// То же, что ray_triangle_intersect, но вместе с барицентрическими координатами
+/// точки попадания в порядке вершин `v0, v1, v2` (как у barycentric).
+/// Треугольник двусторонний: попадание в заднюю грань тоже учитывается.
+/// Лучи, параллельные плоскости треугольника, и попадания позади `orig` отбрасываются,
+/// точки на рёбрах считаются попаданием.
+pub fn ray_triangle_intersect_barycentric(
+    orig: &Point3<f64>,
+    dir: &Vector3<f64>,
+    v0: &Point3<f64>,
+    v1: &Point3<f64>,
+    v2: &Point3<f64>,
+) -> Option<(f64, Vector3<f64>)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let p = dir.cross(&edge2);
+    // Знак определителя задаёт сторону грани, близость к нулю - параллельность
+    let det = edge1.dot(&p);
+    if det.abs() < f64::EPSILON * edge1.norm() * edge2.norm() * dir.norm() {
+        return None;
+    }
+
+    let inv_det = 1. / det;
+    let s = orig - v0;
+    let u = s.dot(&p) * inv_det;
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let v = dir.dot(&q) * inv_det;
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_det;
+    (t > 0.).then(|| (t, Vector3::new(1. - u - v, u, v)))
+}
+
+/// Находит рёбра, принадлежащие не ровно двум треугольникам (граничные или
+/// нарушающие многообразие). Рёбра возвращаются в упорядоченном виде.
+pub fn non_manifold_edges(triangles: &[Triangle]) -> Vec<(usize, usize)> {
+    let mut usage: BTreeMap<[usize; 2], usize> = BTreeMap::new();
+    for &(a, b, c) in triangles {
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            *usage.entry(edge_key(u, v)).or_default() += 1;
+        }
+    }
+
+    usage
+        .into_iter()
+        .filter(|&(_, count)| count != 2)
+        .map(|([a, b], _)| (a, b))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,8 +191,65 @@ mod tests {
         let a = Point3::new(1.0, 2.0, 0.);
         let b = Point3::new(4.0, 5.0, 0.);
         let c = Point3::new(0., 0., 0.);
-        let p = c.clone();
+        let p = c;
 
         println!("{}", barycentric(&p, &a, &b, &c));
     }
+
+    fn unit_triangle() -> [Point3<f64>; 3] {
+        [
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 0., 0.),
+            Point3::new(0., 1., 0.),
+        ]
+    }
+
+    #[test]
+    fn ray_hits_triangle_at_distance() {
+        let [v0, v1, v2] = unit_triangle();
+        let orig = Point3::new(0.25, 0.25, 2.);
+        let dir = -Vector3::z();
+
+        let (t, bary) = ray_triangle_intersect_barycentric(&orig, &dir, &v0, &v1, &v2).unwrap();
+        assert!((t - 2.).abs() < 1e-12);
+        assert!((bary - Vector3::new(0.5, 0.25, 0.25)).norm() < 1e-12);
+
+        // Задняя грань тоже даёт попадание
+        let back = Point3::new(0.25, 0.25, -2.);
+        let t = ray_triangle_intersect(&back, &Vector3::z(), &v0, &v1, &v2).unwrap();
+        assert!((t - 2.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ray_misses_triangle() {
+        let [v0, v1, v2] = unit_triangle();
+        let dir = -Vector3::z();
+        let outside = Point3::new(0.75, 0.75, 2.);
+        assert_eq!(ray_triangle_intersect(&outside, &dir, &v0, &v1, &v2), None);
+
+        // Треугольник позади начала луча
+        let behind = Point3::new(0.25, 0.25, -2.);
+        assert_eq!(ray_triangle_intersect(&behind, &dir, &v0, &v1, &v2), None);
+    }
+
+    #[test]
+    fn grazing_ray_hits_edge() {
+        let [v0, v1, v2] = unit_triangle();
+        let orig = Point3::new(0.5, 0.5, 1.);
+        let dir = -Vector3::z();
+
+        let (t, bary) = ray_triangle_intersect_barycentric(&orig, &dir, &v0, &v1, &v2).unwrap();
+        assert!((t - 1.).abs() < 1e-12);
+        assert!(bary.x.abs() < 1e-12);
+    }
+
+    #[test]
+    fn ray_parallel_to_triangle_misses() {
+        let [v0, v1, v2] = unit_triangle();
+        let in_plane = Point3::new(-1., 0.25, 0.);
+        let above = Point3::new(-1., 0.25, 1.);
+        let dir = Vector3::x();
+        assert_eq!(ray_triangle_intersect(&in_plane, &dir, &v0, &v1, &v2), None);
+        assert_eq!(ray_triangle_intersect(&above, &dir, &v0, &v1, &v2), None);
+    }
 }
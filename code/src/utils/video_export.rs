@@ -0,0 +1,169 @@
+// Запись видео: кадры передаются внешнему ffmpeg через stdin в виде сырых RGB данных
+use image::RgbImage;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Команда ffmpeg, читающая из stdin кадры `width`x`height` в формате rgb24
+/// с частотой `fps` и кодирующая их в `path`. Кодек выбирается по расширению:
+/// VP9 для .webm, иначе H.264.
+pub fn ffmpeg_command(path: &Path, width: u32, height: u32, fps: u32) -> Command {
+    let webm = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("webm"));
+    let codec = if webm { "libvpx-vp9" } else { "libx264" };
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-y", "-loglevel", "error"])
+        .args(["-f", "rawvideo", "-pix_fmt", "rgb24"])
+        .arg("-s")
+        .arg(format!("{}x{}", width, height))
+        .arg("-r")
+        .arg(fps.to_string())
+        .args(["-i", "-"])
+        // yuv420p требует чётных размеров кадра
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+        .args(["-c:v", codec, "-pix_fmt", "yuv420p"])
+        .arg(path);
+    command
+}
+
+/// Запущенный процесс ffmpeg, принимающий кадры одного размера.
+/// Если кодирование не завершено через `finish`, процесс при удалении
+/// останавливается, а недописанный файл удаляется.
+pub struct VideoEncoder {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    finished: bool,
+}
+
+impl VideoEncoder {
+    pub fn start(path: &Path, width: u32, height: u32, fps: u32) -> Result<Self, String> {
+        let command = ffmpeg_command(path, width, height, fps);
+        Self::spawn(command, path, width, height)
+    }
+
+    /// Запускает кодировщик `command`, пишущий видео в `path`
+    fn spawn(mut command: Command, path: &Path, width: u32, height: u32) -> Result<Self, String> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => "ffmpeg не найден в PATH".to_string(),
+                _ => format!("Не удалось запустить ffmpeg: {}", e),
+            })?;
+        let stdin = child.stdin.take();
+
+        Ok(Self {
+            child,
+            stdin,
+            path: path.to_path_buf(),
+            width,
+            height,
+            finished: false,
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &RgbImage) -> Result<(), String> {
+        if frame.dimensions() != (self.width, self.height) {
+            return Err(format!(
+                "Размер кадра {:?} не совпадает с размером видео {}x{}",
+                frame.dimensions(),
+                self.width,
+                self.height
+            ));
+        }
+        let stdin = self.stdin.as_mut().ok_or("Поток ffmpeg закрыт")?;
+        stdin
+            .write_all(frame.as_raw())
+            .map_err(|e| format!("ffmpeg прервал запись: {}", e))
+    }
+
+    /// Закрывает поток кадров и дожидается завершения кодирования.
+    /// При ошибке ffmpeg недописанный файл удаляется.
+    pub fn finish(mut self) -> Result<(), String> {
+        drop(self.stdin.take());
+        let status = self.child.wait();
+        self.finished = true;
+        let result = match status {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => Err(format!("ffmpeg завершился с ошибкой ({})", status)),
+            Err(e) => Err(e.to_string()),
+        };
+        let _ = fs::remove_file(&self.path);
+        result
+    }
+}
+
+impl Drop for VideoEncoder {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // Экспорт прерван: процесс не должен остаться зомби, а файл — недописанным
+        drop(self.stdin.take());
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn value_after(args: &[String], flag: &str) -> String {
+        let i = args.iter().position(|arg| arg == flag).unwrap();
+        args[i + 1].clone()
+    }
+
+    #[test]
+    fn command_carries_frame_size_and_fps() {
+        let command = ffmpeg_command(Path::new("out.mp4"), 640, 360, 24);
+        let mp4 = args(&command);
+
+        assert_eq!(command.get_program(), "ffmpeg");
+        assert_eq!(value_after(&mp4, "-s"), "640x360");
+        assert_eq!(value_after(&mp4, "-r"), "24");
+        assert_eq!(value_after(&mp4, "-i"), "-");
+        assert_eq!(value_after(&mp4, "-c:v"), "libx264");
+        assert_eq!(mp4.last().unwrap(), "out.mp4");
+
+        let webm = ffmpeg_command(Path::new("out.webm"), 640, 360, 24);
+        assert_eq!(value_after(&args(&webm), "-c:v"), "libvpx-vp9");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dropped_encoder_stops_process_and_removes_partial_file() {
+        use crate::utils::testing::TempDir;
+
+        let dir = TempDir::new("video_encoder_drop");
+        let path = dir.join("partial.mp4");
+        fs::write(&path, b"partial").unwrap();
+
+        // cat вместо ffmpeg: читает кадры, пока не закрыт stdin
+        let mut encoder = VideoEncoder::spawn(Command::new("cat"), &path, 2, 2).unwrap();
+        encoder.write_frame(&RgbImage::new(2, 2)).unwrap();
+        let pid = encoder.child.id();
+        drop(encoder);
+
+        assert!(!path.exists());
+        // Процесс дождались, и его больше нет в таблице процессов
+        assert!(!Path::new(&format!("/proc/{}", pid)).exists());
+    }
+}
@@ -0,0 +1,40 @@
+// Общие помощники интеграционных тестов
+// Не каждый тестовый файл пользуется всеми помощниками
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Путь к модели из папки models
+pub fn model_path(name: &str) -> String {
+    format!("{}/models/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+/// Временная папка теста, уникальная для процесса и удаляемая по завершении
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Путь к файлу внутри временной папки
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use code::objects::model3d::InteractiveModel;
+use code::objects::triangle_mesh::TriangleMesh;
+use code::render::Renderer;
+use code::render::z_buffer::ZBufferPerformer;
+use code::scene::Scene;
+use common::model_path;
+use image::RgbImage;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Системный аллокатор, считающий выделения памяти
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn frames_after_warmup_do_not_allocate() {
+    let mesh = TriangleMesh::from_obj(&model_path("apple2.obj")).unwrap();
+    let object: Rc<RefCell<dyn InteractiveModel>> = Rc::new(RefCell::new(mesh));
+    let mut scene = Scene::new(1.);
+    scene.object = Some(object);
+
+    let mut renderer = ZBufferPerformer::new(256, 256);
+    let mut image = RgbImage::new(256, 256);
+    for _ in 0..2 {
+        renderer.create_frame_mut(&mut image, &scene);
+    }
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    renderer.create_frame_mut(&mut image, &scene);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    assert_eq!(allocations, 0, "выделений памяти за кадр: {allocations}");
+}
@@ -0,0 +1,98 @@
+// Сравнение кадров рендерера с эталонными изображениями из tests/fixtures.
+//
+// Эталоны хранятся в репозитории и перезаписываются только намеренно командой
+//     UPDATE_GOLDEN=1 cargo test --test golden_images
+// Отсутствующий эталон - ошибка теста, а не повод записать новый.
+mod common;
+
+use code::objects::model3d::{InteractiveModel, Model3D};
+use code::objects::morph::Morph;
+use code::objects::triangle_mesh::TriangleMesh;
+use code::render::Renderer;
+use code::render::z_buffer::ZBufferPerformer;
+use code::scene::Scene;
+use common::model_path;
+use image::RgbImage;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const SIZE: u32 = 64;
+/// Допустимое отличие канала пикселя от эталона
+const TOLERANCE: u8 = 2;
+
+fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(format!("{}.png", name))
+}
+
+fn render(object: Rc<RefCell<dyn InteractiveModel>>) -> RgbImage {
+    let mut scene = Scene::new(1.);
+    scene.object = Some(object);
+    ZBufferPerformer::new(SIZE, SIZE).create_frame(SIZE, SIZE, &scene)
+}
+
+/// Максимальное отличие канала и число пикселей, отличающихся сильнее допуска
+fn compare(frame: &RgbImage, reference: &RgbImage) -> (u8, usize) {
+    frame
+        .pixels()
+        .zip(reference.pixels())
+        .map(|(a, b)| (0..3).map(|c| a.0[c].abs_diff(b.0[c])).max().unwrap())
+        .fold((0, 0), |(max, count), diff| {
+            (max.max(diff), count + usize::from(diff > TOLERANCE))
+        })
+}
+
+fn assert_matches_golden(name: &str, frame: &RgbImage) {
+    let path = fixture_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        frame.save(&path).unwrap();
+        eprintln!("Эталон записан: {}", path.display());
+        return;
+    }
+    assert!(
+        path.exists(),
+        "Нет эталона {}; запишите его командой UPDATE_GOLDEN=1 cargo test --test golden_images",
+        path.display()
+    );
+
+    let reference = image::open(&path)
+        .expect("Failed to open reference image")
+        .to_rgb8();
+    assert_eq!(frame.dimensions(), reference.dimensions());
+
+    let (max_diff, differing) = compare(frame, &reference);
+    assert!(
+        max_diff <= TOLERANCE,
+        "{}: максимальное отличие канала {}, пикселей вне допуска: {}",
+        name,
+        max_diff,
+        differing
+    );
+}
+
+#[test]
+fn cube_matches_golden() {
+    let frame = render(Rc::new(RefCell::new(TriangleMesh::cube())));
+    assert_matches_golden("cube", &frame);
+}
+
+#[test]
+fn sphere_matches_golden() {
+    let frame = render(Rc::new(RefCell::new(TriangleMesh::unit_sphere(3))));
+    assert_matches_golden("sphere", &frame);
+}
+
+#[test]
+fn morph_midpoint_matches_golden() {
+    let source = TriangleMesh::from_obj(&model_path("apple2.obj")).unwrap();
+    let target = TriangleMesh::from_obj(&model_path("lemon.obj")).unwrap();
+    let mut morph = Morph::new(source, target).unwrap();
+    morph.update(0.5);
+
+    let frame = render(Rc::new(RefCell::new(morph)));
+    assert_matches_golden("morph_midpoint", &frame);
+}
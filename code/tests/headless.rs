@@ -0,0 +1,98 @@
+mod common;
+
+use common::{TempDir, model_path};
+use std::process::Command;
+
+#[test]
+fn headless_renders_morph_frame() {
+    let tmp = TempDir::new("headless_morph_frame");
+    let out = tmp.join("frame.png");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_code"))
+        .args(["--headless", "--source"])
+        .arg(model_path("apple2.obj"))
+        .arg("--target")
+        .arg(model_path("lemon.obj"))
+        .args(["--phase", "0.5", "--width", "64", "--height", "48", "--out"])
+        .arg(&out)
+        .status()
+        .expect("Failed to run headless binary");
+
+    assert!(status.success());
+
+    let frame = image::open(&out).expect("Failed to open rendered frame");
+    assert_eq!((frame.width(), frame.height()), (64, 48));
+}
+
+#[test]
+fn headless_fails_on_missing_model() {
+    let tmp = TempDir::new("headless_missing_model");
+    let out = tmp.join("frame.png");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_code"))
+        .args(["--headless", "--source", "missing.obj", "--target"])
+        .arg(model_path("lemon.obj"))
+        .arg("--out")
+        .arg(&out)
+        .status()
+        .expect("Failed to run headless binary");
+
+    assert!(!status.success());
+}
+
+#[test]
+fn batch_morph_renders_every_pair() {
+    let tmp = TempDir::new("batch_morph");
+    let dir = tmp.join("models");
+    let out = tmp.join("frames");
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in ["apple2.obj", "lemon.obj"] {
+        std::fs::copy(model_path(name), dir.join(name)).unwrap();
+    }
+
+    let status = Command::new(env!("CARGO_BIN_EXE_code"))
+        .args(["batch-morph", "--dir"])
+        .arg(&dir)
+        .args(["--phase", "0.5", "--width", "64", "--height", "48", "--out"])
+        .arg(&out)
+        .status()
+        .expect("Failed to run batch-morph");
+
+    assert!(status.success());
+
+    let frames: Vec<_> = std::fs::read_dir(&out).unwrap().collect();
+    assert_eq!(frames.len(), 1);
+    assert!(out.join("apple2-lemon.png").exists());
+}
+
+#[test]
+fn batch_morph_fails_when_no_pair_is_rendered() {
+    let tmp = TempDir::new("batch_morph_broken");
+    let dir = tmp.join("models");
+    let out = tmp.join("frames");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(model_path("apple2.obj"), dir.join("apple2.obj")).unwrap();
+    std::fs::write(dir.join("broken.obj"), "v 1 2 не_число\nf 1 2 3\n").unwrap();
+
+    let batch = |dir: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_code"))
+            .args(["batch-morph", "--dir"])
+            .arg(dir)
+            .args(["--width", "64", "--height", "48", "--out"])
+            .arg(&out)
+            .output()
+            .expect("Failed to run batch-morph")
+    };
+
+    // Единственная пара не собралась из-за неразбираемой модели
+    let output = batch(&dir);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("не загружено моделей: 1"), "{stdout}");
+
+    // С ещё одной моделью хотя бы одна пара строится, и пакет считается успешным
+    std::fs::copy(model_path("lemon.obj"), dir.join("lemon.obj")).unwrap();
+    let output = batch(&dir);
+    assert!(output.status.success());
+    assert!(out.join("apple2-lemon.png").exists());
+}
@@ -0,0 +1,83 @@
+// Сквозная проверка построения морфинга на маленьких сетках: параметризация,
+// суперсетка и интерполяция вершин по стадиям
+mod common;
+
+use code::objects::model3d::Model3D;
+use code::objects::morph::Morph;
+use code::objects::triangle_mesh::TriangleMesh;
+use code::utils::triangles::closest_point_on_triangle;
+use common::model_path;
+use nalgebra::Point3;
+
+const PHASES: [f64; 5] = [0., 0.25, 0.5, 0.75, 1.];
+/// Допустимое удаление вершины морфинга от поверхности на крайних стадиях
+const SURFACE_TOLERANCE: f64 = 1e-6;
+
+/// Расстояние от точки до ближайшего треугольника сетки
+fn distance_to_surface(p: &Point3<f64>, mesh: &TriangleMesh) -> f64 {
+    let vertices = mesh.vertices_world();
+    mesh.triangles()
+        .iter()
+        .map(|tri| {
+            let closest =
+                closest_point_on_triangle(p, &vertices[tri.0], &vertices[tri.1], &vertices[tri.2]);
+            (p - closest).norm()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn assert_on_surface(morph: &Morph, mesh: &TriangleMesh, phase: f64) {
+    for v in morph.vertices() {
+        let distance = distance_to_surface(v, mesh);
+        assert!(
+            distance < SURFACE_TOLERANCE,
+            "на стадии {phase} вершина {v} удалена от поверхности на {distance}"
+        );
+    }
+}
+
+fn check_pipeline(source: TriangleMesh, target: TriangleMesh) {
+    let mut morph = Morph::new(source.clone(), target.clone()).unwrap();
+    let vertex_count = morph.vertices().len();
+    let triangle_count = morph.triangles().len();
+    assert!(vertex_count > 0 && triangle_count > 0);
+
+    for phase in PHASES {
+        morph.update(phase);
+
+        assert_eq!(morph.vertices().len(), vertex_count);
+        assert_eq!(morph.triangles().len(), triangle_count);
+        assert!(
+            morph
+                .vertices()
+                .iter()
+                .all(|v| v.iter().all(|c| c.is_finite())),
+            "на стадии {phase} появились NaN или бесконечные координаты"
+        );
+        assert!(
+            morph
+                .normals()
+                .iter()
+                .all(|n| n.iter().all(|c| c.is_finite())),
+            "на стадии {phase} появились NaN в нормалях"
+        );
+
+        if phase == 0. {
+            assert_on_surface(&morph, &source, phase);
+        }
+        if phase == 1. {
+            assert_on_surface(&morph, &target, phase);
+        }
+    }
+}
+
+#[test]
+fn sphere_to_bundled_cube() {
+    let cube = TriangleMesh::from_obj(&model_path("cube.obj")).unwrap();
+    check_pipeline(TriangleMesh::unit_sphere(1), cube);
+}
+
+#[test]
+fn cube_to_sphere() {
+    check_pipeline(TriangleMesh::cube(), TriangleMesh::unit_sphere(2));
+}